@@ -0,0 +1,34 @@
+//! Benchmarks iterating over a long stream of jamos, run with
+//! `cargo bench`.
+//!
+//! The program is a binary, so the modules being benchmarked are
+//! included directly, without most of what uses them (or their tests).
+#![allow(dead_code, unused_imports)]
+
+#[path = "../src/hangul.rs"]
+mod hangul;
+#[path = "../src/jamo_stream.rs"]
+mod jamo_stream;
+
+use std::hint::black_box;
+
+use criterion::{Criterion, criterion_group, criterion_main};
+
+use crate::jamo_stream::JamoStream;
+
+fn bench_jamo_stream(c: &mut Criterion) {
+    let text = "동해 물과 백두산이 마르고 닳도록, 하느님이 보우하사 우리나라 만세!\n".repeat(200);
+    c.bench_function("iterate jamo stream", |b| {
+        b.iter(|| JamoStream::from_hangul_syllables(black_box(&text)).count())
+    });
+    c.bench_function("read syllables from jamo stream", |b| {
+        b.iter(|| {
+            JamoStream::from_hangul_syllables(black_box(&text))
+                .filter(|jamo| jamo.next_syllable.is_some() || jamo.curr_syllable.is_some())
+                .count()
+        })
+    });
+}
+
+criterion_group!(benches, bench_jamo_stream);
+criterion_main!(benches);
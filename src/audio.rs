@@ -0,0 +1,10 @@
+/// Sound output, shared by everything that plays audio.
+use anyhow::{Result, anyhow};
+use rodio::{OutputStream, OutputStreamHandle};
+
+/// Opens the default audio output device, with an error that says so
+/// if there isn't one, rather than just what the audio library said.
+pub fn open_output() -> Result<(OutputStream, OutputStreamHandle)> {
+    OutputStream::try_default()
+        .map_err(|err| anyhow!("Unable to open default audio output device: {err}"))
+}
@@ -0,0 +1,144 @@
+/// A practice mode that dictates words using text-to-speech,
+/// and checks that the user typed what they heard.
+use std::fs::read_to_string;
+
+use anyhow::{Result, anyhow};
+use rand::{seq::SliceRandom, thread_rng};
+use rustyline::error::ReadlineError;
+use tts::Tts;
+
+use crate::{
+    hangul::is_all_modern_hangul,
+    introductions::{get_hangul, get_hangul_diff, get_vocabulary},
+    pronunciation::fold_confusable_vowels_in_syllables,
+    tts::{FEMALE_VOICES, create_tts_speaker},
+};
+
+#[derive(Debug, PartialEq)]
+enum Grade {
+    /// The answer exactly matches the word.
+    Correct,
+    /// The answer only differs from the word in vowels that
+    /// sound the same.
+    Close,
+    Incorrect,
+}
+
+fn grade(word: &str, answer: &str) -> Grade {
+    let word = get_hangul(word);
+    let answer = get_hangul(answer);
+    if word == answer {
+        Grade::Correct
+    } else if fold_confusable_vowels_in_syllables(&word)
+        == fold_confusable_vowels_in_syllables(&answer)
+    {
+        Grade::Close
+    } else {
+        Grade::Incorrect
+    }
+}
+
+fn load_words(wordlist: &Option<String>) -> Result<Vec<String>> {
+    let words: Vec<String> = match wordlist {
+        Some(filename) => read_to_string(filename)?
+            .lines()
+            .filter(|line| !get_hangul(line).is_empty())
+            .map(|line| line.trim().to_owned())
+            .collect(),
+        None => get_vocabulary()
+            .into_iter()
+            .map(|word| word.to_owned())
+            .collect(),
+    };
+    if words.is_empty() {
+        return Err(anyhow!("Wordlist contains no Hangul words!"));
+    }
+    // Archaic Hangul can't be typed on a modern keyboard, so it would be
+    // impossible to answer correctly.
+    if let Some(word) = words
+        .iter()
+        .find(|word| !is_all_modern_hangul(&get_hangul(word), false))
+    {
+        return Err(anyhow!("Wordlist contains non-modern Hangul: {word}"));
+    }
+    Ok(words)
+}
+
+/// Runs the dictation practice mode until the user exits.
+///
+/// If `fold_confusables` is true, answers that only differ from
+/// the word in vowels that sound the same are counted as correct.
+pub fn run_dictation(
+    wordlist: &Option<String>,
+    rate: Option<f32>,
+    fold_confusables: bool,
+    repeat_command: &str,
+) -> Result<()> {
+    let words = load_words(wordlist)?;
+    let Some(mut speaker) =
+        create_tts_speaker(Tts::default().ok(), "".to_owned(), &FEMALE_VOICES, rate)
+    else {
+        return Err(anyhow!("Dictation requires a Korean TTS voice!"));
+    };
+    let mut rl = rustyline::DefaultEditor::new()?;
+    let mut rng = thread_rng();
+    let mut score = 0;
+    let mut total = 0;
+
+    println!("TYPE THE WORD YOU HEAR.");
+    println!("\nTo hear it again, type '{repeat_command}'. Press Ctrl-C to exit.\n");
+
+    'words: loop {
+        let word = words.choose(&mut rng).unwrap();
+        loop {
+            speaker.say(word)?;
+            let answer = match rl.readline("> ") {
+                Ok(answer) => answer,
+                Err(ReadlineError::Interrupted | ReadlineError::Eof) => break 'words,
+                Err(err) => return Err(err.into()),
+            };
+            if get_hangul(&answer) == get_hangul(repeat_command) {
+                continue;
+            }
+            total += 1;
+            match grade(word, &answer) {
+                Grade::Correct => {
+                    score += 1;
+                    println!("CORRECT!");
+                }
+                Grade::Close => {
+                    if fold_confusables {
+                        score += 1;
+                    }
+                    println!("CLOSE! It was {word}.");
+                }
+                Grade::Incorrect => {
+                    let expected = get_hangul(word);
+                    let received = get_hangul(&answer);
+                    println!("INCORRECT!");
+                    println!("Expected: {expected}");
+                    println!("Received: {received}");
+                    println!("          {}", get_hangul_diff(&expected, &received));
+                }
+            }
+            println!("Score: {score}/{total}\n");
+            break;
+        }
+    }
+
+    println!("\nFinal score: {score}/{total}");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dictation::{Grade, grade};
+
+    #[test]
+    fn test_grade_works() {
+        assert_eq!(grade("학생", "학생"), Grade::Correct);
+        assert_eq!(grade("학생", " 학생! "), Grade::Correct);
+        assert_eq!(grade("학생", "학셍"), Grade::Close);
+        assert_eq!(grade("학생", "학상"), Grade::Incorrect);
+    }
+}
@@ -0,0 +1,90 @@
+/// The special characters that the player draws its UI with, so that
+/// terminals whose fonts lack them can use plain ASCII instead.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Glyphs {
+    pub paused: &'static str,
+    pub playing: &'static str,
+    /// Repeated to draw a horizontal line across the terminal.
+    pub horizontal_line: char,
+    /// A fully filled-in cell of the progress bar.
+    pub progress_full: char,
+    /// Cells of the progress bar that are filled in by eighths, from
+    /// zero to seven of them.
+    pub progress_partial: [char; 8],
+    pub up: &'static str,
+    pub down: &'static str,
+    pub left: &'static str,
+    pub right: &'static str,
+    /// Separates a word from what it becomes when pronounced.
+    pub arrow: &'static str,
+    /// Ends text that was cut off to fit.
+    pub ellipsis: &'static str,
+}
+
+impl Glyphs {
+    pub const UNICODE: Glyphs = Glyphs {
+        paused: "⏸︎",
+        playing: "⏵︎",
+        horizontal_line: '⎯',
+        progress_full: '█',
+        progress_partial: [' ', '▏', '▎', '▍', '▌', '▋', '▊', '▉'],
+        up: "↑",
+        down: "↓",
+        left: "←",
+        right: "→",
+        arrow: "→",
+        ellipsis: "…",
+    };
+
+    pub const ASCII: Glyphs = Glyphs {
+        paused: "||",
+        playing: ">",
+        horizontal_line: '-',
+        progress_full: '#',
+        progress_partial: [' ', ' ', ' ', ' ', '=', '=', '=', '='],
+        up: "Up",
+        down: "Down",
+        left: "Left",
+        right: "Right",
+        arrow: "->",
+        ellipsis: "...",
+    };
+
+    /// Returns the ASCII glyphs if `ascii` is true, or the Unicode ones
+    /// otherwise.
+    pub fn new(ascii: bool) -> Self {
+        if ascii {
+            Glyphs::ASCII
+        } else {
+            Glyphs::UNICODE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::glyphs::Glyphs;
+
+    #[test]
+    fn test_ascii_glyphs_are_ascii() {
+        let glyphs = Glyphs::new(true);
+        let strs = [
+            glyphs.paused,
+            glyphs.playing,
+            glyphs.up,
+            glyphs.down,
+            glyphs.left,
+            glyphs.right,
+            glyphs.arrow,
+            glyphs.ellipsis,
+        ];
+        assert!(strs.iter().all(|value| value.is_ascii()));
+        let chars = [glyphs.horizontal_line, glyphs.progress_full];
+        assert!(
+            chars
+                .iter()
+                .chain(&glyphs.progress_partial)
+                .all(char::is_ascii)
+        );
+    }
+}
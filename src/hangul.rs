@@ -1,10 +1,17 @@
+use unicode_normalization::UnicodeNormalization;
+
 #[derive(Debug, PartialEq, Copy, Clone)]
 pub enum HangulCharClass {
     CompatibilityJamo,
     JamoExtendedA,
     JamoExtendedB,
+    /// Halfwidth Hangul, sometimes found in older text.
+    HalfwidthJamo,
     Jamo,
     Syllables,
+    /// The Middle Korean tone marks (방점), which follow the syllable
+    /// they apply to.
+    ToneMark,
     None,
 }
 
@@ -16,6 +23,8 @@ impl From<char> for HangulCharClass {
             '\u{3130}'..='\u{318f}' => HangulCharClass::CompatibilityJamo,
             '\u{a960}'..='\u{a97f}' => HangulCharClass::JamoExtendedA,
             '\u{d7b0}'..='\u{d7ff}' => HangulCharClass::JamoExtendedB,
+            '\u{ffa0}'..='\u{ffdc}' => HangulCharClass::HalfwidthJamo,
+            '\u{302e}'..='\u{302f}' => HangulCharClass::ToneMark,
             _ => HangulCharClass::None,
         }
     }
@@ -44,6 +53,39 @@ impl HangulCharClass {
     }
 }
 
+/// A piece of a string, as returned by `split_into_tokens`.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum Token<'a> {
+    /// A single precomposed Hangul syllable.
+    Syllable(char),
+    /// A run of anything other than precomposed Hangul syllables.
+    Other(&'a str),
+}
+
+/// Splits the given string into its individual Hangul syllables,
+/// keeping each run of everything else together as a single token.
+///
+/// Unlike `HangulCharClass::split`, consecutive syllables are not
+/// grouped together.
+pub fn split_into_tokens(value: &str) -> Vec<Token<'_>> {
+    let mut result = vec![];
+    let mut other_start: Option<usize> = None;
+    for (idx, char) in value.char_indices() {
+        if HangulCharClass::from(char) == HangulCharClass::Syllables {
+            if let Some(start_idx) = other_start.take() {
+                result.push(Token::Other(&value[start_idx..idx]));
+            }
+            result.push(Token::Syllable(char));
+        } else if other_start.is_none() {
+            other_start = Some(idx);
+        }
+    }
+    if let Some(start_idx) = other_start {
+        result.push(Token::Other(&value[start_idx..]));
+    }
+    result
+}
+
 /// Composes the given Hangul jamos into a single Hangul syllable.
 ///
 /// If any of the characters are not a Hangul jamo, returns
@@ -86,6 +128,20 @@ pub fn compose_hangul_jamos_to_syllable<T: Iterator<Item = char>>(mut chars: T)
     }
 }
 
+/// Like `compose_hangul_jamos_to_syllable`, but if the first jamo is
+/// a vowel, the silent initial ㅇ is inserted before it, e.g. "ᅡᆫ"
+/// composes to "안".
+pub fn compose_hangul_jamos_to_syllable_lenient<T: Iterator<Item = char>>(
+    chars: T,
+) -> Option<char> {
+    let mut chars = chars.peekable();
+    if chars.peek().is_some_and(|&ch| is_medial_jamo(ch)) {
+        compose_hangul_jamos_to_syllable(std::iter::once('ᄋ').chain(chars))
+    } else {
+        compose_hangul_jamos_to_syllable(chars)
+    }
+}
+
 /// Decomposes the given Hangul syllable into its
 /// composite Hangul jamos.
 ///
@@ -135,6 +191,75 @@ pub fn count_jamos_in_syllable(ch: char) -> usize {
     }
 }
 
+/// Counts how many Hangul syllables are in the given string.
+pub fn count_hangul_syllables(value: &str) -> usize {
+    value
+        .chars()
+        .filter(|&ch| HangulCharClass::from(ch) == HangulCharClass::Syllables)
+        .count()
+}
+
+/// Returns the byte offset and character of each Hangul syllable in
+/// the given string, so that positions in it can be mapped back to
+/// the original text.
+pub fn syllable_offsets(value: &str) -> Vec<(usize, char)> {
+    numbered_chars(value)
+        .filter_map(|(idx, ch, syllable_idx)| syllable_idx.map(|_| (idx, ch)))
+        .collect()
+}
+
+/// Returns the byte offset of each character in the given string,
+/// along with its index among the string's Hangul syllables if it's
+/// one. Anything that numbers syllables, like highlighting the selected
+/// one and describing it, should use this so they can't disagree.
+pub fn numbered_chars(value: &str) -> impl Iterator<Item = (usize, char, Option<usize>)> + '_ {
+    let mut syllable_count = 0;
+    value.char_indices().map(move |(idx, ch)| {
+        if HangulCharClass::from(ch) == HangulCharClass::Syllables {
+            syllable_count += 1;
+            (idx, ch, Some(syllable_count - 1))
+        } else {
+            (idx, ch, None)
+        }
+    })
+}
+
+/// A summary of the characters in a string.
+#[derive(Debug, Default, PartialEq)]
+pub struct HangulCounts {
+    /// Number of Hangul syllables.
+    pub syllables: usize,
+    /// Number of jamos, including the ones that make up syllables.
+    pub jamos: usize,
+    /// Number of characters that aren't Hangul.
+    pub non_hangul: usize,
+    /// The character classes encountered, in the order they first appear.
+    pub classes: Vec<HangulCharClass>,
+}
+
+impl HangulCounts {
+    pub fn new(value: &str) -> Self {
+        let mut counts = HangulCounts::default();
+        for (class, chunk) in HangulCharClass::split(value) {
+            match class {
+                HangulCharClass::Syllables => {
+                    counts.syllables += count_hangul_syllables(chunk);
+                    counts.jamos += chunk.chars().map(count_jamos_in_syllable).sum::<usize>();
+                }
+                HangulCharClass::None => counts.non_hangul += chunk.chars().count(),
+                // Tone marks are neither jamos nor foreign to Hangul, so
+                // they're only reported in `classes`.
+                HangulCharClass::ToneMark => {}
+                _ => counts.jamos += chunk.chars().count(),
+            }
+            if !counts.classes.contains(&class) {
+                counts.classes.push(class);
+            }
+        }
+        counts
+    }
+}
+
 /// Converts a Hangul Jamo to its equivalent
 /// Hangul Compatibility Jamo.
 ///
@@ -212,6 +337,141 @@ pub fn hangul_jamo_to_compat_with_fallback(ch: char) -> char {
     hangul_jamo_to_compat(ch).unwrap_or(ch)
 }
 
+/// Converts a Halfwidth Hangul jamo to its equivalent Hangul
+/// Compatibility Jamo.
+pub fn halfwidth_jamo_to_compat(ch: char) -> Option<char> {
+    // The halfwidth jamo are in the same order as the compatibility
+    // jamo, but the vowels are broken up into a few separate runs.
+    let (halfwidth_start, compat_start) = match ch {
+        '\u{ffa0}' => return Some('\u{3164}'),
+        '\u{ffa1}'..='\u{ffbe}' => ('\u{ffa1}', 'ㄱ'),
+        '\u{ffc2}'..='\u{ffc7}' => ('\u{ffc2}', 'ㅏ'),
+        '\u{ffca}'..='\u{ffcf}' => ('\u{ffca}', 'ㅕ'),
+        '\u{ffd2}'..='\u{ffd7}' => ('\u{ffd2}', 'ㅛ'),
+        '\u{ffda}'..='\u{ffdc}' => ('\u{ffda}', 'ㅡ'),
+        _ => return None,
+    };
+    char::from_u32(compat_start as u32 + (ch as u32 - halfwidth_start as u32))
+}
+
+/// Returns the initial and final Hangul Jamo forms of the given
+/// Hangul Compatibility Jamo consonant. Some consonants only exist
+/// in one of the two forms.
+fn compat_consonant_to_jamos(ch: char) -> Option<(Option<char>, Option<char>)> {
+    match ch {
+        'ㄱ' => Some((Some('ᄀ'), Some('ᆨ'))),
+        'ㄲ' => Some((Some('ᄁ'), Some('ᆩ'))),
+        'ㄳ' => Some((None, Some('ᆪ'))),
+        'ㄴ' => Some((Some('ᄂ'), Some('ᆫ'))),
+        'ㄵ' => Some((None, Some('ᆬ'))),
+        'ㄶ' => Some((None, Some('ᆭ'))),
+        'ㄷ' => Some((Some('ᄃ'), Some('ᆮ'))),
+        'ㄸ' => Some((Some('ᄄ'), None)),
+        'ㄹ' => Some((Some('ᄅ'), Some('ᆯ'))),
+        'ㄺ' => Some((None, Some('ᆰ'))),
+        'ㄻ' => Some((None, Some('ᆱ'))),
+        'ㄼ' => Some((None, Some('ᆲ'))),
+        'ㄽ' => Some((None, Some('ᆳ'))),
+        'ㄾ' => Some((None, Some('ᆴ'))),
+        'ㄿ' => Some((None, Some('ᆵ'))),
+        'ㅀ' => Some((None, Some('ᆶ'))),
+        'ㅁ' => Some((Some('ᄆ'), Some('ᆷ'))),
+        'ㅂ' => Some((Some('ᄇ'), Some('ᆸ'))),
+        'ㅃ' => Some((Some('ᄈ'), None)),
+        'ㅄ' => Some((None, Some('ᆹ'))),
+        'ㅅ' => Some((Some('ᄉ'), Some('ᆺ'))),
+        'ㅆ' => Some((Some('ᄊ'), Some('ᆻ'))),
+        'ㅇ' => Some((Some('ᄋ'), Some('ᆼ'))),
+        'ㅈ' => Some((Some('ᄌ'), Some('ᆽ'))),
+        'ㅉ' => Some((Some('ᄍ'), None)),
+        'ㅊ' => Some((Some('ᄎ'), Some('ᆾ'))),
+        'ㅋ' => Some((Some('ᄏ'), Some('ᆿ'))),
+        'ㅌ' => Some((Some('ᄐ'), Some('ᇀ'))),
+        'ㅍ' => Some((Some('ᄑ'), Some('ᇁ'))),
+        'ㅎ' => Some((Some('ᄒ'), Some('ᇂ'))),
+        _ => None,
+    }
+}
+
+/// Returns the medial Hangul Jamo form of the given Hangul
+/// Compatibility Jamo vowel.
+fn compat_vowel_to_jamo(ch: char) -> Option<char> {
+    match ch {
+        // The vowels are in the same order in both blocks.
+        'ㅏ'..='ㅣ' => char::from_u32('ᅡ' as u32 + (ch as u32 - 'ㅏ' as u32)),
+        _ => None,
+    }
+}
+
+/// Converts any Halfwidth Hangul jamos in the given string into
+/// Hangul jamos, so they can be analyzed like any other Hangul.
+///
+/// A consonant is converted to a final if it follows a vowel and
+/// isn't followed by one; otherwise it's converted to an initial.
+pub fn halfwidth_jamos_to_conjoining<T: AsRef<str>>(value: T) -> String {
+    let chars: Vec<char> = value.as_ref().chars().collect();
+    let compat_at = |idx: usize| chars.get(idx).and_then(|&ch| halfwidth_jamo_to_compat(ch));
+    let mut result = String::with_capacity(value.as_ref().len());
+    let mut follows_vowel = false;
+
+    for (idx, &ch) in chars.iter().enumerate() {
+        let compat = compat_at(idx);
+        if let Some(medial) = compat.and_then(compat_vowel_to_jamo) {
+            result.push(medial);
+            follows_vowel = true;
+            continue;
+        }
+        if let Some((initial, final_)) = compat.and_then(compat_consonant_to_jamos) {
+            let precedes_vowel = compat_at(idx + 1).and_then(compat_vowel_to_jamo).is_some();
+            let jamo = match (initial, final_) {
+                (_, Some(final_)) if follows_vowel && !precedes_vowel => final_,
+                (Some(initial), _) => initial,
+                (None, Some(final_)) => final_,
+                (None, None) => ch,
+            };
+            result.push(jamo);
+        } else {
+            result.push(ch);
+        }
+        follows_vowel = false;
+    }
+
+    result
+}
+
+/// Returns a string for displaying the given Hangul Jamo.
+///
+/// Normally this is its equivalent Hangul Compatibility Jamo, but
+/// that conversion is lossy about the initial/final distinction, so
+/// if `raw` is true, the jamo itself is returned along with its
+/// codepoint, e.g. "ᄋ (U+110B)".
+pub fn display_jamo(ch: char, raw: bool) -> String {
+    if raw {
+        format!("{ch} (U+{:04X})", ch as u32)
+    } else {
+        hangul_jamo_to_compat_with_fallback(ch).to_string()
+    }
+}
+
+/// Returns the jamos of the given Hangul syllable as Hangul
+/// Compatibility Jamo, e.g. "ㅎㅏㄴ" for "한".
+///
+/// Unlike the conjoining jamos that `decompose_all_hangul_syllables`
+/// produces, these display cleanly next to each other, which makes
+/// them a better fit for lists and menus.
+///
+/// If the character is not a Hangul syllable, returns None.
+pub fn syllable_to_compat_jamo_string(ch: char) -> Option<String> {
+    let (initial_ch, medial_ch, maybe_final_ch) = decompose_hangul_syllable_to_jamos(ch)?;
+    Some(
+        [initial_ch, medial_ch]
+            .into_iter()
+            .chain(maybe_final_ch)
+            .map(hangul_jamo_to_compat_with_fallback)
+            .collect(),
+    )
+}
+
 fn hangul_syllable_to_jamos(ch: char) -> Option<String> {
     if let Some((initial_ch, medial_ch, maybe_final_ch)) = decompose_hangul_syllable_to_jamos(ch) {
         if let Some(final_ch) = maybe_final_ch {
@@ -231,6 +491,11 @@ fn is_initial_jamo(ch: char) -> bool {
     }
 }
 
+/// Whether the given character is a modern medial (vowel) jamo.
+pub fn is_medial_jamo(ch: char) -> bool {
+    matches!(ch, '\u{1161}'..='\u{1175}')
+}
+
 /// Converts any Hangul jamos in the given string into Hangul syllables.
 pub fn compose_all_hangul_jamos<T: AsRef<str>>(value: T) -> String {
     let str = value.as_ref();
@@ -238,14 +503,24 @@ pub fn compose_all_hangul_jamos<T: AsRef<str>>(value: T) -> String {
     let mut curr_syllable: Vec<char> = Vec::with_capacity(3);
 
     fn push_curr_syllable(result: &mut String, curr_syllable: &mut Vec<char>) {
-        if let Some(syllable) = compose_hangul_jamos_to_syllable(curr_syllable.iter().cloned()) {
+        // A vowel without an initial is taken to have the silent one,
+        // rather than being dropped.
+        if let Some(syllable) =
+            compose_hangul_jamos_to_syllable_lenient(curr_syllable.iter().cloned())
+        {
             result.push(syllable);
         }
         curr_syllable.clear();
     }
 
     for ch in str.chars() {
-        if HangulCharClass::from(ch) == HangulCharClass::Jamo {
+        if ch == '\u{1160}' {
+            // The medial filler just fills the vowel's slot.
+            continue;
+        } else if ch == '\u{115f}' {
+            // The initial filler starts a syllable that has no initial.
+            push_curr_syllable(&mut result, &mut curr_syllable);
+        } else if HangulCharClass::from(ch) == HangulCharClass::Jamo {
             if is_initial_jamo(ch) {
                 push_curr_syllable(&mut result, &mut curr_syllable);
             }
@@ -260,6 +535,24 @@ pub fn compose_all_hangul_jamos<T: AsRef<str>>(value: T) -> String {
     result
 }
 
+/// Normalizes everything in the given string that isn't Hangul to
+/// Unicode NFD, so that e.g. an accented letter compares equal whether
+/// it's precomposed or written with a combining mark.
+///
+/// Hangul is left as it is. NFD would split syllables into the same
+/// conjoining jamos as `decompose_all_hangul_syllables` does (including
+/// compound finals like ᆪ, which it doesn't split any further), but
+/// most of the program expects syllables to stay precomposed.
+pub fn nfd_non_hangul(value: &str) -> String {
+    HangulCharClass::split(value)
+        .into_iter()
+        .map(|(class, chunk)| match class {
+            HangulCharClass::None => chunk.nfd().collect(),
+            _ => chunk.to_owned(),
+        })
+        .collect()
+}
+
 /// Converts any Hangul syllables in the given string into
 /// Hangul jamos.
 pub fn decompose_all_hangul_syllables<T: AsRef<str>>(value: T) -> String {
@@ -277,13 +570,224 @@ pub fn decompose_all_hangul_syllables<T: AsRef<str>>(value: T) -> String {
     result
 }
 
+/// Whether the Hangul in a string is made of precomposed syllables,
+/// conjoining jamos, or both.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum DecompositionState {
+    /// All the Hangul is in precomposed syllables.
+    AllComposed,
+    /// All the Hangul is in conjoining jamos.
+    AllDecomposed,
+    /// There are both precomposed syllables and conjoining jamos.
+    Mixed,
+    /// There are neither precomposed syllables nor conjoining jamos.
+    NoHangul,
+}
+
+/// Detects whether the given string consists of precomposed Hangul
+/// syllables, conjoining jamos, or both.
+///
+/// Functions that expect jamos, like `romanize_decomposed_hangul`,
+/// mishandle any precomposed syllables they're given, so this can be
+/// used to detect strings that need to be normalized first.
+pub fn detect_decomposition_state(value: &str) -> DecompositionState {
+    let mut has_syllables = false;
+    let mut has_jamos = false;
+    for ch in value.chars() {
+        match HangulCharClass::from(ch) {
+            HangulCharClass::Syllables => has_syllables = true,
+            HangulCharClass::Jamo => has_jamos = true,
+            _ => {}
+        }
+    }
+    match (has_syllables, has_jamos) {
+        (true, true) => DecompositionState::Mixed,
+        (true, false) => DecompositionState::AllComposed,
+        (false, true) => DecompositionState::AllDecomposed,
+        (false, false) => DecompositionState::NoHangul,
+    }
+}
+
+/// Whether the given character is a Hangul filler, which stands in for
+/// a jamo that's missing rather than being one itself: the initial and
+/// medial fillers (U+115F and U+1160), which keep an incomplete syllable
+/// together, and the compatibility and halfwidth fillers (U+3164 and
+/// U+FFA0), which are often used as invisible placeholders.
+///
+/// Fillers aren't pronounced, so they're skipped when romanizing or
+/// applying pronunciation rules.
+pub fn is_filler_jamo(ch: char) -> bool {
+    matches!(ch, '\u{115f}' | '\u{1160}' | '\u{3164}' | '\u{ffa0}')
+}
+
+/// Whether the given jamo is the initial ㅇ, which is silent: it only
+/// marks a syllable that starts with a vowel.
+pub fn is_silent_initial(ch: char) -> bool {
+    ch == 'ᄋ'
+}
+
+/// The position a conjoining jamo takes within a syllable.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum JamoRole {
+    Initial,
+    Medial,
+    Final,
+}
+
+/// Returns the role of the given character if it's one of the
+/// (mostly archaic) jamos in the Hangul Jamo Extended-A or
+/// Extended-B blocks.
+///
+/// Extended-A only contains initial consonants, while Extended-B
+/// contains medial vowels followed by final consonants. Unassigned
+/// codepoints in either block return `None`.
+pub fn classify_extended_jamo(ch: char) -> Option<JamoRole> {
+    match ch {
+        '\u{a960}'..='\u{a97c}' => Some(JamoRole::Initial),
+        '\u{d7b0}'..='\u{d7c6}' => Some(JamoRole::Medial),
+        '\u{d7cb}'..='\u{d7fb}' => Some(JamoRole::Final),
+        _ => None,
+    }
+}
+
+/// Returns the name of the given Middle Korean tone mark, or `None` if
+/// it isn't one.
+pub fn tone_mark_name(ch: char) -> Option<&'static str> {
+    match ch {
+        '\u{302e}' => Some("거성 (high tone)"),
+        '\u{302f}' => Some("상성 (rising tone)"),
+        _ => None,
+    }
+}
+
+/// Converts the given string into its canonical decomposed form, in
+/// which every Hangul syllable is replaced with its conjoining jamos.
+///
+/// This is idempotent, so it's safe to call on strings that are already
+/// (partly) decomposed, e.g. before comparing two strings for equality.
+pub fn to_conjoining_jamo<T: AsRef<str>>(value: T) -> String {
+    decompose_all_hangul_syllables(value)
+}
+
+/// Converts the given string into its canonical composed form, in
+/// which every sequence of conjoining jamos is replaced with its
+/// Hangul syllable.
+///
+/// This is idempotent, so it's safe to call on strings that are already
+/// (partly) composed, e.g. before comparing two strings for equality.
+pub fn to_syllables<T: AsRef<str>>(value: T) -> String {
+    compose_all_hangul_jamos(to_conjoining_jamo(value))
+}
+
+/// Returns only the Hangul syllables and jamos in the given string.
+///
+/// If `keep_spaces` is true, words stay separated by a single space,
+/// e.g. "네, 저는" becomes "네 저는"; otherwise all spacing is removed.
+pub fn strip_non_hangul<T: AsRef<str>>(value: T, keep_spaces: bool) -> String {
+    let strip = |value: &str| -> String {
+        HangulCharClass::split(value)
+            .into_iter()
+            .filter(|(class, _)| *class != HangulCharClass::None)
+            .map(|(_, str)| str)
+            .collect()
+    };
+    if keep_spaces {
+        value
+            .as_ref()
+            .split_whitespace()
+            .map(strip)
+            .filter(|word| !word.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ")
+    } else {
+        strip(value.as_ref())
+    }
+}
+
+/// Whether every character of the given string is modern Hangul: a
+/// syllable, a modern conjoining jamo, or a modern compatibility jamo.
+/// If `allow_spaces` is true, whitespace is allowed too.
+///
+/// This is stricter than `HangulCharClass`, which also accepts archaic
+/// jamos, fillers and tone marks.
+pub fn is_all_modern_hangul(value: &str, allow_spaces: bool) -> bool {
+    value.chars().all(|ch| {
+        (allow_spaces && ch.is_whitespace())
+            || HangulCharClass::from(ch) == HangulCharClass::Syllables
+            || crate::jamo_stream::ModernJamo::try_from_char(ch).is_some()
+            || ('ㄱ'..='ㅣ').contains(&ch)
+    })
+}
+
+/// Common particles that can follow a word, along with whether they
+/// only follow syllables with a final consonant (`Some(true)`), only
+/// ones without one (`Some(false)`), or either (`None`). Longer ones
+/// come first, so that e.g. 에서 is matched before 에.
+const PARTICLES: [(&str, Option<bool>); 11] = [
+    ("에서", None),
+    ("은", Some(true)),
+    ("는", Some(false)),
+    ("이", Some(true)),
+    ("가", Some(false)),
+    ("을", Some(true)),
+    ("를", Some(false)),
+    ("에", None),
+    ("의", None),
+    ("도", None),
+    ("만", None),
+];
+
+/// Splits a common particle, like 는 or 에서, off the end of the given
+/// word, returning what's left along with the particle, if any.
+///
+/// This is only a heuristic, since it knows nothing about the words
+/// themselves: it will strip "particles" that are really part of a
+/// word, as in 고양이 or 오만, as long as at least one syllable is
+/// left. It does at least take into account which form of a particle
+/// follows a final consonant, e.g. 책은 but 학교는, so 사람는 is left
+/// alone.
+pub fn strip_trailing_particle(word: &str) -> (String, Option<&'static str>) {
+    for (particle, needs_final) in PARTICLES {
+        let Some(stem) = word.strip_suffix(particle) else {
+            continue;
+        };
+        let Some((_, _, final_ch)) = stem
+            .chars()
+            .last()
+            .and_then(decompose_hangul_syllable_to_jamos)
+        else {
+            continue;
+        };
+        if needs_final.is_none_or(|needs_final| needs_final == final_ch.is_some()) {
+            return (stem.to_owned(), Some(particle));
+        }
+    }
+    (word.to_owned(), None)
+}
+
 #[cfg(test)]
 mod test {
     use crate::hangul::{
-        HangulCharClass, compose_all_hangul_jamos, compose_hangul_jamos_to_syllable,
+        DecompositionState, HangulCharClass, HangulCounts, JamoRole, Token, classify_extended_jamo,
+        compose_all_hangul_jamos, compose_hangul_jamos_to_syllable,
+        compose_hangul_jamos_to_syllable_lenient, count_hangul_syllables,
         decompose_all_hangul_syllables, decompose_hangul_syllable_to_jamos,
+        detect_decomposition_state, display_jamo, halfwidth_jamo_to_compat,
+        halfwidth_jamos_to_conjoining, is_all_modern_hangul, is_filler_jamo, is_silent_initial,
+        nfd_non_hangul, numbered_chars, split_into_tokens, strip_non_hangul,
+        strip_trailing_particle, syllable_offsets, syllable_to_compat_jamo_string,
+        to_conjoining_jamo, to_syllables, tone_mark_name,
     };
 
+    const NORMALIZATION_EXAMPLES: [&str; 6] = [
+        "",
+        "hi there",
+        "이",
+        "\u{110b}\u{1175}",
+        "hi 넋을인 there",
+        "넋을 앉아 읽고",
+    ];
+
     #[test]
     fn test_char_class_works() {
         assert_eq!(HangulCharClass::from('이'), HangulCharClass::Syllables);
@@ -294,6 +798,55 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_syllable_offsets() {
+        assert_eq!(
+            syllable_offsets("hi 안녕, ㅋ하!"),
+            vec![(3, '안'), (6, '녕'), (14, '하')]
+        );
+        assert_eq!(syllable_offsets("hello"), vec![]);
+    }
+
+    #[test]
+    fn test_counts_work() {
+        assert_eq!(count_hangul_syllables("안녕 hi!"), 2);
+        assert_eq!(
+            HangulCounts::new("안녕 hi!"),
+            HangulCounts {
+                syllables: 2,
+                jamos: 6,
+                non_hangul: 4,
+                classes: vec![HangulCharClass::Syllables, HangulCharClass::None],
+            }
+        );
+        assert_eq!(HangulCounts::new(""), HangulCounts::default());
+    }
+
+    #[test]
+    fn test_halfwidth_jamo_works() {
+        assert_eq!(
+            HangulCharClass::from('\u{ffa1}'),
+            HangulCharClass::HalfwidthJamo
+        );
+        assert_eq!(halfwidth_jamo_to_compat('\u{ffa1}'), Some('ㄱ'));
+        assert_eq!(halfwidth_jamo_to_compat('\u{ffbe}'), Some('ㅎ'));
+        assert_eq!(halfwidth_jamo_to_compat('\u{ffc2}'), Some('ㅏ'));
+        assert_eq!(halfwidth_jamo_to_compat('\u{ffdc}'), Some('ㅣ'));
+        assert_eq!(halfwidth_jamo_to_compat('ㄱ'), None);
+    }
+
+    #[test]
+    fn test_halfwidth_jamos_to_conjoining_works() {
+        // ㄱ by itself.
+        assert_eq!(halfwidth_jamos_to_conjoining("\u{ffa1}"), "ᄀ");
+        // ㄱㅏㄱ, which should compose to 각.
+        let converted = halfwidth_jamos_to_conjoining("\u{ffa1}\u{ffc2}\u{ffa1} hi");
+        assert_eq!(compose_all_hangul_jamos(converted), "각 hi");
+        // ㅇㅏㄴㅈㅏ, in which the ㄴ is a final and the ㅈ an initial.
+        let converted = halfwidth_jamos_to_conjoining("\u{ffb7}\u{ffc2}\u{ffa4}\u{ffb8}\u{ffc2}");
+        assert_eq!(compose_all_hangul_jamos(converted), "안자");
+    }
+
     #[test]
     fn test_compose_returns_none_with_non_jamos() {
         assert_eq!(compose_hangul_jamos_to_syllable("h".chars()), None);
@@ -319,6 +872,53 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_compose_lenient_inserts_silent_initial() {
+        assert_eq!(compose_hangul_jamos_to_syllable("ᅡ".chars()), None);
+        assert_eq!(
+            compose_hangul_jamos_to_syllable_lenient("ᅡ".chars()),
+            Some('아')
+        );
+        assert_eq!(
+            compose_hangul_jamos_to_syllable_lenient("ᅡᆫ".chars()),
+            Some('안')
+        );
+        assert_eq!(
+            compose_hangul_jamos_to_syllable_lenient("\u{110b}\u{1175}\u{11ab}".chars()),
+            Some('인')
+        );
+        assert_eq!(compose_hangul_jamos_to_syllable_lenient("h".chars()), None);
+    }
+
+    #[test]
+    fn test_display_jamo_works() {
+        assert_eq!(display_jamo('ᄋ', false), "ㅇ");
+        assert_eq!(display_jamo('ᆼ', false), "ㅇ");
+        assert_eq!(display_jamo('ᄋ', true), "ᄋ (U+110B)");
+        assert_eq!(display_jamo('ᆼ', true), "ᆼ (U+11BC)");
+    }
+
+    #[test]
+    fn test_detect_decomposition_state_works() {
+        assert_eq!(
+            detect_decomposition_state("안녕 hi"),
+            DecompositionState::AllComposed
+        );
+        assert_eq!(
+            detect_decomposition_state(&decompose_all_hangul_syllables("안녕 hi")),
+            DecompositionState::AllDecomposed
+        );
+        assert_eq!(
+            detect_decomposition_state(&format!("안{}", decompose_all_hangul_syllables("녕"))),
+            DecompositionState::Mixed
+        );
+        assert_eq!(
+            detect_decomposition_state("hi ㄱ"),
+            DecompositionState::NoHangul
+        );
+        assert_eq!(detect_decomposition_state(""), DecompositionState::NoHangul);
+    }
+
     #[test]
     fn test_decompose_works() {
         assert_eq!(decompose_hangul_syllable_to_jamos('h'), None);
@@ -353,6 +953,11 @@ mod test {
             compose_all_hangul_jamos("hi 넋을인 there"),
             "hi 넋을인 there".to_owned()
         );
+        // Vowels typed without the silent initial, e.g. as halfwidth ㅏㄴ.
+        assert_eq!(
+            compose_all_hangul_jamos(halfwidth_jamos_to_conjoining("\u{ffc2}\u{ffa4}")),
+            "안"
+        );
     }
 
     #[test]
@@ -372,4 +977,254 @@ mod test {
             ]
         );
     }
+
+    #[test]
+    fn test_normalization_is_idempotent() {
+        for example in NORMALIZATION_EXAMPLES {
+            let decomposed = to_conjoining_jamo(example);
+            assert_eq!(to_conjoining_jamo(&decomposed), decomposed, "{example:?}");
+            let composed = to_syllables(example);
+            assert_eq!(to_syllables(&composed), composed, "{example:?}");
+        }
+    }
+
+    #[test]
+    fn test_normalization_round_trips() {
+        for example in NORMALIZATION_EXAMPLES {
+            let composed = to_syllables(example);
+            let decomposed = to_conjoining_jamo(example);
+            assert_eq!(to_syllables(&decomposed), composed, "{example:?}");
+            assert_eq!(to_conjoining_jamo(&composed), decomposed, "{example:?}");
+        }
+    }
+
+    #[test]
+    fn test_classify_extended_jamo_works() {
+        // HANGUL CHOSEONG TIKEUT-MIEUM
+        assert_eq!(classify_extended_jamo('\u{a960}'), Some(JamoRole::Initial));
+        // HANGUL JUNGSEONG O-YEO
+        assert_eq!(classify_extended_jamo('\u{d7b0}'), Some(JamoRole::Medial));
+        // HANGUL JONGSEONG NIEUN-RIEUL
+        assert_eq!(classify_extended_jamo('\u{d7cb}'), Some(JamoRole::Final));
+        // Unassigned codepoints in the blocks.
+        assert_eq!(classify_extended_jamo('\u{a97d}'), None);
+        assert_eq!(classify_extended_jamo('\u{d7c7}'), None);
+        // Modern jamos aren't in the extended blocks.
+        assert_eq!(classify_extended_jamo('ᄀ'), None);
+        assert_eq!(classify_extended_jamo('a'), None);
+    }
+
+    #[test]
+    fn test_split_into_tokens_works() {
+        assert_eq!(
+            split_into_tokens("안녕 world"),
+            vec![
+                Token::Syllable('안'),
+                Token::Syllable('녕'),
+                Token::Other(" world")
+            ]
+        );
+        assert_eq!(
+            split_into_tokens("\"네\"?"),
+            vec![
+                Token::Other("\""),
+                Token::Syllable('네'),
+                Token::Other("\"?")
+            ]
+        );
+        assert_eq!(split_into_tokens(""), vec![]);
+    }
+
+    #[test]
+    fn test_is_silent_initial_works() {
+        assert!(is_silent_initial('ᄋ'));
+        // The final ㅇ is pronounced "ng".
+        assert!(!is_silent_initial('ᆼ'));
+        assert!(!is_silent_initial('ㅇ'));
+        assert!(!is_silent_initial('ᄀ'));
+    }
+
+    #[test]
+    fn test_strip_non_hangul() {
+        assert_eq!(
+            strip_non_hangul("네, 저는 의사예요", false),
+            "네저는의사예요"
+        );
+        assert_eq!(
+            strip_non_hangul("네, 저는 의사예요", true),
+            "네 저는 의사예요"
+        );
+        assert_eq!(strip_non_hangul("Hi! ㅋㅋ 2개", true), "ㅋㅋ 개");
+        assert_eq!(strip_non_hangul("hello", true), "");
+    }
+
+    #[test]
+    fn test_is_all_modern_hangul() {
+        assert!(is_all_modern_hangul("안녕하세요", false));
+        assert!(is_all_modern_hangul("ㅋㅋ \u{1112}\u{1161}\u{11ab}", true));
+        assert!(!is_all_modern_hangul("안녕 하세요", false));
+        assert!(!is_all_modern_hangul("안녕!", true));
+        // Archaic jamos, like ㆍ (arae-a), aren't modern.
+        assert!(!is_all_modern_hangul("ᄒᆞᆫ", false));
+        assert!(!is_all_modern_hangul("ㆍ", false));
+        // Neither are fillers.
+        assert!(!is_all_modern_hangul("\u{3164}", false));
+    }
+
+    #[test]
+    fn test_strip_trailing_particle() {
+        assert_eq!(
+            strip_trailing_particle("학교에서"),
+            ("학교".to_owned(), Some("에서"))
+        );
+        assert_eq!(
+            strip_trailing_particle("학교에"),
+            ("학교".to_owned(), Some("에"))
+        );
+        assert_eq!(
+            strip_trailing_particle("책은"),
+            ("책".to_owned(), Some("은"))
+        );
+        assert_eq!(
+            strip_trailing_particle("저는"),
+            ("저".to_owned(), Some("는"))
+        );
+        assert_eq!(
+            strip_trailing_particle("친구를"),
+            ("친구".to_owned(), Some("를"))
+        );
+        // The form of the particle doesn't match the final consonant.
+        assert_eq!(
+            strip_trailing_particle("사람는"),
+            ("사람는".to_owned(), None)
+        );
+        // At least one syllable is always left.
+        assert_eq!(strip_trailing_particle("는"), ("는".to_owned(), None));
+        assert_eq!(strip_trailing_particle("hello"), ("hello".to_owned(), None));
+        // A known false positive.
+        assert_eq!(
+            strip_trailing_particle("고양이"),
+            ("고양".to_owned(), Some("이"))
+        );
+    }
+
+    #[test]
+    fn test_tone_marks_are_classified() {
+        assert_eq!(HangulCharClass::from('\u{302e}'), HangulCharClass::ToneMark);
+        assert_eq!(HangulCharClass::from('\u{302f}'), HangulCharClass::ToneMark);
+        assert_eq!(tone_mark_name('\u{302e}'), Some("거성 (high tone)"));
+        assert_eq!(tone_mark_name('.'), None);
+        let counts = HangulCounts::new("말\u{302e}");
+        assert_eq!(counts.syllables, 1);
+        assert_eq!(counts.non_hangul, 0);
+        assert_eq!(
+            counts.classes,
+            vec![HangulCharClass::Syllables, HangulCharClass::ToneMark]
+        );
+    }
+
+    #[test]
+    fn test_every_syllable_round_trips() {
+        for codepoint in 0xac00..=0xd7a3 {
+            let syllable = char::from_u32(codepoint).unwrap();
+            let (initial, medial, maybe_final) = decompose_hangul_syllable_to_jamos(syllable)
+                .unwrap_or_else(|| panic!("U+{codepoint:04X} didn't decompose"));
+            let jamos = [initial, medial].into_iter().chain(maybe_final);
+            assert_eq!(
+                compose_hangul_jamos_to_syllable(jamos),
+                Some(syllable),
+                "U+{codepoint:04X} didn't round-trip"
+            );
+        }
+    }
+
+    #[test]
+    fn test_every_jamo_combination_round_trips() {
+        let mut count = 0;
+        for initial in '\u{1100}'..='\u{1112}' {
+            for medial in '\u{1161}'..='\u{1175}' {
+                let finals = std::iter::once(None).chain(('\u{11a8}'..='\u{11c2}').map(Some));
+                for maybe_final in finals {
+                    let jamos = [initial, medial].into_iter().chain(maybe_final);
+                    let syllable = compose_hangul_jamos_to_syllable(jamos).unwrap_or_else(|| {
+                        panic!("{initial:?} {medial:?} {maybe_final:?} didn't compose")
+                    });
+                    assert_eq!(
+                        decompose_hangul_syllable_to_jamos(syllable),
+                        Some((initial, medial, maybe_final)),
+                        "U+{:04X} didn't round-trip",
+                        syllable as u32
+                    );
+                    count += 1;
+                }
+            }
+        }
+        assert_eq!(count, 11172);
+    }
+
+    #[test]
+    fn test_syllable_to_compat_jamo_string() {
+        assert_eq!(
+            syllable_to_compat_jamo_string('한'),
+            Some("ㅎㅏㄴ".to_owned())
+        );
+        assert_eq!(
+            syllable_to_compat_jamo_string('이'),
+            Some("ㅇㅣ".to_owned())
+        );
+        assert_eq!(
+            syllable_to_compat_jamo_string('닭'),
+            Some("ㄷㅏㄺ".to_owned())
+        );
+        assert_eq!(syllable_to_compat_jamo_string('ㅎ'), None);
+        assert_eq!(syllable_to_compat_jamo_string('h'), None);
+    }
+
+    #[test]
+    fn test_is_filler_jamo() {
+        for ch in ['\u{115f}', '\u{1160}', '\u{3164}', '\u{ffa0}'] {
+            assert!(is_filler_jamo(ch), "{ch:?}");
+        }
+        for ch in ['ᄀ', 'ᅡ', 'ㄱ', ' ', '한'] {
+            assert!(!is_filler_jamo(ch), "{ch:?}");
+        }
+    }
+
+    #[test]
+    fn test_compose_skips_fillers() {
+        assert_eq!(compose_all_hangul_jamos("ᄒ\u{1160}ᅡᆫ"), "한");
+        assert_eq!(compose_all_hangul_jamos("가\u{115f}나"), "가나");
+    }
+
+    #[test]
+    fn test_nfd_non_hangul_leaves_hangul_alone() {
+        for value in ["한글", "닭", "값이 없어요", "ᄒᆞᆫ", "ㅋㅋ"] {
+            assert_eq!(nfd_non_hangul(value), value);
+        }
+        // "café 한글", with the é precomposed and then with a combining
+        // accent, decodes the same way once both are normalized.
+        let nfc = "caf\u{e9} 한글";
+        let nfd = "cafe\u{301} 한글";
+        assert_ne!(
+            decompose_all_hangul_syllables(nfc),
+            decompose_all_hangul_syllables(nfd)
+        );
+        assert_eq!(
+            decompose_all_hangul_syllables(nfd_non_hangul(nfc)),
+            decompose_all_hangul_syllables(nfd_non_hangul(nfd))
+        );
+    }
+
+    #[test]
+    fn test_numbered_chars() {
+        assert_eq!(
+            numbered_chars("가ᄀ,나").collect::<Vec<_>>(),
+            vec![
+                (0, '가', Some(0)),
+                (3, 'ᄀ', None),
+                (6, ',', None),
+                (7, '나', Some(1))
+            ]
+        );
+    }
 }
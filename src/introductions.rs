@@ -7,11 +7,11 @@ use rand::seq::SliceRandom;
 use rand::{Rng, thread_rng};
 use rustyline::Editor;
 use rustyline::history::FileHistory;
-use tts::{Tts, Voice};
+use tts::Tts;
 
-use crate::hangul::{
-    HangulCharClass, compose_all_hangul_jamos, decompose_all_hangul_syllables,
-    decompose_hangul_syllable_to_jamos,
+use crate::{
+    hangul::{decompose_hangul_syllable_to_jamos, strip_non_hangul, to_syllables},
+    tts::{FEMALE_VOICES, MALE_VOICES, Speaker, create_speaker, with_preferred_voice},
 };
 
 const NAMES: [&str; 8] = [
@@ -55,105 +55,14 @@ const OCCUPATIONS: [&str; 11] = [
 
 const CONGRATS: [&str; 5] = ["잘했어요!", "멋있다!", "잘하네요!", "좋아요!", "굉장해요!"];
 
-const REPEAT_COMMAND: &str = "뭐라고";
+/// What the user can say to have the last line repeated.
+pub const REPEAT_COMMAND: &str = "뭐라고";
 const SKIP_COMMAND: &str = "다음";
 
 /// Annoyingly, on MacOS Ctrl-C gets eaten in the run loop so we'll
 /// do this to capture it.
 const AUTO_PROMPT: &str = "Press enter to continue or Ctrl-C to exit.";
 
-trait Speaker {
-    fn speak(&mut self, text: &str) -> Result<()>;
-}
-
-struct StdoutSpeaker {
-    name: String,
-}
-
-impl Speaker for StdoutSpeaker {
-    fn speak(&mut self, text: &str) -> Result<()> {
-        println!("{}: {}", self.name, text);
-        Ok(())
-    }
-}
-
-struct TtsSpeaker {
-    name: String,
-    tts: Tts,
-    voice: Voice,
-    rate: f32,
-}
-
-impl Speaker for TtsSpeaker {
-    fn speak(&mut self, text: &str) -> Result<()> {
-        println!("{}: {}", self.name, text);
-        self.tts.set_rate(self.rate)?;
-        self.tts.set_voice(&self.voice)?;
-        self.tts.speak(text, true)?;
-        #[cfg(target_os = "macos")]
-        {
-            use objc2_foundation::NSDate;
-            let run_loop = objc2_foundation::NSRunLoop::currentRunLoop();
-            loop {
-                let future = NSDate::dateWithTimeIntervalSinceNow(2.0);
-                run_loop.runUntilDate(&future);
-                if !self.tts.is_speaking()? {
-                    break;
-                }
-            }
-        }
-        Ok(())
-    }
-}
-
-fn create_speaker<T: AsRef<str>>(
-    tts: Option<Tts>,
-    name: String,
-    preferred_voices: &[T],
-    rate: Option<f32>,
-) -> Box<dyn Speaker> {
-    if let Some(tts) = tts {
-        let features = tts.supported_features();
-        if features.is_speaking && features.voice && features.rate {
-            if let Ok(voices) = tts.voices() {
-                if let Some(voice) = preferred_voices.iter().find_map(|preferred_voice| {
-                    for voice in &voices {
-                        if voice.language() != "ko-KR" {
-                            continue;
-                        }
-                        if preferred_voice.as_ref() == "*" {
-                            return Some(voice.clone());
-                        }
-                        if voice.id() == preferred_voice.as_ref() {
-                            return Some(voice.clone());
-                        }
-                    }
-                    return None;
-                }) {
-                    let mut rate = rate.unwrap_or(tts.min_rate());
-                    if rate < tts.min_rate() {
-                        rate = tts.min_rate();
-                    } else if rate > tts.max_rate() {
-                        rate = tts.max_rate();
-                    }
-                    println!(
-                        "Initializing TTS voice '{}' at rate {}.",
-                        voice.name(),
-                        rate
-                    );
-                    return Box::new(TtsSpeaker {
-                        name,
-                        tts,
-                        voice,
-                        rate,
-                    });
-                }
-            }
-        }
-    }
-    Box::new(StdoutSpeaker { name })
-}
-
 struct Conversation {
     is_interactive: bool,
     rl: Editor<(), FileHistory>,
@@ -200,7 +109,7 @@ impl Conversation {
 
 /// Return a diff of the two hangul strings, with carets for
 /// every mismatched character.
-fn get_hangul_diff(a: &str, b: &str) -> String {
+pub fn get_hangul_diff(a: &str, b: &str) -> String {
     let mut result = String::with_capacity(a.len());
 
     for (a, b) in a.chars().zip(b.chars()) {
@@ -216,19 +125,21 @@ fn get_hangul_diff(a: &str, b: &str) -> String {
     result
 }
 
-fn get_hangul<T: AsRef<str>>(value: T) -> String {
-    let normalized = compose_all_hangul_jamos(decompose_all_hangul_syllables(value.as_ref()));
-    HangulCharClass::split(&normalized)
-        .into_iter()
-        .map(|(class, str)| {
-            if class == HangulCharClass::None {
-                ""
-            } else {
-                str
-            }
-        })
-        .collect::<Vec<_>>()
-        .join("")
+/// Return only the Hangul in the given string, normalized to
+/// syllables, so that strings can be compared while ignoring
+/// spacing and punctuation.
+pub fn get_hangul<T: AsRef<str>>(value: T) -> String {
+    strip_non_hangul(to_syllables(value), false)
+}
+
+/// Returns all of the vocabulary used in the conversation.
+pub fn get_vocabulary() -> Vec<&'static str> {
+    NAMES
+        .iter()
+        .chain(COUNTRIES.iter())
+        .chain(OCCUPATIONS.iter())
+        .cloned()
+        .collect()
 }
 
 fn run_introduction(c: &mut Conversation) -> Result<()> {
@@ -278,32 +189,20 @@ fn run_introduction(c: &mut Conversation) -> Result<()> {
     Ok(())
 }
 
-pub fn run_introductions(rate: Option<f32>, is_interactive: bool) -> Result<()> {
+/// Runs the conversation simulator. `voice_a` and `voice_b` are the IDs
+/// of voices to use for each speaker in preference to the default ones.
+pub fn run_introductions(
+    rate: Option<f32>,
+    is_interactive: bool,
+    voice_a: &Option<String>,
+    voice_b: &Option<String>,
+) -> Result<()> {
     let tts = Tts::default().ok();
+    let voices_a = with_preferred_voice(voice_a, &FEMALE_VOICES);
+    let voices_b = with_preferred_voice(voice_b, &MALE_VOICES);
     let mut c = Conversation {
-        a: create_speaker(
-            tts.clone(),
-            "A".to_owned(),
-            &[
-                "com.apple.voice.premium.ko-KR.Yuna",
-                "com.apple.voice.enhanced.ko-KR.Yuna",
-                "com.apple.voice.compact.ko-KR.Yuna",
-                "com.apple.eloquence.ko-KR.Grandma",
-                "*",
-            ],
-            rate,
-        ),
-        b: create_speaker(
-            tts.clone(),
-            "B".to_owned(),
-            &[
-                "com.apple.voice.enhanced.ko-KR.Minsu",
-                "com.apple.voice.compact.ko-KR.Minsu",
-                "com.apple.eloquence.ko-KR.Grandpa",
-                "*",
-            ],
-            rate,
-        ),
+        a: create_speaker(tts.clone(), "A".to_owned(), &voices_a, rate),
+        b: create_speaker(tts.clone(), "B".to_owned(), &voices_b, rate),
         rl: rustyline::DefaultEditor::new()?,
         is_interactive,
     };
@@ -364,7 +263,10 @@ fn get_copula<T: AsRef<str>>(value: T) -> Result<&'static str> {
 
 #[cfg(test)]
 mod tests {
-    use crate::introductions::{ends_in_vowel, get_hangul};
+    use crate::{
+        hangul::is_all_modern_hangul,
+        introductions::{ends_in_vowel, get_hangul, get_vocabulary},
+    };
 
     #[test]
     fn test_ends_in_vowel() {
@@ -376,4 +278,11 @@ mod tests {
     fn test_get_hangul_works() {
         assert_eq!(get_hangul("네, 저는 의사예요"), "네저는의사예요");
     }
+
+    #[test]
+    fn test_vocabulary_is_modern_hangul() {
+        for word in get_vocabulary() {
+            assert!(is_all_modern_hangul(word, true), "{word}");
+        }
+    }
 }
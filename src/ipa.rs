@@ -0,0 +1,177 @@
+/// An approximate IPA transcription of modern Korean, for learners who
+/// find it clearer than romanization.
+///
+/// This is only a rough guide to the standard Seoul pronunciation: it
+/// applies the same pronunciation rules as the rest of the program,
+/// voices lax consonants between voiced sounds (e.g. ㄱ is [k] in 고기's
+/// first syllable, but [g] in its second), and marks final stops as
+/// unreleased, but it doesn't account for vowel length, intonation, or
+/// finer allophones like [ɦ] for ㅎ. Archaic jamos are passed through
+/// unchanged.
+use crate::{
+    hangul::{
+        decompose_all_hangul_syllables, halfwidth_jamos_to_conjoining, is_medial_jamo,
+        is_silent_initial,
+    },
+    jamo_stream::{JamoInStream, JamoStream},
+    pronunciation::apply_pronunciation_rules_to_jamos,
+};
+
+/// Whether the given jamo is voiced, so that a lax consonant after it
+/// is voiced too.
+fn is_voiced(ch: char) -> bool {
+    is_medial_jamo(ch) || matches!(ch, 'ᆫ' | 'ᆯ' | 'ᆷ' | 'ᆼ')
+}
+
+/// Whether the given vowel palatalizes a ㅅ before it, e.g. 시 and 샤
+/// start with [ɕ] rather than [s].
+fn is_palatalizing_vowel(ch: char) -> bool {
+    matches!(ch, 'ᅵ' | 'ᅣ' | 'ᅤ' | 'ᅧ' | 'ᅨ' | 'ᅭ' | 'ᅲ' | 'ᅱ')
+}
+
+/// Whether the given initial is pronounced as an alveolo-palatal
+/// consonant before the given vowel.
+fn is_palatal_initial(initial: char, vowel: char) -> bool {
+    match initial {
+        'ᄉ' | 'ᄊ' => is_palatalizing_vowel(vowel),
+        'ᄌ' | 'ᄍ' | 'ᄎ' => true,
+        _ => false,
+    }
+}
+
+fn get_initial_ipa(jamo: &JamoInStream) -> Option<&'static str> {
+    let voiced = jamo.prev.is_some_and(is_voiced);
+    let ipa = match jamo.curr {
+        'ᄀ' if voiced => "g",
+        'ᄀ' => "k",
+        'ᄁ' => "k͈",
+        'ᄂ' => "n",
+        'ᄃ' if voiced => "d",
+        'ᄃ' => "t",
+        'ᄄ' => "t͈",
+        // ㄹ is a flap, except after another ㄹ, e.g. 빨리.
+        'ᄅ' if jamo.prev == Some('ᆯ') => "l",
+        'ᄅ' => "ɾ",
+        'ᄆ' => "m",
+        'ᄇ' if voiced => "b",
+        'ᄇ' => "p",
+        'ᄈ' => "p͈",
+        'ᄉ' if jamo.next.is_some_and(is_palatalizing_vowel) => "ɕ",
+        'ᄉ' => "s",
+        'ᄊ' if jamo.next.is_some_and(is_palatalizing_vowel) => "ɕ͈",
+        'ᄊ' => "s͈",
+        'ᄋ' => "",
+        'ᄌ' if voiced => "dʑ",
+        'ᄌ' => "tɕ",
+        'ᄍ' => "t͈ɕ",
+        'ᄎ' => "tɕʰ",
+        'ᄏ' => "kʰ",
+        'ᄐ' => "tʰ",
+        'ᄑ' => "pʰ",
+        'ᄒ' => "h",
+        _ => return None,
+    };
+    Some(ipa)
+}
+
+fn get_medial_ipa(jamo: &JamoInStream) -> Option<&'static str> {
+    let ipa = match jamo.curr {
+        'ᅡ' => "a",
+        'ᅢ' => "ɛ",
+        'ᅣ' => "ja",
+        'ᅤ' => "jɛ",
+        'ᅥ' => "ʌ",
+        'ᅦ' => "e",
+        'ᅧ' => "jʌ",
+        'ᅨ' => "je",
+        'ᅩ' => "o",
+        'ᅪ' => "wa",
+        'ᅫ' => "wɛ",
+        'ᅬ' => "we",
+        'ᅭ' => "jo",
+        'ᅮ' => "u",
+        'ᅯ' => "wʌ",
+        'ᅰ' => "we",
+        'ᅱ' => "wi",
+        'ᅲ' => "ju",
+        'ᅳ' => "ɯ",
+        // ㅢ is only pronounced fully without a consonant before it.
+        'ᅴ' if jamo.prev.is_some_and(|prev| !is_silent_initial(prev)) => "i",
+        'ᅴ' => "ɰi",
+        'ᅵ' => "i",
+        _ => return None,
+    };
+    // The [j] of a vowel like ㅑ merges into an alveolo-palatal
+    // consonant before it, e.g. 샤 is [ɕa] and 져 is [tɕʌ].
+    if let Some(prev) = jamo.prev
+        && is_palatal_initial(prev, jamo.curr)
+        && let Some(ipa) = ipa.strip_prefix('j')
+    {
+        return Some(ipa);
+    }
+    Some(ipa)
+}
+
+/// Finals are neutralized to one of seven sounds, with stops being
+/// unreleased.
+fn get_final_ipa(ch: char) -> Option<&'static str> {
+    let ipa = match ch {
+        'ᆨ' | 'ᆩ' | 'ᆪ' | 'ᆰ' | 'ᆿ' => "k̚",
+        'ᆫ' | 'ᆬ' | 'ᆭ' => "n",
+        'ᆮ' | 'ᆺ' | 'ᆻ' | 'ᆽ' | 'ᆾ' | 'ᇀ' | 'ᇂ' => "t̚",
+        'ᆯ' | 'ᆲ' | 'ᆳ' | 'ᆴ' | 'ᆶ' => "l",
+        'ᆷ' | 'ᆱ' => "m",
+        'ᆸ' | 'ᆹ' | 'ᆵ' | 'ᇁ' => "p̚",
+        'ᆼ' => "ŋ",
+        _ => return None,
+    };
+    Some(ipa)
+}
+
+/// Transcribes the given Hangul, which may be written with syllables or
+/// jamos, into approximate IPA, after applying pronunciation rules to
+/// it. Everything that isn't modern Hangul is passed through unchanged.
+pub fn transcribe_ipa(value: &str) -> String {
+    let jamos = apply_pronunciation_rules_to_jamos(decompose_all_hangul_syllables(
+        halfwidth_jamos_to_conjoining(value),
+    ));
+    JamoStream::from_jamos(jamos)
+        .map(|jamo| {
+            let ipa = get_initial_ipa(&jamo)
+                .or_else(|| get_medial_ipa(&jamo))
+                .or_else(|| get_final_ipa(jamo.curr));
+            match ipa {
+                Some(ipa) => ipa.to_owned(),
+                None => jamo.curr.to_string(),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ipa::transcribe_ipa;
+
+    #[test]
+    fn test_transcribe_ipa() {
+        assert_eq!(transcribe_ipa("밥"), "pap̚");
+        // Lax consonants are voiced between vowels.
+        assert_eq!(transcribe_ipa("고기"), "kogi");
+        // Pronunciation rules are applied first (한국어 is 한구거).
+        assert_eq!(transcribe_ipa("한국어"), "hangugʌ");
+        assert_eq!(transcribe_ipa("빨리"), "p͈alli");
+        assert_eq!(transcribe_ipa("시장"), "ɕidʑaŋ");
+        assert_eq!(transcribe_ipa("샤워"), "ɕawʌ");
+        assert_eq!(transcribe_ipa("쉬워"), "ɕwiwʌ");
+        assert_eq!(transcribe_ipa("가져"), "kadʑʌ");
+        assert_eq!(transcribe_ipa("서울"), "sʌul");
+        assert_eq!(transcribe_ipa("의사"), "ɰisa");
+        assert_eq!(transcribe_ipa("희망"), "himaŋ");
+        assert_eq!(transcribe_ipa("크다"), "kʰɯda");
+    }
+
+    #[test]
+    fn test_non_hangul_is_unchanged() {
+        assert_eq!(transcribe_ipa("밥, please!"), "pap̚, please!");
+    }
+}
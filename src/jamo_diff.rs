@@ -0,0 +1,169 @@
+/// Comparing words jamo by jamo, for telling apart words that sound
+/// alike, e.g. 불 (fire) and 풀 (grass), which only differ in their
+/// initial consonant.
+use crossterm::style::{Color, Stylize};
+
+use crate::hangul::{
+    HangulCharClass, JamoRole, compose_all_hangul_jamos, decompose_hangul_syllable_to_jamos,
+    hangul_jamo_to_compat_with_fallback,
+};
+
+/// How a syllable of one word compares to the syllable in the same
+/// position of another.
+#[derive(Debug, PartialEq)]
+pub struct SyllableDiff {
+    /// The syllable of the first word, unless it's shorter.
+    pub a: Option<char>,
+    /// The syllable of the second word, unless it's shorter.
+    pub b: Option<char>,
+    /// Where the two syllables' jamos differ. If one of the syllables
+    /// is missing, this is wherever the other one has a jamo.
+    pub differing: Vec<JamoRole>,
+}
+
+/// Returns the jamo in each position of the given syllable, if any.
+fn get_jamos(syllable: Option<char>) -> [(JamoRole, Option<char>); 3] {
+    let (initial, medial, final_) = match syllable.and_then(decompose_hangul_syllable_to_jamos) {
+        Some((initial, medial, final_)) => (Some(initial), Some(medial), final_),
+        None => (None, None, None),
+    };
+    [
+        (JamoRole::Initial, initial),
+        (JamoRole::Medial, medial),
+        (JamoRole::Final, final_),
+    ]
+}
+
+/// Compares the Hangul syllables of the two given words, which may be
+/// written with syllables or jamos, aligning them from the start.
+/// Anything else is ignored.
+pub fn diff_syllables(a: &str, b: &str) -> Vec<SyllableDiff> {
+    let syllables = |value: &str| -> Vec<char> {
+        compose_all_hangul_jamos(value)
+            .chars()
+            .filter(|&ch| HangulCharClass::from(ch) == HangulCharClass::Syllables)
+            .collect()
+    };
+    let (a, b) = (syllables(a), syllables(b));
+    (0..a.len().max(b.len()))
+        .map(|idx| {
+            let (a, b) = (a.get(idx).copied(), b.get(idx).copied());
+            let differing = get_jamos(a)
+                .into_iter()
+                .zip(get_jamos(b))
+                .filter(|((_, a_jamo), (_, b_jamo))| a_jamo != b_jamo)
+                .map(|((role, _), _)| role)
+                .collect();
+            SyllableDiff { a, b, differing }
+        })
+        .collect()
+}
+
+fn role_name(role: JamoRole) -> &'static str {
+    match role {
+        JamoRole::Initial => "initial",
+        JamoRole::Medial => "medial",
+        JamoRole::Final => "final",
+    }
+}
+
+/// Returns the compatibility jamos of the given syllable, with the ones
+/// that differ highlighted if `color` is set.
+fn format_jamos(syllable: Option<char>, differing: &[JamoRole], color: bool) -> String {
+    if syllable.is_none() {
+        return "(none)".to_owned();
+    }
+    get_jamos(syllable)
+        .into_iter()
+        .filter_map(|(role, jamo)| {
+            let jamo = hangul_jamo_to_compat_with_fallback(jamo?).to_string();
+            if color && differing.contains(&role) {
+                Some(jamo.with(Color::Red).bold().to_string())
+            } else {
+                Some(jamo)
+            }
+        })
+        .collect()
+}
+
+/// Describes how the two syllables compare, e.g. "불 → 풀: ㅂㅜㄹ → ㅍㅜㄹ
+/// (initial differs)".
+pub fn format_syllable_diff(diff: &SyllableDiff, color: bool) -> String {
+    let summary = match (diff.a, diff.b) {
+        (Some(_), None) | (None, Some(_)) => "extra syllable".to_owned(),
+        _ if diff.differing.is_empty() => "same".to_owned(),
+        _ => {
+            let roles: Vec<&str> = diff.differing.iter().map(|&role| role_name(role)).collect();
+            format!(
+                "{} differ{}",
+                roles.join(", "),
+                if roles.len() == 1 { "s" } else { "" }
+            )
+        }
+    };
+    let syllable = |syllable: Option<char>| syllable.map_or("(none)".to_owned(), String::from);
+    format!(
+        "{} → {}: {} → {} ({summary})",
+        syllable(diff.a),
+        syllable(diff.b),
+        format_jamos(diff.a, &diff.differing, color),
+        format_jamos(diff.b, &diff.differing, color),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        hangul::JamoRole,
+        jamo_diff::{SyllableDiff, diff_syllables, format_syllable_diff},
+    };
+
+    #[test]
+    fn test_diff_syllables() {
+        assert_eq!(
+            diff_syllables("불", "풀"),
+            vec![SyllableDiff {
+                a: Some('불'),
+                b: Some('풀'),
+                differing: vec![JamoRole::Initial],
+            }]
+        );
+        // Words written with jamos are compared by their syllables.
+        assert_eq!(
+            diff_syllables("\u{1107}\u{116e}\u{11af}", "풀"),
+            diff_syllables("불", "풀")
+        );
+        let diffs = diff_syllables("사자", "사장");
+        assert_eq!(diffs[0].differing, vec![]);
+        assert_eq!(diffs[1].differing, vec![JamoRole::Final]);
+        let diffs = diff_syllables("달", "딸기");
+        assert_eq!(diffs[0].differing, vec![JamoRole::Initial]);
+        assert_eq!(
+            diffs[1],
+            SyllableDiff {
+                a: None,
+                b: Some('기'),
+                differing: vec![JamoRole::Initial, JamoRole::Medial],
+            }
+        );
+    }
+
+    #[test]
+    fn test_format_syllable_diff() {
+        let diffs = diff_syllables("불고기", "풀고");
+        let lines: Vec<String> = diffs
+            .iter()
+            .map(|diff| format_syllable_diff(diff, false))
+            .collect();
+        assert_eq!(
+            lines,
+            vec![
+                "불 → 풀: ㅂㅜㄹ → ㅍㅜㄹ (initial differs)",
+                "고 → 고: ㄱㅗ → ㄱㅗ (same)",
+                "기 → (none): ㄱㅣ → (none) (extra syllable)",
+            ]
+        );
+        let diff = &diff_syllables("발", "팔")[0];
+        assert!(format_syllable_diff(diff, true).contains("\u{1b}["));
+    }
+}
@@ -1,4 +1,4 @@
-use crate::hangul::compose_hangul_jamos_to_syllable;
+use crate::hangul::{compose_hangul_jamos_to_syllable, is_filler_jamo, is_silent_initial};
 
 #[derive(PartialEq, Debug)]
 pub struct JamoInStream {
@@ -6,20 +6,29 @@ pub struct JamoInStream {
     pub prev: Option<char>,
     pub next: Option<char>,
     pub next_syllable: Option<char>,
+    /// The syllable that this jamo is part of, if any.
+    pub curr_syllable: Option<char>,
+    /// Whether this jamo is the initial consonant of its syllable.
+    pub is_syllable_start: bool,
 }
 
 impl JamoInStream {
     pub fn is_final_consonant_followed_by_vowel(&self) -> bool {
         // This assumes our stream is a well-formed sequence of Jamos.
-        self.next == Some('ᄋ')
+        self.next.is_some_and(is_silent_initial)
     }
 }
 
 pub struct JamoStream {
     jamos: Vec<char>,
     syllable_indices: Vec<usize>,
+    /// Each syllable of the stream, once it's been composed. Every jamo
+    /// of a syllable (and of the one before it) needs it, so it's only
+    /// composed the first time it's asked for. This only saves composing
+    /// a syllable more than once: `next()` still asks for the current and
+    /// next syllable of every jamo as it goes.
+    syllables: Vec<Option<Option<char>>>,
     index: usize,
-    syllable_index: usize,
 }
 
 impl JamoStream {
@@ -28,8 +37,18 @@ impl JamoStream {
         Self::from_jamos(decompose_all_hangul_syllables(value))
     }
 
+    /// Streams the given jamos. Hangul fillers are left out, since they
+    /// only mark where a jamo is missing.
+    ///
+    /// All of the jamos are collected up front, rather than read as
+    /// they're needed, so that any of them can be looked ahead to or
+    /// back at, e.g. to find the next syllable.
     pub fn from_jamos<T: AsRef<str>>(value: T) -> Self {
-        let jamos: Vec<char> = value.as_ref().chars().collect();
+        let jamos: Vec<char> = value
+            .as_ref()
+            .chars()
+            .filter(|&ch| !is_filler_jamo(ch))
+            .collect();
         let mut syllable_indices = Vec::with_capacity(jamos.len() / 2);
         for (index, jamo) in jamos.iter().enumerate() {
             if ModernJamo::is_initial_consonant(*jamo) {
@@ -38,10 +57,10 @@ impl JamoStream {
         }
 
         Self {
+            syllables: vec![None; syllable_indices.len()],
             jamos,
             syllable_indices,
             index: 0,
-            syllable_index: 0,
         }
     }
 
@@ -55,11 +74,38 @@ impl JamoStream {
         let Some(&jamo_start_index) = self.syllable_indices.get(index) else {
             return None;
         };
+        if let Some(syllable) = self.syllables[index] {
+            return syllable;
+        }
         let slice = match self.syllable_indices.get(index + 1) {
             Some(&jamo_end_index) => &self.jamos[jamo_start_index..jamo_end_index],
             None => &self.jamos[jamo_start_index..],
         };
-        compose_hangul_jamos_to_syllable(slice.iter().cloned())
+        // The syllable ends at anything that isn't a jamo, e.g. the
+        // punctuation after the last syllable of a sentence.
+        let syllable = compose_hangul_jamos_to_syllable(
+            slice
+                .iter()
+                .cloned()
+                .take_while(|&ch| ModernJamo::try_from_char(ch).is_some()),
+        );
+        self.syllables[index] = Some(syllable);
+        syllable
+    }
+
+    /// Returns how many syllables start at or before the given index.
+    fn count_syllables_started(&self, jamo_index: usize) -> usize {
+        self.syllable_indices
+            .partition_point(|&start_index| start_index <= jamo_index)
+    }
+
+    /// Returns the syllable that the jamo at the given index is part of.
+    fn get_syllable_containing(&mut self, jamo_index: usize) -> Option<char> {
+        let count = self.count_syllables_started(jamo_index);
+        if count == 0 {
+            return None;
+        }
+        self.get_syllable_at(count - 1)
     }
 }
 
@@ -70,23 +116,26 @@ impl Iterator for JamoStream {
         let Some(&curr) = self.jamos.get(self.index) else {
             return None;
         };
+        let is_syllable_start = ModernJamo::is_initial_consonant(curr);
+        let curr_syllable = if ModernJamo::try_from_char(curr).is_some() {
+            self.get_syllable_containing(self.index)
+        } else {
+            None
+        };
         let prev = if self.index == 0 {
             None
         } else {
             self.jamos.get(self.index - 1).cloned()
         };
         let (next, next_syllable) = match self.jamos.get(self.index + 1) {
+            // The next syllable is the first one that starts after this
+            // jamo, which is worked out from scratch rather than kept
+            // track of, so that seeking doesn't throw it off.
             Some(&next) => {
-                let next_syllable = self.get_syllable_at(self.syllable_index + 1);
-                if let Some(ModernJamo::InitialConsonant(_)) = ModernJamo::try_from_char(next) {
-                    self.syllable_index += 1;
-                }
+                let next_syllable = self.get_syllable_at(self.count_syllables_started(self.index));
                 (Some(next), next_syllable)
             }
-            None => {
-                self.syllable_index += 1;
-                (None, None)
-            }
+            None => (None, None),
         };
         self.index += 1;
         Some(JamoInStream {
@@ -94,6 +143,8 @@ impl Iterator for JamoStream {
             prev,
             next,
             next_syllable,
+            curr_syllable,
+            is_syllable_start,
         })
     }
 }
@@ -155,7 +206,9 @@ mod tests {
                 prev: None,
                 curr: 'ᄇ',
                 next: Some('ᅡ'),
-                next_syllable: Some('이')
+                next_syllable: Some('이'),
+                curr_syllable: Some('밥'),
+                is_syllable_start: true
             }
         );
 
@@ -165,7 +218,9 @@ mod tests {
                 prev: Some('ᄇ'),
                 curr: 'ᅡ',
                 next: Some('ᆸ'),
-                next_syllable: Some('이')
+                next_syllable: Some('이'),
+                curr_syllable: Some('밥'),
+                is_syllable_start: false
             }
         );
 
@@ -175,7 +230,9 @@ mod tests {
                 prev: Some('ᅡ'),
                 curr: 'ᆸ',
                 next: Some('ᄋ'),
-                next_syllable: Some('이')
+                next_syllable: Some('이'),
+                curr_syllable: Some('밥'),
+                is_syllable_start: false
             }
         );
 
@@ -185,7 +242,9 @@ mod tests {
                 prev: Some('ᆸ'),
                 curr: 'ᄋ',
                 next: Some('ᅵ'),
-                next_syllable: None
+                next_syllable: None,
+                curr_syllable: Some('이'),
+                is_syllable_start: true
             }
         );
 
@@ -195,7 +254,9 @@ mod tests {
                 prev: Some('ᄋ'),
                 curr: 'ᅵ',
                 next: None,
-                next_syllable: None
+                next_syllable: None,
+                curr_syllable: Some('이'),
+                is_syllable_start: false
             }
         );
 
@@ -203,4 +264,69 @@ mod tests {
         assert_eq!(stream.next(), None);
         assert_eq!(stream.next(), None);
     }
+
+    #[test]
+    fn test_curr_syllable_ignores_non_hangul() {
+        let curr_syllables: Vec<Option<char>> = JamoStream::from_hangul_syllables("h밥 이")
+            .map(|jamo| jamo.curr_syllable)
+            .collect();
+
+        assert_eq!(
+            curr_syllables,
+            vec![
+                None,
+                Some('밥'),
+                Some('밥'),
+                Some('밥'),
+                None,
+                Some('이'),
+                Some('이')
+            ]
+        );
+    }
+
+    #[test]
+    fn test_curr_syllable_before_punctuation() {
+        let curr_syllables: Vec<Option<char>> = JamoStream::from_hangul_syllables("다!")
+            .map(|jamo| jamo.curr_syllable)
+            .collect();
+
+        assert_eq!(curr_syllables, vec![Some('다'), Some('다'), None]);
+    }
+
+    #[test]
+    fn test_fillers_are_skipped() {
+        let jamos: Vec<char> = JamoStream::from_jamos("ᄋ\u{1160}ᅡᆫ\u{3164}")
+            .map(|jamo| jamo.curr)
+            .collect();
+        assert_eq!(jamos, vec!['ᄋ', 'ᅡ', 'ᆫ']);
+    }
+
+    #[test]
+    fn test_seeking_back_gives_the_same_jamos() {
+        for value in ["밥을 먹었어요!", "h밥 이"] {
+            let fresh: Vec<JamoInStream> = JamoStream::from_hangul_syllables(value).collect();
+            let mut stream = JamoStream::from_hangul_syllables(value);
+            assert_eq!(stream.by_ref().count(), fresh.len());
+            for syllable in [1, 0] {
+                stream.seek_to_syllable(syllable);
+                let start = fresh
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, jamo)| jamo.is_syllable_start)
+                    .nth(syllable)
+                    .unwrap()
+                    .0;
+                let seeked: Vec<JamoInStream> = stream.by_ref().collect();
+                assert_eq!(seeked, fresh[start..], "{value} from syllable {syllable}");
+                let syllables = |jamos: &[JamoInStream]| -> Vec<(Option<char>, Option<char>)> {
+                    jamos
+                        .iter()
+                        .map(|jamo| (jamo.curr_syllable, jamo.next_syllable))
+                        .collect()
+                };
+                assert_eq!(syllables(&seeked), syllables(&fresh[start..]));
+            }
+        }
+    }
 }
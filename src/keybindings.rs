@@ -0,0 +1,388 @@
+/// Key bindings for the player, which can be customized with a
+/// config file.
+///
+/// The config file uses a small subset of TOML: each line maps an
+/// action name to a key, or to an array of keys, e.g.:
+///
+/// ```toml
+/// # Vim-style navigation
+/// next_line = ["j", "Down"]
+/// prev_line = "k"
+/// quit = "q"
+/// ```
+///
+/// Any action that isn't mentioned keeps its default keys.
+use anyhow::{Result, anyhow};
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+
+use crate::glyphs::Glyphs;
+
+/// Something the user can do in the player by pressing a key.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Action {
+    PrevLine,
+    NextLine,
+    PrevSyllable,
+    NextSyllable,
+    PlayLine,
+    RepeatLine,
+    Pause,
+    Rewind,
+    SeekPrevWord,
+    SeekNextWord,
+    TogglePronounced,
+    ToggleAutoScroll,
+    SeekToTime,
+    ShiftLinesEarlier,
+    ShiftLinesLater,
+    SaveLyrics,
+    Quit,
+}
+
+impl Action {
+    /// All actions, in the order they're shown in the help.
+    pub const ALL: [Action; 17] = [
+        Action::PrevLine,
+        Action::NextLine,
+        Action::PrevSyllable,
+        Action::NextSyllable,
+        Action::PlayLine,
+        Action::RepeatLine,
+        Action::Pause,
+        Action::Rewind,
+        Action::SeekPrevWord,
+        Action::SeekNextWord,
+        Action::TogglePronounced,
+        Action::ToggleAutoScroll,
+        Action::SeekToTime,
+        Action::ShiftLinesEarlier,
+        Action::ShiftLinesLater,
+        Action::SaveLyrics,
+        Action::Quit,
+    ];
+
+    /// The name of the action in the config file.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::PrevLine => "prev_line",
+            Action::NextLine => "next_line",
+            Action::PrevSyllable => "prev_syllable",
+            Action::NextSyllable => "next_syllable",
+            Action::PlayLine => "play_line",
+            Action::RepeatLine => "repeat_line",
+            Action::Pause => "pause",
+            Action::Rewind => "rewind",
+            Action::SeekPrevWord => "seek_prev_word",
+            Action::SeekNextWord => "seek_next_word",
+            Action::TogglePronounced => "toggle_pronounced",
+            Action::ToggleAutoScroll => "toggle_auto_scroll",
+            Action::SeekToTime => "seek_to_time",
+            Action::ShiftLinesEarlier => "shift_lines_earlier",
+            Action::ShiftLinesLater => "shift_lines_later",
+            Action::SaveLyrics => "save_lyrics",
+            Action::Quit => "quit",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Action> {
+        Action::ALL.into_iter().find(|action| action.name() == name)
+    }
+
+    fn default_keys(&self) -> Vec<KeyEvent> {
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        let ctrl = |ch| KeyEvent::new(KeyCode::Char(ch), KeyModifiers::CONTROL);
+        match self {
+            Action::PrevLine => vec![key(KeyCode::Up), ctrl('p')],
+            Action::NextLine => vec![key(KeyCode::Down), ctrl('n')],
+            Action::PrevSyllable => vec![key(KeyCode::Left), ctrl('b')],
+            Action::NextSyllable => vec![key(KeyCode::Right), ctrl('f')],
+            Action::PlayLine => vec![key(KeyCode::Enter)],
+            Action::RepeatLine => vec![key(KeyCode::Char('r'))],
+            Action::Pause => vec![key(KeyCode::Char(' '))],
+            Action::Rewind => vec![key(KeyCode::Char('b'))],
+            Action::SeekPrevWord => vec![key(KeyCode::Char('['))],
+            Action::SeekNextWord => vec![key(KeyCode::Char(']'))],
+            Action::TogglePronounced => vec![key(KeyCode::Char('p'))],
+            Action::ToggleAutoScroll => vec![key(KeyCode::Char('a'))],
+            Action::SeekToTime => vec![key(KeyCode::Char('t'))],
+            Action::ShiftLinesEarlier => vec![key(KeyCode::Char('-'))],
+            Action::ShiftLinesLater => vec![key(KeyCode::Char('='))],
+            Action::SaveLyrics => vec![key(KeyCode::Char('s'))],
+            Action::Quit => vec![key(KeyCode::Esc)],
+        }
+    }
+}
+
+pub struct KeyBindings {
+    /// Each action, along with the keys that trigger it.
+    bindings: Vec<(Action, Vec<KeyEvent>)>,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        KeyBindings {
+            bindings: Action::ALL
+                .into_iter()
+                .map(|action| (action, action.default_keys()))
+                .collect(),
+        }
+    }
+}
+
+impl KeyBindings {
+    /// Parses the given config, using the default keys for any
+    /// actions it doesn't mention.
+    pub fn parse(config: &str) -> Result<Self> {
+        let mut key_bindings = KeyBindings::default();
+        for (idx, line) in config.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with('[') {
+                continue;
+            }
+            let line_number = idx + 1;
+            let Some((name, keys)) = line.split_once('=') else {
+                return Err(anyhow!("Expected 'action = \"key\"' on line {line_number}"));
+            };
+            let name = name.trim();
+            let Some(action) = Action::from_name(name) else {
+                return Err(anyhow!("Unknown action '{name}' on line {line_number}"));
+            };
+            let keys = parse_key_list(keys.trim())
+                .ok_or_else(|| anyhow!("Invalid keys for '{name}' on line {line_number}"))?;
+            for (existing_action, existing_keys) in key_bindings.bindings.iter_mut() {
+                if *existing_action == action {
+                    *existing_keys = keys.clone();
+                }
+            }
+        }
+        Ok(key_bindings)
+    }
+
+    /// Returns the action triggered by the given event, if any.
+    pub fn get_action(&self, event: &Event) -> Option<Action> {
+        let Event::Key(key_event) = event else {
+            return None;
+        };
+        self.bindings
+            .iter()
+            .find(|(_, keys)| keys.contains(key_event))
+            .map(|(action, _)| *action)
+    }
+
+    /// Returns a line of help for each action that has a key bound
+    /// to it, showing the first key that triggers it, drawn with the
+    /// given glyphs.
+    pub fn help_lines(&self, rewind_secs: u64, glyphs: &Glyphs) -> Vec<String> {
+        self.bindings
+            .iter()
+            .filter_map(|(action, keys)| {
+                let key = keys.first()?;
+                let description = match action {
+                    Action::PrevLine => "prev line".to_owned(),
+                    Action::NextLine => "next line".to_owned(),
+                    Action::PrevSyllable => "prev syllable".to_owned(),
+                    Action::NextSyllable => "next syllable".to_owned(),
+                    Action::PlayLine => "play current line".to_owned(),
+                    Action::RepeatLine => "repeat current line".to_owned(),
+                    Action::Pause => "pause/unpause".to_owned(),
+                    Action::Rewind => format!("rewind {rewind_secs} seconds"),
+                    Action::SeekPrevWord => "seek to prev word".to_owned(),
+                    Action::SeekNextWord => "seek to next word".to_owned(),
+                    Action::TogglePronounced => "show pronunciation".to_owned(),
+                    Action::ToggleAutoScroll => "auto-scroll lyrics".to_owned(),
+                    Action::SeekToTime => "seek to time".to_owned(),
+                    Action::ShiftLinesEarlier => "shift lines from here earlier".to_owned(),
+                    Action::ShiftLinesLater => "shift lines from here later".to_owned(),
+                    Action::SaveLyrics => "save shifted lyrics".to_owned(),
+                    Action::Quit => "quit".to_owned(),
+                };
+                Some(format!("{:<5} - {description}", format_key(key, glyphs)))
+            })
+            .collect()
+    }
+}
+
+/// Parses either a single quoted key, or an array of them.
+fn parse_key_list(value: &str) -> Option<Vec<KeyEvent>> {
+    let keys = match value.strip_prefix('[') {
+        Some(rest) => rest
+            .strip_suffix(']')?
+            .split(',')
+            .map(|key| key.trim())
+            .filter(|key| !key.is_empty())
+            .map(parse_quoted_key)
+            .collect::<Option<Vec<_>>>()?,
+        None => vec![parse_quoted_key(value)?],
+    };
+    Some(keys)
+}
+
+fn parse_quoted_key(value: &str) -> Option<KeyEvent> {
+    let key = value.strip_prefix('"')?.strip_suffix('"')?;
+    parse_key(key)
+}
+
+/// Parses a key name like "q", "Space", "Down" or "Ctrl-n".
+fn parse_key(value: &str) -> Option<KeyEvent> {
+    if let Some(rest) = value.strip_prefix("Ctrl-") {
+        let key = parse_key(rest)?;
+        return Some(KeyEvent::new(key.code, KeyModifiers::CONTROL));
+    }
+    let code = match value {
+        "Space" => KeyCode::Char(' '),
+        "Enter" => KeyCode::Enter,
+        "Esc" => KeyCode::Esc,
+        "Tab" => KeyCode::Tab,
+        "Backspace" => KeyCode::Backspace,
+        "Up" => KeyCode::Up,
+        "Down" => KeyCode::Down,
+        "Left" => KeyCode::Left,
+        "Right" => KeyCode::Right,
+        _ => {
+            let mut chars = value.chars();
+            let ch = chars.next()?;
+            if chars.next().is_some() {
+                return None;
+            }
+            KeyCode::Char(ch)
+        }
+    };
+    Some(KeyEvent::new(code, KeyModifiers::NONE))
+}
+
+/// Formats the given key for display in the help.
+fn format_key(key: &KeyEvent, glyphs: &Glyphs) -> String {
+    let name = match key.code {
+        KeyCode::Char(' ') => "Space".to_owned(),
+        KeyCode::Char(ch) => ch.to_uppercase().to_string(),
+        KeyCode::Enter => "Enter".to_owned(),
+        KeyCode::Esc => "Esc".to_owned(),
+        KeyCode::Tab => "Tab".to_owned(),
+        KeyCode::Backspace => "Bksp".to_owned(),
+        KeyCode::Up => glyphs.up.to_owned(),
+        KeyCode::Down => glyphs.down.to_owned(),
+        KeyCode::Left => glyphs.left.to_owned(),
+        KeyCode::Right => glyphs.right.to_owned(),
+        _ => "?".to_owned(),
+    };
+    if key.modifiers.contains(KeyModifiers::CONTROL) {
+        format!("^{name}")
+    } else {
+        name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+
+    use crate::{
+        glyphs::Glyphs,
+        keybindings::{Action, KeyBindings},
+    };
+
+    fn key(code: KeyCode) -> Event {
+        Event::Key(KeyEvent::new(code, KeyModifiers::NONE))
+    }
+
+    #[test]
+    fn test_defaults_are_used_without_config() {
+        let bindings = KeyBindings::parse("").unwrap();
+        assert_eq!(bindings.get_action(&key(KeyCode::Esc)), Some(Action::Quit));
+        assert_eq!(
+            bindings.get_action(&Event::Key(KeyEvent::new(
+                KeyCode::Char('n'),
+                KeyModifiers::CONTROL
+            ))),
+            Some(Action::NextLine)
+        );
+        assert_eq!(bindings.get_action(&key(KeyCode::Char('z'))), None);
+    }
+
+    #[test]
+    fn test_default_keys_map_to_actions() {
+        let bindings = KeyBindings::default();
+        let ctrl = |ch| Event::Key(KeyEvent::new(KeyCode::Char(ch), KeyModifiers::CONTROL));
+        for (event, action) in [
+            (key(KeyCode::Up), Action::PrevLine),
+            (ctrl('p'), Action::PrevLine),
+            (key(KeyCode::Down), Action::NextLine),
+            (ctrl('n'), Action::NextLine),
+            (key(KeyCode::Left), Action::PrevSyllable),
+            (ctrl('b'), Action::PrevSyllable),
+            (key(KeyCode::Right), Action::NextSyllable),
+            (ctrl('f'), Action::NextSyllable),
+            (key(KeyCode::Enter), Action::PlayLine),
+            (key(KeyCode::Char('r')), Action::RepeatLine),
+            (key(KeyCode::Char(' ')), Action::Pause),
+            (key(KeyCode::Char('b')), Action::Rewind),
+            (key(KeyCode::Char('[')), Action::SeekPrevWord),
+            (key(KeyCode::Char(']')), Action::SeekNextWord),
+            (key(KeyCode::Char('p')), Action::TogglePronounced),
+            (key(KeyCode::Char('a')), Action::ToggleAutoScroll),
+            (key(KeyCode::Char('t')), Action::SeekToTime),
+            (key(KeyCode::Char('-')), Action::ShiftLinesEarlier),
+            (key(KeyCode::Char('=')), Action::ShiftLinesLater),
+            (key(KeyCode::Char('s')), Action::SaveLyrics),
+            (key(KeyCode::Esc), Action::Quit),
+        ] {
+            assert_eq!(bindings.get_action(&event), Some(action), "{event:?}");
+        }
+        assert_eq!(bindings.get_action(&Event::FocusGained), None);
+    }
+
+    #[test]
+    fn test_config_overrides_only_given_actions() {
+        let config = r#"
+# Vim-style navigation
+[keys]
+next_line = ["j", "Down"]
+quit = "q"
+"#;
+        let bindings = KeyBindings::parse(config).unwrap();
+        assert_eq!(
+            bindings.get_action(&key(KeyCode::Char('j'))),
+            Some(Action::NextLine)
+        );
+        assert_eq!(
+            bindings.get_action(&key(KeyCode::Down)),
+            Some(Action::NextLine)
+        );
+        assert_eq!(
+            bindings.get_action(&key(KeyCode::Char('q'))),
+            Some(Action::Quit)
+        );
+        assert_eq!(bindings.get_action(&key(KeyCode::Esc)), None);
+        assert_eq!(
+            bindings.get_action(&key(KeyCode::Up)),
+            Some(Action::PrevLine)
+        );
+    }
+
+    #[test]
+    fn test_invalid_config_fails() {
+        assert!(KeyBindings::parse("jump = \"j\"").is_err());
+        assert!(KeyBindings::parse("quit = q").is_err());
+        assert!(KeyBindings::parse("quit = \"Ctrl-\"").is_err());
+        assert!(KeyBindings::parse("quit").is_err());
+    }
+
+    #[test]
+    fn test_help_lines_reflect_bindings() {
+        let bindings = KeyBindings::parse("quit = \"q\"\nrewind = \"Ctrl-r\"").unwrap();
+        let help_lines = bindings.help_lines(2, &Glyphs::UNICODE);
+        assert_eq!(help_lines[0], "↑     - prev line");
+        assert!(help_lines.contains(&"^R    - rewind 2 seconds".to_owned()));
+        assert!(help_lines.contains(&"Q     - quit".to_owned()));
+    }
+
+    #[test]
+    fn test_ascii_help_lines() {
+        let help_lines = KeyBindings::default().help_lines(2, &Glyphs::ASCII);
+        assert_eq!(help_lines[0], "Up    - prev line");
+        assert!(help_lines.contains(&"Right - next syllable".to_owned()));
+        for line in help_lines {
+            assert!(line.is_ascii(), "{line:?} isn't ASCII");
+        }
+    }
+}
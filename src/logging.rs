@@ -0,0 +1,110 @@
+/// Diagnostic logging, enabled with `--verbose`, or with `RUST_LOG` for
+/// finer control, e.g. `RUST_LOG=hangul_fun::lrc=trace`.
+///
+/// Log messages go to stderr, except while the player's TUI is
+/// running, when they'd corrupt the screen, so they go to a file
+/// instead.
+use anyhow::{Result, anyhow};
+use env_logger::{Builder, Target};
+use log::{Level, LevelFilter};
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, Write, stderr},
+    sync::Mutex,
+};
+
+/// The file that log messages are written to while the TUI is running.
+/// Each run's messages are appended to it, so earlier ones aren't lost.
+pub const LOG_FILENAME: &str = "hangul-fun.log";
+
+/// Where log messages are written to, or `None` for stderr.
+static LOG_FILE: Mutex<Option<File>> = Mutex::new(None);
+
+/// Writes log messages to `LOG_FILE`, or stderr if it isn't set.
+struct LogTarget;
+
+impl Write for LogTarget {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // There's nowhere to report a failure to log, so ignore it.
+        let Ok(mut file) = LOG_FILE.lock() else {
+            return Ok(buf.len());
+        };
+        match file.as_mut() {
+            Some(file) => file.write(buf),
+            None => stderr().write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let Ok(mut file) = LOG_FILE.lock() else {
+            return Ok(());
+        };
+        match file.as_mut() {
+            Some(file) => file.flush(),
+            None => stderr().flush(),
+        }
+    }
+}
+
+fn format_record(level: Level, target: &str, message: &str) -> String {
+    format!("[{level:<5} {target}] {message}")
+}
+
+/// Sets up logging. Debug messages are shown if `verbose` is true, and
+/// `RUST_LOG` can pick which ones are shown, with or without it.
+pub fn init_logging(verbose: bool) -> Result<()> {
+    Builder::new()
+        .filter_level(if verbose {
+            LevelFilter::Debug
+        } else {
+            LevelFilter::Off
+        })
+        .parse_default_env()
+        .format(|buf, record| {
+            let line = format_record(record.level(), record.target(), &record.args().to_string());
+            writeln!(buf, "{line}")
+        })
+        .target(Target::Pipe(Box::new(LogTarget)))
+        .try_init()
+        .map_err(|err| anyhow!("Unable to set up logging: {err}"))
+}
+
+/// Sends log messages to `LOG_FILENAME` until `log_to_stderr` is called.
+/// Does nothing if logging is disabled.
+pub fn log_to_file() -> Result<()> {
+    if log::max_level() == LevelFilter::Off {
+        return Ok(());
+    }
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(LOG_FILENAME)?;
+    if let Ok(mut target) = LOG_FILE.lock() {
+        *target = Some(file);
+    }
+    eprintln!("Logging to {LOG_FILENAME}.");
+    Ok(())
+}
+
+/// Sends log messages back to stderr.
+pub fn log_to_stderr() {
+    log::logger().flush();
+    if let Ok(mut target) = LOG_FILE.lock() {
+        *target = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use log::Level;
+
+    use crate::logging::format_record;
+
+    #[test]
+    fn test_format_record_works() {
+        assert_eq!(
+            format_record(Level::Info, "hangul_fun::play", "hi"),
+            "[INFO  hangul_fun::play] hi"
+        );
+    }
+}
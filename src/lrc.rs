@@ -5,21 +5,25 @@
 ///
 /// Alternatively see commit 641432df72165dbf81971a0acbed162123e6e3cb in
 /// this project's git history.
-use anyhow::Result;
+use std::{fs::read, path::Path, time::Duration};
+
+use anyhow::{Result, anyhow, bail};
+use encoding_rs::{Encoding, UTF_8};
+use log::{info, warn};
 use nom::{
     IResult, Parser,
     bytes::complete::take_while1,
-    character::complete::{char, digit1, not_line_ending},
-    combinator::{map, map_res},
+    character::complete::{char, digit1},
+    combinator::{map, map_res, opt, rest},
     multi::many1,
-    sequence::delimited,
+    sequence::{delimited, preceded},
 };
 
 /// Simple lyrics format.
 ///
 /// Each entry is a tuple consisting of the time in milliseconds
 /// at which the given line of lyrics (a string) is performed.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Default)]
 pub struct SimpleLyrics(pub Vec<(u64, String)>);
 
 /// Synced lyrics format.
@@ -29,7 +33,7 @@ pub struct SimpleLyrics(pub Vec<(u64, String)>);
 /// itself is broken up into its own list of tuples, specifying
 /// the time in milliseconds at which each word or phrase is
 /// performed.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Default)]
 pub struct SyncedLyrics(pub Vec<(u64, Vec<(u64, String)>)>);
 
 #[derive(Debug, Clone, PartialEq)]
@@ -38,7 +42,147 @@ pub enum Lyrics {
     SyncedLyrics(SyncedLyrics),
 }
 
+impl SimpleLyrics {
+    /// Creates lyrics from (timestamp, line) pairs, sorting them by
+    /// timestamp. Lines with the same timestamp keep their order.
+    pub fn from_pairs(pairs: impl IntoIterator<Item = (u64, String)>) -> Self {
+        let mut lines: Vec<(u64, String)> = pairs.into_iter().collect();
+        lines.sort_by_key(|(timestamp, _)| *timestamp);
+        SimpleLyrics(lines)
+    }
+
+    /// Returns an error if the lines aren't sorted by timestamp.
+    pub fn validate(&self) -> Result<()> {
+        validate_timestamps("Line", self.0.iter().map(|(timestamp, _)| *timestamp))
+    }
+
+    /// Returns the time of the last line of lyrics, or None if there
+    /// are no lyrics.
+    pub fn total_duration(&self) -> Option<Duration> {
+        self.0
+            .iter()
+            .map(|(timestamp, _)| *timestamp)
+            .max()
+            .map(Duration::from_millis)
+    }
+
+    /// Returns the lines of lyrics performed at or after `start`, but
+    /// before `end`.
+    pub fn lines_between(&self, start: Duration, end: Duration) -> Vec<&(u64, String)> {
+        self.0
+            .iter()
+            .filter(|(timestamp, _)| is_between(*timestamp, start, end))
+            .collect()
+    }
+
+    /// Returns each line of lyrics along with when it starts and ends.
+    /// Each line ends when the next line with a later timestamp starts,
+    /// except for the last ones, which are assumed to last for
+    /// `default_tail`.
+    ///
+    /// The lines are expected to be sorted by timestamp, so that this
+    /// can be done in a single pass over them, from the last one back.
+    pub fn with_end_times(&self, default_tail: Duration) -> Vec<(Duration, Duration, String)> {
+        let mut result = Vec::with_capacity(self.0.len());
+        // The timestamp of the line after the one being looked at, and
+        // of the first line after it with a later timestamp.
+        let mut following_timestamp: Option<u64> = None;
+        let mut next_timestamp: Option<u64> = None;
+        for (timestamp, line) in self.0.iter().rev() {
+            if let Some(following_timestamp) = following_timestamp
+                && following_timestamp > *timestamp
+            {
+                next_timestamp = Some(following_timestamp);
+            }
+            following_timestamp = Some(*timestamp);
+            let start = Duration::from_millis(*timestamp);
+            let end = match next_timestamp {
+                Some(next_timestamp) => Duration::from_millis(next_timestamp),
+                None => start + default_tail,
+            };
+            result.push((start, end, line.clone()));
+        }
+        result.reverse();
+        result
+    }
+}
+
 impl SyncedLyrics {
+    /// Adds a line performed at the given timestamp, consisting of the
+    /// given (timestamp, word) pairs. The line is inserted after any
+    /// existing lines at or before the timestamp, so the lyrics stay
+    /// sorted.
+    pub fn push_line(&mut self, timestamp: u64, words: Vec<(u64, String)>) {
+        let index = self
+            .0
+            .partition_point(|(existing, _)| *existing <= timestamp);
+        self.0.insert(index, (timestamp, words));
+    }
+
+    /// Returns an error if the lines aren't sorted by timestamp, or if
+    /// the words within any line aren't.
+    pub fn validate(&self) -> Result<()> {
+        validate_timestamps("Line", self.0.iter().map(|(timestamp, _)| *timestamp))?;
+        for (timestamp, words) in &self.0 {
+            validate_timestamps(
+                &format!("In line at {timestamp}ms, word"),
+                words.iter().map(|(timestamp, _)| *timestamp),
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Returns a copy of the lyrics in which the words of each line are
+    /// never timed before the word preceding them, as hand-written files
+    /// sometimes have. Such words are moved to the time of the preceding
+    /// word, keeping their order.
+    pub fn repair(&self) -> SyncedLyrics {
+        let mut fixed = 0;
+        let lines = self
+            .0
+            .iter()
+            .map(|(timestamp, words)| {
+                let mut prev = 0;
+                let words = words
+                    .iter()
+                    .map(|(word_timestamp, text)| {
+                        if *word_timestamp < prev {
+                            fixed += 1;
+                        }
+                        prev = prev.max(*word_timestamp);
+                        (prev, text.clone())
+                    })
+                    .collect();
+                (*timestamp, words)
+            })
+            .collect();
+        if fixed > 0 {
+            warn!("Repaired {fixed} word timestamp(s) that went backwards");
+        }
+        SyncedLyrics(lines)
+    }
+
+    /// Returns the time of the last line or word of lyrics, or None if
+    /// there are no lyrics.
+    pub fn total_duration(&self) -> Option<Duration> {
+        self.0
+            .iter()
+            .flat_map(|(timestamp, words)| {
+                std::iter::once(*timestamp).chain(words.iter().map(|(timestamp, _)| *timestamp))
+            })
+            .max()
+            .map(Duration::from_millis)
+    }
+
+    /// Returns the lines of lyrics performed at or after `start`, but
+    /// before `end`.
+    pub fn lines_between(&self, start: Duration, end: Duration) -> Vec<&(u64, Vec<(u64, String)>)> {
+        self.0
+            .iter()
+            .filter(|(timestamp, _)| is_between(*timestamp, start, end))
+            .collect()
+    }
+
     /// Convert SyncedLyrics to SimpleLyrics by joining all words in each line
     pub fn to_simple(&self) -> SimpleLyrics {
         let simple_entries: Vec<(u64, String)> = self
@@ -58,6 +202,188 @@ impl SyncedLyrics {
     }
 }
 
+impl Lyrics {
+    /// Returns the time of the last lyrics, or None if there are no lyrics.
+    pub fn total_duration(&self) -> Option<Duration> {
+        match self {
+            Lyrics::SimpleLyrics(simple_lyrics) => simple_lyrics.total_duration(),
+            Lyrics::SyncedLyrics(synced_lyrics) => synced_lyrics.total_duration(),
+        }
+    }
+
+    /// Returns an error if the lyrics' timestamps are out of order.
+    pub fn validate(&self) -> Result<()> {
+        match self {
+            Lyrics::SimpleLyrics(simple_lyrics) => simple_lyrics.validate(),
+            Lyrics::SyncedLyrics(synced_lyrics) => synced_lyrics.validate(),
+        }
+    }
+
+    /// Converts the lyrics to simple lyrics, joining the words of
+    /// each line if they're synced.
+    pub fn to_simple(&self) -> SimpleLyrics {
+        match self {
+            Lyrics::SimpleLyrics(simple_lyrics) => simple_lyrics.clone(),
+            Lyrics::SyncedLyrics(synced_lyrics) => synced_lyrics.to_simple(),
+        }
+    }
+
+    /// Returns only the lines of lyrics performed at or after `since`,
+    /// but before `until`. Leaving either out doesn't limit that end.
+    pub fn between(&self, since: Option<Duration>, until: Option<Duration>) -> Result<Lyrics> {
+        let start = since.unwrap_or(Duration::ZERO);
+        let end = until.unwrap_or(Duration::MAX);
+        if start > end {
+            bail!(
+                "The start time {} is after the end time {}",
+                format_timestamp(start.as_millis() as u64),
+                format_timestamp(end.as_millis() as u64)
+            );
+        }
+        Ok(match self {
+            Lyrics::SimpleLyrics(simple_lyrics) => Lyrics::SimpleLyrics(SimpleLyrics(
+                simple_lyrics
+                    .lines_between(start, end)
+                    .into_iter()
+                    .cloned()
+                    .collect(),
+            )),
+            Lyrics::SyncedLyrics(synced_lyrics) => Lyrics::SyncedLyrics(SyncedLyrics(
+                synced_lyrics
+                    .lines_between(start, end)
+                    .into_iter()
+                    .cloned()
+                    .collect(),
+            )),
+        })
+    }
+
+    /// Moves every line and word performed at or after `start` by the
+    /// given number of milliseconds, keeping the lines in order.
+    pub fn shift_from(&mut self, start: u64, delta_ms: i64) {
+        let shift = |timestamp: &mut u64| {
+            if *timestamp >= start {
+                *timestamp = timestamp.saturating_add_signed(delta_ms);
+            }
+        };
+        match self {
+            Lyrics::SimpleLyrics(SimpleLyrics(lines)) => {
+                lines.iter_mut().for_each(|(timestamp, _)| shift(timestamp));
+                lines.sort_by_key(|(timestamp, _)| *timestamp);
+            }
+            Lyrics::SyncedLyrics(SyncedLyrics(lines)) => {
+                for (timestamp, words) in lines.iter_mut() {
+                    shift(timestamp);
+                    words.iter_mut().for_each(|(timestamp, _)| shift(timestamp));
+                }
+                lines.sort_by_key(|(timestamp, _)| *timestamp);
+            }
+        }
+    }
+
+    /// Serializes the lyrics in LRC format, with one line of lyrics
+    /// per line of text. Timestamps are written with millisecond
+    /// precision.
+    pub fn to_lrc(&self) -> String {
+        let mut result = String::new();
+        match self {
+            Lyrics::SimpleLyrics(SimpleLyrics(lines)) => {
+                for (timestamp, text) in lines {
+                    result.push_str(&format!("[{}]{text}\n", format_timestamp(*timestamp)));
+                }
+            }
+            Lyrics::SyncedLyrics(SyncedLyrics(lines)) => {
+                for (timestamp, words) in lines {
+                    result.push_str(&format!("[{}]", format_timestamp(*timestamp)));
+                    for (timestamp, text) in words {
+                        result.push_str(&format!("<{}>{text}", format_timestamp(*timestamp)));
+                    }
+                    result.push('\n');
+                }
+            }
+        }
+        result
+    }
+}
+
+/// Merges the given lyrics into a single timeline, sorted by timestamp,
+/// leaving out any lines that are identical to an earlier one at the
+/// same time.
+///
+/// If any of the lyrics are simple, the result is simple too, and any
+/// synced lyrics are converted to simple ones.
+pub fn merge_lyrics(files: &[Lyrics]) -> Lyrics {
+    let all_synced = files
+        .iter()
+        .all(|lyrics| matches!(lyrics, Lyrics::SyncedLyrics(_)));
+    if all_synced && !files.is_empty() {
+        let mut merged = SyncedLyrics::default();
+        for lyrics in files {
+            if let Lyrics::SyncedLyrics(synced_lyrics) = lyrics {
+                for line in &synced_lyrics.0 {
+                    if !merged.0.contains(line) {
+                        merged.push_line(line.0, line.1.clone());
+                    }
+                }
+            }
+        }
+        Lyrics::SyncedLyrics(merged)
+    } else {
+        let mut lines: Vec<(u64, String)> = vec![];
+        for lyrics in files {
+            for line in lyrics.to_simple().0 {
+                if !lines.contains(&line) {
+                    lines.push(line);
+                }
+            }
+        }
+        Lyrics::SimpleLyrics(SimpleLyrics::from_pairs(lines))
+    }
+}
+
+/// Formats the given milliseconds as a `mm:ss.xxx` LRC timestamp.
+fn format_timestamp(millis: u64) -> String {
+    format!(
+        "{:02}:{:02}.{:03}",
+        millis / 60_000,
+        (millis / 1000) % 60,
+        millis % 1000
+    )
+}
+
+/// Returns an error describing the first timestamp that's earlier than
+/// the one before it, if any. `what` describes what the timestamps
+/// belong to, e.g. "Line".
+fn validate_timestamps(what: &str, timestamps: impl Iterator<Item = u64>) -> Result<()> {
+    let mut prev: Option<u64> = None;
+    for (index, timestamp) in timestamps.enumerate() {
+        if let Some(prev) = prev
+            && timestamp < prev
+        {
+            bail!(
+                "{what} {} is at {timestamp}ms, which is before the previous one at {prev}ms",
+                index + 1
+            );
+        }
+        prev = Some(timestamp);
+    }
+    Ok(())
+}
+
+fn is_between(timestamp: u64, start: Duration, end: Duration) -> bool {
+    let timestamp = Duration::from_millis(timestamp);
+    start <= timestamp && timestamp < end
+}
+
+/// Parses a time given as `mm:ss`, or with a fraction of a second like
+/// the timestamps in LRC files, e.g. `01:23.45`.
+pub fn parse_time(value: &str) -> Result<Duration> {
+    match parse_timestamp(value.trim()) {
+        Ok(("", millis)) => Ok(Duration::from_millis(millis)),
+        _ => Err(anyhow!("Invalid time '{value}', expected mm:ss")),
+    }
+}
+
 /// Parse minutes:seconds.centiseconds or minutes:seconds.milliseconds format
 fn parse_timestamp(input: &str) -> IResult<&str, u64> {
     map(
@@ -67,26 +393,20 @@ fn parse_timestamp(input: &str) -> IResult<&str, u64> {
             map_res(take_while1(|c: char| c.is_ascii_digit()), |s: &str| {
                 s.parse::<u64>()
             }),
-            char('.'),
-            map_res(take_while1(|c: char| c.is_ascii_digit()), |s: &str| {
-                s.parse::<u64>()
-            }),
+            // Some hand-written files leave out the fraction entirely.
+            opt(preceded(
+                char('.'),
+                take_while1(|c: char| c.is_ascii_digit()),
+            )),
         ),
-        |(minutes, _, seconds, _, fraction)| {
-            let fraction_len = fraction.to_string().len();
-            let milliseconds = if fraction_len == 2 {
-                // Centiseconds (hundredths)
-                fraction * 10
-            } else if fraction_len == 3 {
-                // Milliseconds (thousandths)
-                fraction
-            } else {
-                // Handle other cases by padding or truncating to 3 digits
-                if fraction_len < 3 {
-                    fraction * 10_u64.pow((3 - fraction_len) as u32)
-                } else {
-                    fraction / 10_u64.pow((fraction_len - 3) as u32)
-                }
+        |(minutes, _, seconds, fraction): (u64, char, u64, Option<&str>)| {
+            // The fraction may be in centiseconds (hundredths), milliseconds
+            // (thousandths) or anything else, so pad or truncate it to
+            // milliseconds. This is done on the digits themselves, since
+            // leading zeros are significant.
+            let milliseconds: u64 = match fraction {
+                Some(fraction) => format!("{fraction:0<3.3}").parse().unwrap_or(0),
+                None => 0,
             };
             minutes * 60 * 1000 + seconds * 1000 + milliseconds
         },
@@ -104,7 +424,10 @@ fn parse_timestamp_tags(input: &str) -> IResult<&str, Vec<u64>> {
     many1(parse_timestamp_tag).parse(input)
 }
 
-/// Parse a word/phrase with its timestamp in synced format
+/// Parse a word/phrase with its timestamp in synced format.
+///
+/// Any untagged text following the word, through the end of
+/// the line, is considered part of it.
 fn parse_synced_word(input: &str) -> IResult<&str, (u64, String)> {
     let (input, _) = char('<')(input)?;
     let (input, timestamp) = parse_timestamp(input)?;
@@ -112,16 +435,20 @@ fn parse_synced_word(input: &str) -> IResult<&str, (u64, String)> {
 
     // Try to find the next '<' or use the rest of the line
     let end_pos = input.find('<').unwrap_or(input.len());
-    let text = &input[..end_pos];
+    let text = input[..end_pos].trim_end_matches('\r');
     let remaining = &input[end_pos..];
 
     Ok((remaining, (timestamp, text.to_string())))
 }
 
-/// Parse a complete synced lyrics line
+/// Parse a complete synced lyrics line.
+///
+/// Some dialects leave out the line's `[mm:ss]` tag and start it with
+/// the first word's tag, in which case the line starts with that word.
 fn parse_synced_line(input: &str) -> IResult<&str, Vec<(u64, Vec<(u64, String)>)>> {
-    let (input, timestamps) = parse_timestamp_tags(input)?;
+    let (input, timestamps) = opt(parse_timestamp_tags).parse(input)?;
     let (input, words) = many1(parse_synced_word).parse(input)?;
+    let timestamps = timestamps.unwrap_or_else(|| vec![words[0].0]);
 
     Ok((
         input,
@@ -135,7 +462,10 @@ fn parse_synced_line(input: &str) -> IResult<&str, Vec<(u64, Vec<(u64, String)>)
 /// Parse a simple lyrics line
 fn parse_simple_line(input: &str) -> IResult<&str, Vec<(u64, String)>> {
     let (input, timestamps) = parse_timestamp_tags(input)?;
-    let (input, text) = not_line_ending(input)?;
+    // Lines have already been split, so the rest of the input is the text.
+    // Files exported from Windows tools can leave stray carriage returns.
+    let (input, text) = rest(input)?;
+    let text = text.trim_end_matches('\r');
 
     Ok((
         input,
@@ -146,12 +476,96 @@ fn parse_simple_line(input: &str) -> IResult<&str, Vec<(u64, String)>> {
     ))
 }
 
+/// Metadata from the ID tags of an LRC file, e.g. `[ti:Song Title]`.
+#[derive(Debug, Default, PartialEq)]
+pub struct LrcMetadata {
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub album: Option<String>,
+}
+
+/// Parses an ID tag line like `[ar:Artist Name]` into its key and value.
+fn parse_id_tag(line: &str) -> Option<(&str, &str)> {
+    let inner = line.trim().strip_prefix('[')?.strip_suffix(']')?;
+    let (key, value) = inner.split_once(':')?;
+    if key.is_empty() || !key.chars().all(|ch| ch.is_ascii_alphabetic()) {
+        return None;
+    }
+    Some((key, value.trim()))
+}
+
+/// Returns the ID tag lines of the given LRC file, e.g. `[ar:Artist Name]`,
+/// so they can be kept when the lyrics are written back to it.
+pub fn id_tag_lines(lyrics: &str) -> Vec<String> {
+    lyrics
+        .lines()
+        .filter(|line| parse_id_tag(line).is_some())
+        .map(|line| line.trim().to_owned())
+        .collect()
+}
+
+/// Parses the title, artist and album from the ID tags of the given LRC
+/// file. Tags that are missing or empty are `None`.
+pub fn parse_lrc_metadata(lyrics: &str) -> LrcMetadata {
+    let mut metadata = LrcMetadata::default();
+    for (key, value) in lyrics.lines().filter_map(parse_id_tag) {
+        if value.is_empty() {
+            continue;
+        }
+        let field = match key {
+            "ti" => &mut metadata.title,
+            "ar" => &mut metadata.artist,
+            "al" => &mut metadata.album,
+            _ => continue,
+        };
+        *field = Some(value.to_owned());
+    }
+    metadata
+}
+
+/// Decodes the contents of an LRC file.
+///
+/// If `encoding` is given, it's the label of the encoding to use, e.g.
+/// "euc-kr" (which also covers CP949). Otherwise the encoding is
+/// detected from the file's byte order mark, defaulting to UTF-8.
+pub fn decode_lrc(bytes: &[u8], encoding: Option<&str>) -> Result<String> {
+    let encoding = match encoding {
+        Some(label) => Encoding::for_label(label.as_bytes())
+            .ok_or_else(|| anyhow!("Unknown encoding: {label}"))?,
+        None => match Encoding::for_bom(bytes) {
+            Some((encoding, _)) => {
+                info!("Detected {} byte order mark", encoding.name());
+                encoding
+            }
+            None => UTF_8,
+        },
+    };
+    let (lyrics, had_errors) = encoding.decode_with_bom_removal(bytes);
+    if had_errors {
+        if encoding == UTF_8 {
+            bail!("Lyrics aren't valid UTF-8 (if they're EUC-KR, try `--encoding euc-kr`)");
+        }
+        bail!("Lyrics aren't valid {}", encoding.name());
+    }
+    Ok(lyrics.into_owned())
+}
+
+/// Reads the LRC file at the given path, decoding it with `decode_lrc`.
+pub fn read_lrc<P: AsRef<Path>>(path: P, encoding: Option<&str>) -> Result<String> {
+    let path = path.as_ref();
+    decode_lrc(&read(path)?, encoding)
+        .map_err(|err| anyhow!("Couldn't decode {}: {err}", path.to_string_lossy()))
+}
+
 /// Parse the given LRC file. Detects if it is in simple or
 /// synced format and parses it, returning the result.
 ///
 /// Only lines of lyrics are parsed. Any line that doesn't
 /// represent lyrics is ignored.
 pub fn parse_lrc(lyrics: String) -> Result<Lyrics> {
+    // Strip any UTF-8 byte order mark, which would otherwise break
+    // parsing of the first line.
+    let lyrics = lyrics.strip_prefix('\u{feff}').unwrap_or(&lyrics);
     let lines: Vec<&str> = lyrics.lines().collect();
 
     // First, check if any line contains synced format
@@ -159,28 +573,29 @@ pub fn parse_lrc(lyrics: String) -> Result<Lyrics> {
         .iter()
         .any(|line| line.contains("<") && line.contains(">"));
 
-    if is_synced {
-        let mut synced_lyrics = Vec::new();
+    let lyrics = if is_synced {
+        let mut synced_lyrics = SyncedLyrics::default();
 
-        for line in lines {
+        for (idx, line) in lines.into_iter().enumerate() {
             if line.trim().is_empty() {
                 continue;
             }
 
             if let Ok((_, entries)) = parse_synced_line(line) {
-                synced_lyrics.extend(entries);
+                for (ts, words) in entries {
+                    synced_lyrics.push_line(ts, words);
+                }
+            } else if parse_id_tag(line).is_none() {
+                // Ignore lines that don't parse as synced lyrics
+                warn!("Ignoring unparseable synced LRC line {}: {line:?}", idx + 1);
             }
-            // Ignore lines that don't parse as synced lyrics
         }
 
-        // Sort by timestamp
-        synced_lyrics.sort_by_key(|(ts, _)| *ts);
-
-        Ok(Lyrics::SyncedLyrics(SyncedLyrics(synced_lyrics)))
+        Lyrics::SyncedLyrics(synced_lyrics)
     } else {
         let mut simple_lyrics = Vec::new();
 
-        for line in lines {
+        for (idx, line) in lines.into_iter().enumerate() {
             if line.trim().is_empty() {
                 continue;
             }
@@ -192,15 +607,20 @@ pub fn parse_lrc(lyrics: String) -> Result<Lyrics> {
 
             if let Ok((_, entries)) = parse_simple_line(line) {
                 simple_lyrics.extend(entries);
+            } else if parse_id_tag(line).is_none() {
+                // Ignore lines that don't parse as simple lyrics
+                warn!("Ignoring unparseable LRC line {}: {line:?}", idx + 1);
             }
-            // Ignore lines that don't parse as simple lyrics
         }
 
-        // Sort by timestamp
-        simple_lyrics.sort_by_key(|(ts, _)| *ts);
+        Lyrics::SimpleLyrics(SimpleLyrics::from_pairs(simple_lyrics))
+    };
 
-        Ok(Lyrics::SimpleLyrics(SimpleLyrics(simple_lyrics)))
+    // Out of order timestamps are still playable, but probably a mistake.
+    if let Err(err) = lyrics.validate() {
+        warn!("{err}");
     }
+    Ok(lyrics)
 }
 
 #[cfg(test)]
@@ -217,6 +637,53 @@ mod tests {
         assert_eq!(parse_timestamp("00:00.12"), Ok(("", 120)));
         assert_eq!(parse_timestamp("00:00.123"), Ok(("", 123)));
         assert_eq!(parse_timestamp("00:00.1234"), Ok(("", 123)));
+        assert_eq!(parse_timestamp("00:00.05"), Ok(("", 50)));
+        assert_eq!(parse_timestamp("00:00.007"), Ok(("", 7)));
+        assert_eq!(parse_timestamp("01:23"), Ok(("", 83000)));
+    }
+
+    #[test]
+    fn test_parse_time() {
+        assert_eq!(parse_time("1:30").unwrap(), Duration::from_secs(90));
+        assert_eq!(
+            parse_time("01:23.45").unwrap(),
+            Duration::from_millis(83450)
+        );
+        assert!(parse_time("1:30 later").is_err());
+        assert!(parse_time("90").is_err());
+    }
+
+    #[test]
+    fn test_lyrics_between() {
+        let lyrics = Lyrics::SimpleLyrics(SimpleLyrics(vec![
+            (5000, "First".to_string()),
+            (10000, "Second".to_string()),
+            (15000, "Third".to_string()),
+        ]));
+        assert_eq!(
+            lyrics
+                .between(Some(Duration::from_secs(10)), Some(Duration::from_secs(15)))
+                .unwrap(),
+            Lyrics::SimpleLyrics(SimpleLyrics(vec![(10000, "Second".to_string())]))
+        );
+        assert_eq!(
+            lyrics.between(None, Some(Duration::from_secs(10))).unwrap(),
+            Lyrics::SimpleLyrics(SimpleLyrics(vec![(5000, "First".to_string())]))
+        );
+        assert_eq!(lyrics.between(None, None).unwrap(), lyrics);
+        assert_eq!(
+            lyrics
+                .between(Some(Duration::from_secs(20)), Some(Duration::from_secs(10)))
+                .unwrap_err()
+                .to_string(),
+            "The start time 00:20.000 is after the end time 00:10.000"
+        );
+    }
+
+    #[test]
+    fn test_parse_timestamp_tag_without_fraction() {
+        assert_eq!(parse_timestamp_tag("[01:23]"), Ok(("", 83000)));
+        assert_eq!(parse_timestamp_tag("[01:23.45]"), Ok(("", 83450)));
     }
 
     #[test]
@@ -292,6 +759,47 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_synced_lyrics_with_trailing_untagged_text() {
+        let lrc = r#"[00:12.34]<00:12.34>First <00:13.00>word and the rest"#;
+
+        let result = parse_lrc(lrc.to_string()).unwrap();
+
+        match result {
+            Lyrics::SyncedLyrics(SyncedLyrics(lyrics)) => {
+                assert_eq!(lyrics.len(), 1);
+                assert_eq!(
+                    lyrics[0].1,
+                    vec![
+                        (12340, "First ".to_string()),
+                        (13000, "word and the rest".to_string())
+                    ]
+                );
+            }
+            _ => panic!("Expected SyncedLyrics"),
+        }
+    }
+
+    #[test]
+    fn test_parse_synced_lyrics_without_line_tag() {
+        let lrc = "<00:12.34>First <00:13.00>word\n[00:14.00]<00:14.00>Next";
+
+        let result = parse_lrc(lrc.to_string()).unwrap();
+
+        match result {
+            Lyrics::SyncedLyrics(SyncedLyrics(lyrics)) => {
+                assert_eq!(lyrics.len(), 2);
+                assert_eq!(lyrics[0].0, 12340);
+                assert_eq!(
+                    lyrics[0].1,
+                    vec![(12340, "First ".to_string()), (13000, "word".to_string())]
+                );
+                assert_eq!(lyrics[1].0, 14000);
+            }
+            _ => panic!("Expected SyncedLyrics"),
+        }
+    }
+
     #[test]
     fn test_parse_multiple_timestamps() {
         let lrc = r#"[00:12.34][00:15.67]Line with multiple timestamps
@@ -357,6 +865,56 @@ Invalid line without timestamp
         }
     }
 
+    #[test]
+    fn test_parse_lyrics_with_bom() {
+        let lrc = "\u{feff}[00:12.34]First line\n[00:15.67]Second line";
+
+        let result = parse_lrc(lrc.to_string()).unwrap();
+
+        match result {
+            Lyrics::SimpleLyrics(SimpleLyrics(lyrics)) => {
+                assert_eq!(lyrics.len(), 2);
+                assert_eq!(lyrics[0], (12340, "First line".to_string()));
+                assert_eq!(lyrics[1], (15670, "Second line".to_string()));
+            }
+            _ => panic!("Expected SimpleLyrics"),
+        }
+    }
+
+    #[test]
+    fn test_parse_lyrics_with_crlf() {
+        let lrc = "[ar:Artist Name]\r\n[00:12.34]First line\r\r\n[00:15.67]Second line\r\n";
+
+        let result = parse_lrc(lrc.to_string()).unwrap();
+
+        match result {
+            Lyrics::SimpleLyrics(SimpleLyrics(lyrics)) => {
+                assert_eq!(lyrics.len(), 2);
+                assert_eq!(lyrics[0], (12340, "First line".to_string()));
+                assert_eq!(lyrics[1], (15670, "Second line".to_string()));
+            }
+            _ => panic!("Expected SimpleLyrics"),
+        }
+    }
+
+    #[test]
+    fn test_parse_synced_lyrics_with_bom_and_crlf() {
+        let lrc = "\u{feff}[00:12.34]<00:12.34>First <00:13.00>line\r\r\n";
+
+        let result = parse_lrc(lrc.to_string()).unwrap();
+
+        match result {
+            Lyrics::SyncedLyrics(SyncedLyrics(lyrics)) => {
+                assert_eq!(lyrics.len(), 1);
+                assert_eq!(
+                    lyrics[0].1,
+                    vec![(12340, "First ".to_string()), (13000, "line".to_string())]
+                );
+            }
+            _ => panic!("Expected SyncedLyrics"),
+        }
+    }
+
     #[test]
     fn test_sorting_by_timestamp() {
         let lrc = r#"[00:15.67]Second line
@@ -376,6 +934,24 @@ Invalid line without timestamp
         }
     }
 
+    #[test]
+    fn test_lines_sharing_a_timestamp_keep_file_order() {
+        let lrc = r#"[00:12.00]Next line
+[00:10.00]Lead vocals
+[00:10.00]Backing vocals"#;
+
+        let result = parse_lrc(lrc.to_string()).unwrap();
+
+        assert_eq!(
+            result,
+            Lyrics::SimpleLyrics(SimpleLyrics(vec![
+                (10000, "Lead vocals".to_string()),
+                (10000, "Backing vocals".to_string()),
+                (12000, "Next line".to_string()),
+            ]))
+        );
+    }
+
     #[test]
     fn test_synced_to_simple_conversion() {
         // Create a SyncedLyrics instance
@@ -444,4 +1020,319 @@ Invalid line without timestamp
         assert_eq!(simple_lyrics.0[0].0, 5000);
         assert_eq!(simple_lyrics.0[1].0, 10000);
     }
+
+    #[test]
+    fn test_total_duration() {
+        assert_eq!(SimpleLyrics(vec![]).total_duration(), None);
+        assert_eq!(SyncedLyrics(vec![]).total_duration(), None);
+
+        let lrc = r#"[00:12.34]First line
+[00:15.67]Second line"#;
+        let result = parse_lrc(lrc.to_string()).unwrap();
+        assert_eq!(result.total_duration(), Some(Duration::from_millis(15670)));
+
+        let lrc = r#"[00:12.34]<00:12.34>First <00:13.00>word"#;
+        let result = parse_lrc(lrc.to_string()).unwrap();
+        assert_eq!(result.total_duration(), Some(Duration::from_millis(13000)));
+    }
+
+    #[test]
+    fn test_with_end_times() {
+        let lyrics = SimpleLyrics(vec![
+            (12340, "First line".to_string()),
+            (15670, "Second line".to_string()),
+        ]);
+        assert_eq!(
+            lyrics.with_end_times(Duration::from_secs(5)),
+            vec![
+                (
+                    Duration::from_millis(12340),
+                    Duration::from_millis(15670),
+                    "First line".to_string()
+                ),
+                (
+                    Duration::from_millis(15670),
+                    Duration::from_millis(20670),
+                    "Second line".to_string()
+                ),
+            ]
+        );
+
+        let lyrics = SimpleLyrics(vec![(1000, "Only line".to_string())]);
+        assert_eq!(
+            lyrics.with_end_times(Duration::from_secs(3)),
+            vec![(
+                Duration::from_secs(1),
+                Duration::from_secs(4),
+                "Only line".to_string()
+            )]
+        );
+        assert!(
+            SimpleLyrics(vec![])
+                .with_end_times(Duration::ZERO)
+                .is_empty()
+        );
+
+        let lyrics = SimpleLyrics(vec![
+            (10000, "Lead".to_string()),
+            (10000, "Backing".to_string()),
+            (12000, "Next".to_string()),
+        ]);
+        let ends: Vec<Duration> = lyrics
+            .with_end_times(Duration::from_secs(1))
+            .into_iter()
+            .map(|(_, end, _)| end)
+            .collect();
+        assert_eq!(
+            ends,
+            vec![
+                Duration::from_secs(12),
+                Duration::from_secs(12),
+                Duration::from_secs(13)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_lines_between() {
+        let lyrics = SimpleLyrics(vec![
+            (12340, "First line".to_string()),
+            (15670, "Second line".to_string()),
+            (20000, "Third line".to_string()),
+        ]);
+
+        assert_eq!(
+            lyrics.lines_between(Duration::from_millis(15670), Duration::from_millis(20000)),
+            vec![&(15670, "Second line".to_string())]
+        );
+        assert!(
+            lyrics
+                .lines_between(Duration::from_secs(30), Duration::from_secs(40))
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_simple_from_pairs_sorts_by_timestamp() {
+        let lyrics = SimpleLyrics::from_pairs([
+            (15670, "Second line".to_string()),
+            (12340, "First line".to_string()),
+            (15670, "Also second line".to_string()),
+        ]);
+
+        assert_eq!(
+            lyrics,
+            SimpleLyrics(vec![
+                (12340, "First line".to_string()),
+                (15670, "Second line".to_string()),
+                (15670, "Also second line".to_string()),
+            ])
+        );
+        assert!(lyrics.validate().is_ok());
+    }
+
+    #[test]
+    fn test_synced_push_line_keeps_lines_sorted() {
+        let mut lyrics = SyncedLyrics::default();
+        lyrics.push_line(15000, vec![(15000, "Second".to_string())]);
+        lyrics.push_line(12000, vec![(12000, "First".to_string())]);
+
+        assert_eq!(
+            lyrics,
+            SyncedLyrics(vec![
+                (12000, vec![(12000, "First".to_string())]),
+                (15000, vec![(15000, "Second".to_string())]),
+            ])
+        );
+        assert!(lyrics.validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_fails_on_unsorted_timestamps() {
+        let lyrics = SimpleLyrics(vec![
+            (15670, "Second line".to_string()),
+            (12340, "First line".to_string()),
+        ]);
+        assert_eq!(
+            lyrics.validate().unwrap_err().to_string(),
+            "Line 2 is at 12340ms, which is before the previous one at 15670ms"
+        );
+
+        let lyrics = SyncedLyrics(vec![(
+            12000,
+            vec![(13000, "Second ".to_string()), (12000, "first".to_string())],
+        )]);
+        assert_eq!(
+            lyrics.validate().unwrap_err().to_string(),
+            "In line at 12000ms, word 2 is at 12000ms, which is before the previous one at 13000ms"
+        );
+    }
+
+    #[test]
+    fn test_parse_lrc_keeps_unsorted_word_timestamps() {
+        // They're warned about, rather than failing to parse.
+        let lrc = "[00:12.00]<00:13.00>Second <00:12.00>first";
+        let lyrics = parse_lrc(lrc.to_string()).unwrap();
+        assert!(lyrics.validate().is_err());
+        assert_eq!(
+            lyrics.to_simple().0,
+            vec![(12000, "Second first".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_repair_makes_word_timestamps_monotonic() {
+        let lyrics = SyncedLyrics(vec![
+            (
+                1000,
+                vec![
+                    (1000, "One ".to_string()),
+                    (1500, "two ".to_string()),
+                    (1200, "three ".to_string()),
+                    (1800, "four".to_string()),
+                ],
+            ),
+            (2000, vec![(2000, "Five".to_string())]),
+        ]);
+        assert!(lyrics.validate().is_err());
+
+        let repaired = lyrics.repair();
+        assert!(repaired.validate().is_ok());
+        assert_eq!(
+            repaired.0[0].1,
+            vec![
+                (1000, "One ".to_string()),
+                (1500, "two ".to_string()),
+                (1500, "three ".to_string()),
+                (1800, "four".to_string()),
+            ]
+        );
+        assert_eq!(repaired.0[1], lyrics.0[1]);
+        assert_eq!(repaired.repair(), repaired);
+    }
+
+    #[test]
+    fn test_to_lrc_round_trips() {
+        let lrc = "[00:12.340]First line\n[01:15.007]Second line\n";
+        let lyrics = parse_lrc(lrc.to_string()).unwrap();
+        assert_eq!(lyrics.to_lrc(), lrc);
+
+        let lrc = "[00:12.340]<00:12.340>First <00:13.000>word\n";
+        let lyrics = parse_lrc(lrc.to_string()).unwrap();
+        assert_eq!(lyrics.to_lrc(), lrc);
+    }
+
+    #[test]
+    fn test_shift_from() {
+        let mut lyrics = parse_lrc(
+            "[00:01.000]<00:01.000>One <00:02.500>two\n[00:03.000]<00:03.000>Three\n".to_string(),
+        )
+        .unwrap();
+        lyrics.shift_from(2500, 250);
+        assert_eq!(
+            lyrics.to_lrc(),
+            "[00:01.000]<00:01.000>One <00:02.750>two\n[00:03.250]<00:03.250>Three\n"
+        );
+
+        let mut lyrics = parse_lrc("[00:01.00]One\n[00:02.00]Two\n".to_string()).unwrap();
+        lyrics.shift_from(2000, -1500);
+        assert_eq!(lyrics.to_lrc(), "[00:00.500]Two\n[00:01.000]One\n");
+        lyrics.shift_from(0, -1000);
+        assert_eq!(lyrics.to_lrc(), "[00:00.000]Two\n[00:00.000]One\n");
+    }
+
+    #[test]
+    fn test_merge_simple_lyrics() {
+        let verse = Lyrics::SimpleLyrics(SimpleLyrics(vec![
+            (10000, "Verse one".to_string()),
+            (30000, "Chorus".to_string()),
+        ]));
+        let chorus = Lyrics::SimpleLyrics(SimpleLyrics(vec![
+            (20000, "Verse two".to_string()),
+            (30000, "Chorus".to_string()),
+            (30000, "Chorus harmony".to_string()),
+        ]));
+
+        assert_eq!(
+            merge_lyrics(&[verse, chorus]),
+            Lyrics::SimpleLyrics(SimpleLyrics(vec![
+                (10000, "Verse one".to_string()),
+                (20000, "Verse two".to_string()),
+                (30000, "Chorus".to_string()),
+                (30000, "Chorus harmony".to_string()),
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_merge_mixed_lyrics_downgrades_to_simple() {
+        let simple = Lyrics::SimpleLyrics(SimpleLyrics(vec![(20000, "Simple".to_string())]));
+        let synced = Lyrics::SyncedLyrics(SyncedLyrics(vec![(
+            10000,
+            vec![(10000, "Synced ".to_string()), (11000, "line".to_string())],
+        )]));
+
+        assert_eq!(
+            merge_lyrics(&[simple, synced]),
+            Lyrics::SimpleLyrics(SimpleLyrics(vec![
+                (10000, "Synced line".to_string()),
+                (20000, "Simple".to_string()),
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_parse_lrc_metadata() {
+        let lrc = r#"[ar:Artist Name]
+[ti: Song Title ]
+[al:]
+[offset:1000]
+[00:12.34]Only lyrics line"#;
+        assert_eq!(
+            parse_lrc_metadata(lrc),
+            LrcMetadata {
+                title: Some("Song Title".to_owned()),
+                artist: Some("Artist Name".to_owned()),
+                album: None,
+            }
+        );
+        assert_eq!(parse_lrc_metadata(""), LrcMetadata::default());
+        assert_eq!(
+            id_tag_lines(lrc),
+            vec![
+                "[ar:Artist Name]",
+                "[ti: Song Title ]",
+                "[al:]",
+                "[offset:1000]"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_decode_lrc() {
+        // "[00:01.00]안녕하세요" in EUC-KR.
+        let mut bytes = b"[00:01.00]".to_vec();
+        bytes.extend([0xbe, 0xc8, 0xb3, 0xe7, 0xc7, 0xcf, 0xbc, 0xbc, 0xbf, 0xe4]);
+        assert_eq!(
+            decode_lrc(&bytes, Some("euc-kr")).unwrap(),
+            "[00:01.00]안녕하세요"
+        );
+        assert_eq!(
+            decode_lrc(&bytes, Some("windows-949")).unwrap(),
+            "[00:01.00]안녕하세요"
+        );
+        assert!(decode_lrc(&bytes, None).is_err());
+        assert!(decode_lrc(&bytes, Some("klingon")).is_err());
+    }
+
+    #[test]
+    fn test_decode_lrc_detects_bom() {
+        assert_eq!(
+            decode_lrc("\u{feff}[00:01.00]안녕".as_bytes(), None).unwrap(),
+            "[00:01.00]안녕"
+        );
+        let mut utf16 = vec![0xff, 0xfe];
+        utf16.extend("안녕".encode_utf16().flat_map(u16::to_le_bytes));
+        assert_eq!(decode_lrc(&utf16, None).unwrap(), "안녕");
+    }
 }
@@ -1,25 +1,69 @@
-use anyhow::Result;
-use clap::{Parser, Subcommand};
+use std::{
+    collections::{HashMap, HashSet},
+    fs::{read_dir, read_to_string, write},
+    io::{IsTerminal, stdin, stdout},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use anyhow::{Result, anyhow};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use crossterm::style::{Color, Stylize};
 
 use crate::{
+    dictation::run_dictation,
     hangul::{
-        HangulCharClass, decompose_all_hangul_syllables, decompose_hangul_syllable_to_jamos,
-        hangul_jamo_to_compat_with_fallback,
+        DecompositionState, HangulCharClass, HangulCounts, Token, classify_extended_jamo,
+        decompose_all_hangul_syllables, decompose_hangul_syllable_to_jamos,
+        detect_decomposition_state, display_jamo, halfwidth_jamo_to_compat,
+        halfwidth_jamos_to_conjoining, hangul_jamo_to_compat_with_fallback, nfd_non_hangul,
+        split_into_tokens, strip_non_hangul, strip_trailing_particle, to_syllables, tone_mark_name,
+    },
+    introductions::{REPEAT_COMMAND, get_hangul, run_introductions},
+    ipa::transcribe_ipa,
+    jamo_diff::{diff_syllables, format_syllable_diff},
+    jamo_stream::{JamoStream, ModernJamo},
+    keybindings::KeyBindings,
+    logging::init_logging,
+    lrc::{
+        Lyrics, SimpleLyrics, merge_lyrics, parse_lrc, parse_lrc_metadata, parse_time, read_lrc,
     },
-    introductions::run_introductions,
-    pronunciation::apply_pronunciation_rules_to_jamos,
-    record::run_record,
-    romanize::romanize_decomposed_hangul,
+    play::{HighlightStyle, PlayOptions, format_mm_ss},
+    pronunciation::{
+        PRONUNCIATION_RULES, RuleKind, apply_pronunciation_rules_with,
+        fold_confusable_vowels_in_syllables, get_jamo_pronunciation, pronunciation_complexity,
+    },
+    record::{RECORDING_FILENAME, list_input_devices, run_compare, run_playback, run_record},
+    romanize::{
+        RomanizationScheme, RomanizeTextOptions, get_romanized_final_alternatives, romanize,
+        romanize_aligned, romanize_disambiguated, romanize_initial_display, romanize_text,
+        romanize_with_liaison,
+    },
+    study_sheet::{flashcard_row, study_sheet},
+    tts::{list_korean_voices, speak_korean},
 };
 
+mod audio;
+mod dictation;
+mod glyphs;
 mod hangul;
 mod introductions;
+mod ipa;
+mod jamo_diff;
 mod jamo_stream;
+mod keybindings;
+mod logging;
 mod lrc;
+mod numbers;
 mod play;
 mod pronunciation;
 mod record;
 mod romanize;
+mod session;
+mod study_sheet;
+#[cfg(feature = "time-stretch")]
+mod time_stretch;
+mod tts;
 
 #[derive(Parser)]
 #[command(name = "hangul-fun")]
@@ -27,6 +71,65 @@ mod romanize;
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+
+    /// Show jamos as their raw conjoining forms and codepoints, rather
+    /// than as compatibility jamos
+    #[arg(long = "raw", global = true, default_value_t = false)]
+    raw: bool,
+
+    /// Log diagnostics, e.g. about how files were parsed. `RUST_LOG` can
+    /// pick which ones are logged, e.g. `RUST_LOG=hangul_fun::lrc=trace`
+    #[arg(short = 'v', long = "verbose", global = true, default_value_t = false)]
+    verbose: bool,
+
+    /// The encoding of LRC files, e.g. "euc-kr" (defaults to UTF-8, or
+    /// whatever the file's byte order mark says)
+    #[arg(long = "encoding", global = true)]
+    encoding: Option<String>,
+
+    /// Normalize everything in the given text or lyrics that isn't
+    /// Hangul to Unicode NFD before doing anything else with it, so that e.g.
+    /// accented letters are compared the same way whether or not
+    /// they're precomposed
+    #[arg(long = "nfd", global = true, default_value_t = false)]
+    nfd: bool,
+}
+
+/// A section of a song to limit an LRC file's lyrics to.
+#[derive(Args, Debug)]
+struct TimeRange {
+    /// Only use lyrics at or after this time, e.g. "1:30"
+    #[arg(long = "since")]
+    since: Option<String>,
+    /// Only use lyrics before this time, e.g. "2:05.5"
+    #[arg(long = "until")]
+    until: Option<String>,
+}
+
+impl TimeRange {
+    fn parse(&self) -> Result<(Option<Duration>, Option<Duration>)> {
+        let since = self.since.as_deref().map(parse_time).transpose()?;
+        let until = self.until.as_deref().map(parse_time).transpose()?;
+        Ok((since, until))
+    }
+
+    /// Returns only the given lyrics that are within the range.
+    fn apply(&self, lyrics: Lyrics) -> Result<Lyrics> {
+        let (since, until) = self.parse()?;
+        lyrics.between(since, until)
+    }
+}
+
+/// The form that jamos are shown in.
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+enum JamoForm {
+    /// Conjoining jamos, which are what the string actually decomposes to.
+    Conjoining,
+    /// Compatibility jamos, which display more consistently in terminals,
+    /// with a dot between neighboring syllables, e.g. "ㅎㅏㄴ·ㄱㅡㄹ".
+    /// This is for display only: it can't be converted back, since e.g.
+    /// initial and final ㄱ are the same compatibility jamo.
+    Compat,
 }
 
 #[derive(Subcommand)]
@@ -35,11 +138,176 @@ enum Commands {
     Decode {
         /// The string to decode
         string: String,
+        /// Speak the string using text-to-speech
+        #[arg(long = "speak", default_value_t = false)]
+        speak: bool,
+        /// Print a summary of the number of syllables, jamos and other characters
+        #[arg(long = "count", default_value_t = false)]
+        count: bool,
+        /// Color the romanization of initials, vowels and finals differently
+        /// (ignored if NO_COLOR is set or output isn't a terminal)
+        #[arg(long = "color", default_value_t = false)]
+        color: bool,
+        /// Show pronunciation hints for each jamo of each syllable
+        #[arg(long = "hints", default_value_t = false)]
+        hints: bool,
+        /// How to show the jamos of the decomposed string
+        #[arg(long = "output-jamo-form", value_enum, default_value_t = JamoForm::Conjoining)]
+        output_jamo_form: JamoForm,
+        /// Ignore everything in the string that isn't Hangul, except spacing
+        #[arg(long = "only-hangul", default_value_t = false)]
+        only_hangul: bool,
+        /// Print a rough score of how hard the string is to pronounce,
+        /// based on its sound changes, compound finals and confusable vowels
+        #[arg(long = "difficulty", default_value_t = false)]
+        difficulty: bool,
+        /// Print each word with any common particle (e.g. 는, 에서) split
+        /// off its end. This is a guess, so it can split words that
+        /// merely end in the same syllables, like 고양이.
+        #[arg(long = "stems", default_value_t = false)]
+        stems: bool,
+        /// Also print an approximate IPA transcription of how the string
+        /// is pronounced
+        #[arg(long = "ipa", default_value_t = false)]
+        ipa: bool,
+        /// Read the string as whitespace-separated hex codepoints, e.g.
+        /// "U+1100 0x1161 11AB"
+        #[arg(long = "codepoints", default_value_t = false)]
+        codepoints: bool,
     },
     /// Show pronunciation information for a string
     Say {
-        /// The string to display pronunciation information for
+        /// The string to display pronunciation information for. Morpheme
+        /// boundaries in compound words can be marked with '+', e.g. "담+요".
+        string: String,
+        /// Speak the string using text-to-speech
+        #[arg(long = "speak", default_value_t = false)]
+        speak: bool,
+        /// Only apply the given pronunciation rules (defaults to all of them)
+        #[arg(long = "rules", value_enum, value_delimiter = ',')]
+        rules: Vec<RuleKind>,
+    },
+    /// List the distinct words of Hangul in a text, most frequent first,
+    /// with how many times each one occurs and its romanization
+    Words {
+        /// The text to list the words of (defaults to reading it from
+        /// standard input)
+        string: Option<String>,
+        /// Count words with any common particle (e.g. 는, 에서) split off
+        /// their end as the same word, e.g. 학교에서 as 학교
+        #[arg(long = "stems", default_value_t = false)]
+        stems: bool,
+    },
+    /// Compare two words syllable by syllable, highlighting the jamos
+    /// that differ, e.g. "불" and "풀"
+    Diff {
+        /// The first word
+        a: String,
+        /// The second word
+        b: String,
+    },
+    /// Print a study sheet for a word or line, with its romanization,
+    /// pronunciation and a breakdown of every syllable
+    StudySheet {
+        /// The word or line to make a study sheet for
+        string: String,
+        /// Format the study sheet as Markdown
+        #[arg(long = "markdown", default_value_t = false)]
+        markdown: bool,
+    },
+    /// List the pronunciation rules, with an example of each
+    Rules,
+    /// Romanize a string
+    Romanize {
+        /// The string to romanize
         string: String,
+        /// Normalize spacing and capitalize the start of each sentence
+        #[arg(long = "sentences", default_value_t = false)]
+        sentences: bool,
+        /// Mark easily confused vowels with diacritics, e.g. "ŏ" for ㅓ
+        #[arg(long = "diacritics", default_value_t = false)]
+        diacritics: bool,
+        /// Read numbers written with digits in Sino-Korean, e.g. "3" as "sam"
+        #[arg(long = "transliterate-digits", default_value_t = false)]
+        transliterate_digits: bool,
+        /// Romanize ㄹ at the start of a word as "l", as Yale romanization
+        /// does, e.g. 라면 as "lamyeon"
+        #[arg(long = "initial-l", default_value_t = false)]
+        initial_l: bool,
+        /// Romanize the string as it's pronounced, e.g. 희망 as "himang"
+        #[arg(long = "pronounced", default_value_t = false)]
+        pronounced: bool,
+        /// How to romanize consonants
+        #[arg(long = "scheme", value_enum, default_value_t = RomanizationScheme::Revised)]
+        scheme: RomanizationScheme,
+        /// Ignore everything in the string that isn't Hangul, except spacing
+        #[arg(long = "only-hangul", default_value_t = false)]
+        only_hangul: bool,
+        /// Print an approximate IPA transcription of how the string is
+        /// pronounced instead
+        #[arg(
+            long = "ipa",
+            default_value_t = false,
+            conflicts_with_all = [
+                "sentences",
+                "diacritics",
+                "transliterate_digits",
+                "initial_l",
+                "pronounced",
+                "scheme",
+                "hyphenate",
+            ]
+        )]
+        ipa: bool,
+        /// Insert hyphens where syllable boundaries would otherwise be
+        /// ambiguous, e.g. 중앙 as "jung-ang"
+        #[arg(long = "hyphenate", default_value_t = false)]
+        hyphenate: bool,
+    },
+    /// Turn a list of words, one per line, into tab-separated flashcards
+    /// that can be imported into e.g. Anki, with each word's
+    /// romanization, pronunciation and jamos
+    Flashcards {
+        /// The file with the list of words
+        input: String,
+        /// File to write the flashcards to (defaults to printing them)
+        #[arg(long = "output", short = 'o')]
+        output: Option<String>,
+    },
+    /// Romanize every line of an LRC file's lyrics
+    RomanizeLrc {
+        /// The LRC file to romanize
+        input: String,
+        /// File to write the romanized lyrics to (defaults to printing them)
+        #[arg(long = "output", short = 'o')]
+        output: Option<String>,
+        /// Apply pronunciation rules before romanizing
+        #[arg(long = "rules", default_value_t = false)]
+        rules: bool,
+        /// Write plain text without timestamps, instead of LRC
+        #[arg(long = "text", default_value_t = false)]
+        text: bool,
+        #[command(flatten)]
+        range: TimeRange,
+    },
+    /// Print every line of an LRC file's lyrics that contains the given
+    /// Hangul, ignoring spacing and punctuation
+    Find {
+        /// The LRC file to search
+        lrc: String,
+        /// The Hangul to search for
+        query: String,
+        /// Treat vowels that sound the same (e.g. ㅐ/ㅔ) as matching
+        #[arg(long = "fuzzy", default_value_t = false)]
+        fuzzy: bool,
+        #[command(flatten)]
+        range: TimeRange,
+    },
+    /// Print a report about the lyrics of every LRC file in a directory
+    /// and its subdirectories
+    Analyze {
+        /// The directory to search for LRC files
+        dir: String,
     },
     /// Play a file
     Play {
@@ -51,6 +319,57 @@ enum Commands {
         /// Optional LRC file to use instead of the default
         #[arg(long = "lrc")]
         lrc: Option<String>,
+        /// How to highlight the current word and selected syllable
+        #[arg(long = "highlight", value_enum, default_value_t = HighlightStyle::Bg)]
+        highlight: HighlightStyle,
+        /// Optional key bindings config file, mapping actions to keys
+        /// (e.g. `quit = "q"`)
+        #[arg(long = "keys")]
+        keys: Option<String>,
+        /// Allow selecting words that aren't Hangul, e.g. loanwords
+        /// written in the Latin alphabet
+        #[arg(long = "all-words", default_value_t = false)]
+        all_words: bool,
+        /// Only split words of Hangul at whitespace and these characters,
+        /// e.g. "-" to treat "안녕,반가워" as one word (by default, any
+        /// character that isn't a Hangul syllable splits words)
+        #[arg(long = "word-separators")]
+        word_separators: Option<String>,
+        /// How many times the repeat key plays the current line
+        #[arg(long = "repeat-count", default_value_t = 3)]
+        repeat_count: usize,
+        /// How fast to play the song, e.g. 0.75 for three quarters of its
+        /// normal speed (defaults to 1, or the resumed session's speed).
+        /// This also changes its pitch, unless --preserve-pitch is given.
+        #[arg(long = "speed")]
+        speed: Option<f32>,
+        /// Keep the song's pitch when changing its speed (requires the
+        /// time-stretch feature)
+        #[arg(long = "preserve-pitch", default_value_t = false)]
+        preserve_pitch: bool,
+        /// Move words that are timed before the word preceding them, as
+        /// hand-written LRC files sometimes have, to that word's time
+        #[arg(long = "repair-timings", default_value_t = false)]
+        repair_timings: bool,
+        /// Select syllables by clicking on them
+        #[arg(long = "mouse", default_value_t = false)]
+        mouse: bool,
+        /// Only draw the player with ASCII characters, for terminals
+        /// whose fonts lack symbols like ⏸︎
+        #[arg(long = "ascii", default_value_t = false)]
+        ascii: bool,
+        /// Save where playback was when quitting, along with its speed,
+        /// volume and --since/--until, next to the audio file, and pick up
+        /// from there the next time it's played
+        #[arg(long = "resume", default_value_t = false, overrides_with = "no_resume")]
+        resume: bool,
+        /// Don't save or restore where playback was (overrides --resume)
+        #[arg(long = "no-resume", default_value_t = false)]
+        no_resume: bool,
+        // Playback starts at --since, and loops back there when it
+        // reaches --until.
+        #[command(flatten)]
+        range: TimeRange,
     },
     /// Run the conversation simulator for greetings and introductions.
     Introductions {
@@ -61,76 +380,947 @@ enum Commands {
         /// Whether to automate the second speaker instead of prompting the user.
         #[arg(long = "auto", default_value_t = false)]
         auto: bool,
+
+        /// List the IDs of all available Korean voices, and exit.
+        #[arg(long = "list-voices", default_value_t = false)]
+        list_voices: bool,
+
+        /// ID of the voice to use for the first speaker, if available.
+        #[arg(long = "voice-a")]
+        voice_a: Option<String>,
+
+        /// ID of the voice to use for the second speaker, if available.
+        #[arg(long = "voice-b")]
+        voice_b: Option<String>,
+    },
+    /// Practice by typing words dictated using text-to-speech.
+    Dictation {
+        /// File with one word per line to dictate (defaults to built-in vocabulary).
+        #[arg(long = "wordlist")]
+        wordlist: Option<String>,
+
+        /// Rate of speech.
+        #[arg(long = "rate")]
+        rate: Option<f32>,
+
+        /// Count answers with vowels that sound the same (e.g. ㅐ/ㅔ) as correct.
+        #[arg(long = "fold-confusables", default_value_t = false)]
+        fold_confusables: bool,
+
+        /// What to type to hear the word again.
+        #[arg(long = "repeat", default_value_t = REPEAT_COMMAND.to_owned())]
+        repeat: String,
     },
     /// Record audio.
-    Record {},
+    Record {
+        /// Adjust the volume of the recording so that its loudest part
+        /// peaks at -3 dBFS
+        #[arg(long = "normalize", default_value_t = false)]
+        normalize: bool,
+        /// The name of the audio input device to record from (defaults to
+        /// the system's default one)
+        #[arg(long = "device")]
+        device: Option<String>,
+        /// List the names of all audio input devices, and exit
+        #[arg(long = "list-devices", default_value_t = false)]
+        list_devices: bool,
+    },
+    /// Record audio, then play the recording followed by a reference
+    /// clip to compare it to.
+    Compare {
+        /// The audio file to compare the recording to
+        reference: String,
+        /// How many times to play the recording and then the reference
+        #[arg(
+            long = "rounds",
+            default_value_t = 1,
+            value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..)
+        )]
+        rounds: usize,
+        /// Adjust the volume of the recording so that its loudest part
+        /// peaks at -3 dBFS
+        #[arg(long = "normalize", default_value_t = false)]
+        normalize: bool,
+        /// The name of the audio input device to record from (defaults to
+        /// the system's default one)
+        #[arg(long = "device")]
+        device: Option<String>,
+    },
+    /// Play back recorded audio.
+    Playback {
+        /// The WAV file to play
+        #[arg(default_value_t = RECORDING_FILENAME.to_owned())]
+        filename: String,
+        /// Show a level meter while playing
+        #[arg(long = "meter", default_value_t = false)]
+        meter: bool,
+    },
+    /// Merge multiple LRC files into a single one.
+    Merge {
+        /// The LRC files to merge
+        #[arg(required = true)]
+        inputs: Vec<String>,
+        /// The LRC file to write the merged lyrics to
+        #[arg(long = "output", short = 'o')]
+        output: String,
+    },
+    /// Print every jamo yielded by the jamo stream for a string (for debugging
+    /// pronunciation rules).
+    #[command(hide = true)]
+    DumpJamoStream {
+        /// The string to dump the jamo stream of
+        string: String,
+    },
 }
 
-fn print_char_info(ch: char) {
+fn get_char_info(ch: char, raw: bool) -> String {
     let class = HangulCharClass::from(ch);
     let codepoint = ch as u32;
     let start = format!("ch={ch} ({codepoint:#x}) {class:?}");
+    if let Some(compat) = halfwidth_jamo_to_compat(ch) {
+        return format!("{start} compat={compat} ({:#x})", compat as u32);
+    }
+    if let Some(role) = classify_extended_jamo(ch) {
+        return format!("{start} role={role:?}");
+    }
+    if let Some(name) = tone_mark_name(ch) {
+        return format!("{start} tone={name}");
+    }
     let Some((initial_ch, medial_ch, maybe_final_ch)) = decompose_hangul_syllable_to_jamos(ch)
     else {
-        println!("{start}");
-        return;
+        return start;
+    };
+    let jamo_info = |jamo_ch: char| {
+        if raw {
+            display_jamo(jamo_ch, true)
+        } else {
+            format!("{} ({:#x})", display_jamo(jamo_ch, false), jamo_ch as u32)
+        }
     };
     let final_info = if let Some(final_ch) = maybe_final_ch {
-        let final_compat = hangul_jamo_to_compat_with_fallback(final_ch);
-        format!(" final={final_compat} ({:#x})", final_ch as u32)
+        let final_rom = get_romanized_final_alternatives(final_ch).unwrap_or("?".to_owned());
+        format!(" final={} ({final_rom})", jamo_info(final_ch))
     } else {
         String::default()
     };
-    let initial_compat = hangul_jamo_to_compat_with_fallback(initial_ch);
-    let medial_compat = hangul_jamo_to_compat_with_fallback(medial_ch);
-    let initial_codepoint = initial_ch as u32;
-    let medial_codepoint = medial_ch as u32;
-    println!(
-        "{start} initial={initial_compat} ({initial_codepoint:#x}) medial={medial_compat} ({medial_codepoint:#x}){final_info}"
-    );
+    format!(
+        "{start} initial={} ({}) medial={}{final_info}",
+        jamo_info(initial_ch),
+        romanize_initial_display(initial_ch),
+        jamo_info(medial_ch)
+    )
+}
+
+/// Parses whitespace-separated hex codepoints, which may start with
+/// "U+" or "0x", into the string they represent.
+fn parse_codepoints(value: &str) -> Result<String> {
+    value
+        .split_whitespace()
+        .map(|codepoint| {
+            let hex = ["U+", "u+", "0x", "0X"]
+                .iter()
+                .find_map(|prefix| codepoint.strip_prefix(prefix))
+                .unwrap_or(codepoint);
+            u32::from_str_radix(hex, 16)
+                .ok()
+                .and_then(char::from_u32)
+                .ok_or_else(|| anyhow!("Invalid codepoint: {codepoint}"))
+        })
+        .collect()
+}
+
+/// Returns each word of Hangul in the given string with any trailing
+/// particle split off by a "+", e.g. "학교+에서 친구".
+fn word_stems(value: &str) -> String {
+    strip_non_hangul(value, true)
+        .split(' ')
+        .filter(|word| !word.is_empty())
+        .map(|word| match strip_trailing_particle(word) {
+            (stem, Some(particle)) => format!("{stem}+{particle}"),
+            (stem, None) => stem,
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Returns each distinct run of Hangul syllables in the given text
+/// along with how many times it occurs, most frequent first. Words that
+/// occur equally often are kept in the order they first appear.
+fn word_counts(value: &str, stems: bool) -> Vec<(String, usize)> {
+    let mut counts: Vec<(String, usize)> = vec![];
+    let mut indices: HashMap<String, usize> = HashMap::new();
+    for (class, word) in HangulCharClass::split(value) {
+        if class != HangulCharClass::Syllables {
+            continue;
+        }
+        let word = if stems {
+            strip_trailing_particle(word).0
+        } else {
+            word.to_owned()
+        };
+        match indices.get(&word) {
+            Some(&idx) => counts[idx].1 += 1,
+            None => {
+                indices.insert(word.clone(), counts.len());
+                counts.push((word, 1));
+            }
+        }
+    }
+    counts.sort_by(|(_, a), (_, b)| b.cmp(a));
+    counts
+}
+
+/// Returns the given decomposed string with its jamos in the given form.
+fn display_decomposed(decomposed: &str, form: JamoForm) -> String {
+    match form {
+        JamoForm::Conjoining => decomposed.to_owned(),
+        JamoForm::Compat => {
+            let mut result = String::with_capacity(decomposed.len());
+            let mut prev: Option<char> = None;
+            for ch in decomposed.chars() {
+                if ModernJamo::is_initial_consonant(ch)
+                    && prev.is_some_and(|prev| HangulCharClass::from(prev) == HangulCharClass::Jamo)
+                {
+                    result.push('·');
+                }
+                result.push(hangul_jamo_to_compat_with_fallback(ch));
+                prev = Some(ch);
+            }
+            result
+        }
+    }
+}
+
+/// Returns a line of pronunciation advice for each jamo in the given
+/// character, if it's a Hangul syllable, followed by how it sounds when
+/// a vowel follows it, if it has a final consonant.
+fn get_jamo_hints(ch: char, raw: bool) -> Vec<String> {
+    if decompose_hangul_syllable_to_jamos(ch).is_none() {
+        return vec![];
+    }
+    let mut hints: Vec<String> = JamoStream::from_hangul_syllables(ch.to_string())
+        .filter_map(|jamo| {
+            let hint = get_jamo_pronunciation(&jamo);
+            if hint.is_empty() {
+                None
+            } else {
+                Some(format!("  {}: {hint}", display_jamo(jamo.curr, raw)))
+            }
+        })
+        .collect();
+    if let Some(liaison) = romanize_with_liaison(ch) {
+        hints.push(format!(
+            "  liaison→ {liaison} (before a vowel, vs. {} alone)",
+            romanize(&ch.to_string())
+        ));
+    }
+    hints
+}
+
+/// Prints a warning if the given input mixes precomposed syllables and
+/// conjoining jamos, which is probably unintentional. Commands normalize
+/// their input either way.
+fn warn_if_mixed_decomposition(value: &str) {
+    if detect_decomposition_state(value) == DecompositionState::Mixed {
+        eprintln!(
+            "Warning: input mixes precomposed Hangul syllables and conjoining jamos, normalizing it."
+        );
+    }
+}
+
+/// Romanizes each line of the given lyrics, optionally applying
+/// pronunciation rules first. Synced lyrics are converted to simple ones.
+fn romanize_lyrics(lyrics: &Lyrics, apply_rules: bool) -> SimpleLyrics {
+    let lines = lyrics
+        .to_simple()
+        .0
+        .into_iter()
+        .map(|(timestamp, line)| {
+            let options = RomanizeTextOptions {
+                pronounced: apply_rules,
+                ..Default::default()
+            };
+            (timestamp, romanize_text(&line, options))
+        })
+        .collect();
+    SimpleLyrics(lines)
+}
+
+/// Returns the lines of the given lyrics that contain the given query,
+/// comparing only their Hangul, and optionally folding vowels that
+/// sound the same.
+fn find_lyrics_lines<'a>(
+    lyrics: &'a SimpleLyrics,
+    query: &str,
+    fuzzy: bool,
+) -> Vec<&'a (u64, String)> {
+    let normalize = |value: &str| {
+        let hangul = get_hangul(value);
+        if fuzzy {
+            fold_confusable_vowels_in_syllables(hangul)
+        } else {
+            hangul
+        }
+    };
+    let query = normalize(query);
+    if query.is_empty() {
+        return vec![];
+    }
+    lyrics
+        .0
+        .iter()
+        .filter(|(_, line)| normalize(line).contains(&query))
+        .collect()
+}
+
+/// Returns every LRC file in the given directory and its
+/// subdirectories, sorted by path. Symlinks to directories aren't
+/// followed, so that a link back up the tree can't loop forever, and
+/// anything that can't be read is warned about and skipped.
+fn find_lrc_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut result = vec![];
+    for entry in read_dir(dir)? {
+        let (path, file_type) = match entry.and_then(|entry| Ok((entry.path(), entry.file_type()?)))
+        {
+            Ok(entry) => entry,
+            Err(err) => {
+                eprintln!(
+                    "Warning: skipping an entry of {} because it couldn't be read: {err}",
+                    dir.to_string_lossy()
+                );
+                continue;
+            }
+        };
+        if file_type.is_dir() {
+            match find_lrc_files(&path) {
+                Ok(files) => result.extend(files),
+                Err(err) => eprintln!(
+                    "Warning: skipping {} because it couldn't be read: {err}",
+                    path.to_string_lossy()
+                ),
+            }
+        } else if path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("lrc"))
+        {
+            result.push(path);
+        }
+    }
+    result.sort();
+    Ok(result)
+}
+
+/// Returns a report about the lyrics in the given LRC file: its
+/// title and artist, how many lines it has, how long it is and how
+/// many different Hangul syllables it uses.
+fn song_report(lrc: String) -> Result<String> {
+    let metadata = parse_lrc_metadata(&lrc);
+    let lyrics = parse_lrc(lrc)?;
+    let lines = lyrics.to_simple().0;
+    let syllables: HashSet<char> = lines
+        .iter()
+        .flat_map(|(_, line)| {
+            split_into_tokens(&to_syllables(line))
+                .into_iter()
+                .filter_map(|token| match token {
+                    Token::Syllable(ch) => Some(ch),
+                    Token::Other(_) => None,
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    let duration = lyrics
+        .total_duration()
+        .map_or("unknown".to_owned(), format_mm_ss);
+    let unknown = "unknown".to_owned();
+    Ok([
+        format!(
+            "  title           : {}",
+            metadata.title.unwrap_or(unknown.clone())
+        ),
+        format!("  artist          : {}", metadata.artist.unwrap_or(unknown)),
+        format!("  lines           : {}", lines.len()),
+        format!("  duration        : {duration}"),
+        format!("  unique syllables: {}", syllables.len()),
+    ]
+    .join("\n"))
+}
+
+/// Describes the given pronunciation rule, with the name that `--rules`
+/// accepts for it and an example.
+fn describe_rule(kind: RuleKind) -> String {
+    let name = kind
+        .to_possible_value()
+        .map(|value| value.get_name().to_owned())
+        .unwrap_or_default();
+    let (before, after) = kind.example();
+    format!("{name:<26}{}: {before} → {after}", kind.display_name())
+}
+
+/// Returns whether colored output should be used, respecting the
+/// `NO_COLOR` convention (https://no-color.org).
+fn should_use_color() -> bool {
+    let no_color = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+    !no_color && stdout().is_terminal()
+}
+
+/// Romanizes the given jamos, coloring initials, vowels and finals
+/// differently.
+fn colorize_romanization(jamos: &str) -> String {
+    romanize_aligned(jamos)
+        .into_iter()
+        .map(|(ch, romanized)| {
+            let color = match ModernJamo::try_from_char(ch) {
+                Some(ModernJamo::InitialConsonant(_)) => Color::Cyan,
+                Some(ModernJamo::Vowel(_)) => Color::Yellow,
+                Some(ModernJamo::FinalConsonant(_)) => Color::Magenta,
+                None => return romanized,
+            };
+            romanized.with(color).to_string()
+        })
+        .collect()
 }
 
 fn main() -> Result<()> {
     let cli = Cli::parse();
+    init_logging(cli.verbose)?;
+    let encoding = cli.encoding.as_deref();
+    let normalize = |value: &str| {
+        if cli.nfd {
+            nfd_non_hangul(value)
+        } else {
+            value.to_owned()
+        }
+    };
+    let read_lyrics = |path: &Path| read_lrc(path, encoding).map(|lrc| normalize(&lrc));
 
     match &cli.command {
-        Commands::Decode { string } => {
+        Commands::Decode {
+            string,
+            speak,
+            count,
+            color,
+            output_jamo_form,
+            hints,
+            only_hangul,
+            difficulty,
+            stems,
+            ipa,
+            codepoints,
+        } => {
+            let string = normalize(&if *codepoints {
+                parse_codepoints(string)?
+            } else {
+                string.clone()
+            });
+            let string = &if *only_hangul {
+                strip_non_hangul(string, true)
+            } else {
+                string
+            };
+            warn_if_mixed_decomposition(string);
             for ch in string.chars() {
-                print_char_info(ch);
+                println!("{}", get_char_info(ch, cli.raw));
+                if *hints {
+                    for hint in get_jamo_hints(ch, cli.raw) {
+                        println!("{hint}");
+                    }
+                }
             }
-            let decomposed = decompose_all_hangul_syllables(&string);
+            let decomposed = decompose_all_hangul_syllables(halfwidth_jamos_to_conjoining(string));
             println!(
-                "decomposed: {decomposed} (original length={}, decomposed length={})",
+                "decomposed: {} (original length={}, decomposed length={})",
+                display_decomposed(&decomposed, *output_jamo_form),
                 string.len(),
                 decomposed.len()
             );
-            println!("romanized: {}", romanize_decomposed_hangul(&decomposed));
+            if *color && should_use_color() {
+                println!("romanized: {}", colorize_romanization(&decomposed));
+            } else {
+                println!("romanized: {}", romanize(&decomposed));
+            }
+            if *count {
+                let counts = HangulCounts::new(string);
+                let classes: Vec<String> = counts
+                    .classes
+                    .iter()
+                    .map(|class| format!("{class:?}"))
+                    .collect();
+                println!(
+                    "counts: syllables={} jamos={} non-hangul={} classes={}",
+                    counts.syllables,
+                    counts.jamos,
+                    counts.non_hangul,
+                    classes.join(",")
+                );
+            }
+            if *difficulty {
+                println!("difficulty: {}", pronunciation_complexity(&decomposed));
+            }
+            if *stems {
+                println!("stems: {}", word_stems(string));
+            }
+            if *ipa {
+                println!("ipa: [{}]", transcribe_ipa(string));
+            }
+            if *speak {
+                speak_korean(string)?;
+            }
         }
-        Commands::Say { string } => {
-            let decomposed = decompose_all_hangul_syllables(&string);
+        Commands::Say {
+            string,
+            speak,
+            rules,
+        } => {
+            let string = &normalize(string);
+            warn_if_mixed_decomposition(string);
+            let decomposed = decompose_all_hangul_syllables(string);
             println!(
                 "original   : {decomposed}  romanized: {}",
-                romanize_decomposed_hangul(&decomposed)
+                romanize(&decomposed)
             );
-            let pronounced = apply_pronunciation_rules_to_jamos(&decomposed);
+            let rules = if rules.is_empty() {
+                &PRONUNCIATION_RULES[..]
+            } else {
+                &rules[..]
+            };
+            let pronounced = apply_pronunciation_rules_with(&decomposed, rules);
             println!(
                 "pronounced : {pronounced}  romanized: {}",
-                romanize_decomposed_hangul(&pronounced)
+                romanize(&pronounced)
             );
+            if *speak {
+                speak_korean(string)?;
+            }
+        }
+        Commands::Rules => {
+            for kind in PRONUNCIATION_RULES {
+                println!("{}", describe_rule(kind));
+            }
+        }
+        Commands::Words { string, stems } => {
+            let text = normalize(&match string {
+                Some(string) => string.clone(),
+                None => std::io::read_to_string(stdin())?,
+            });
+            for (word, count) in word_counts(&text, *stems) {
+                println!("{count:>5} {word} ({})", romanize(&word));
+            }
+        }
+        Commands::Diff { a, b } => {
+            let (a, b) = (normalize(a), normalize(b));
+            let color = should_use_color();
+            for diff in diff_syllables(&a, &b) {
+                println!("{}", format_syllable_diff(&diff, color));
+            }
+        }
+        Commands::StudySheet { string, markdown } => {
+            let string = &normalize(string);
+            warn_if_mixed_decomposition(string);
+            println!("{}", study_sheet(string, *markdown));
+        }
+        Commands::Romanize {
+            string,
+            sentences,
+            diacritics,
+            transliterate_digits,
+            initial_l,
+            pronounced,
+            scheme,
+            only_hangul,
+            ipa,
+            hyphenate,
+        } => {
+            let string = &normalize(&if *only_hangul {
+                strip_non_hangul(string, true)
+            } else {
+                string.clone()
+            });
+            warn_if_mixed_decomposition(string);
+            let options = RomanizeTextOptions {
+                normalize_spacing: *sentences,
+                capitalize_sentences: *sentences,
+                diacritics: *diacritics,
+                transliterate_digits: *transliterate_digits,
+                word_initial_l: *initial_l,
+                pronounced: *pronounced,
+                scheme: *scheme,
+            };
+            if *ipa {
+                println!("[{}]", transcribe_ipa(string));
+            } else if *hyphenate {
+                println!("{}", romanize_disambiguated(string, options));
+            } else {
+                println!("{}", romanize_text(string, options));
+            }
+        }
+        Commands::Flashcards { input, output } => {
+            let result: String = read_to_string(input)?
+                .lines()
+                .map(|line| normalize(line.trim()))
+                .filter(|word| !word.is_empty())
+                .map(|word| format!("{}\n", flashcard_row(&word)))
+                .collect();
+            match output {
+                Some(output) => write(output, result)?,
+                None => print!("{result}"),
+            }
+        }
+        Commands::RomanizeLrc {
+            input,
+            output,
+            rules,
+            text,
+            range,
+        } => {
+            let lyrics = range.apply(parse_lrc(read_lyrics(Path::new(input))?)?)?;
+            let lyrics = romanize_lyrics(&lyrics, *rules);
+            let result = if *text {
+                lyrics
+                    .0
+                    .iter()
+                    .map(|(_, line)| format!("{line}\n"))
+                    .collect()
+            } else {
+                Lyrics::SimpleLyrics(lyrics).to_lrc()
+            };
+            match output {
+                Some(output) => write(output, result)?,
+                None => print!("{result}"),
+            }
+        }
+        Commands::Find {
+            lrc,
+            query,
+            fuzzy,
+            range,
+        } => {
+            let lyrics = range
+                .apply(parse_lrc(read_lyrics(Path::new(lrc))?)?)?
+                .to_simple();
+            let lines = find_lyrics_lines(&lyrics, &normalize(query), *fuzzy);
+            if lines.is_empty() {
+                std::process::exit(1);
+            }
+            for (timestamp, line) in lines {
+                let secs = timestamp / 1000;
+                println!("{:02}:{:02} {line}", secs / 60, secs % 60);
+            }
+        }
+        Commands::Analyze { dir } => {
+            for path in find_lrc_files(Path::new(dir))? {
+                let report = read_lyrics(&path).and_then(song_report);
+                match report {
+                    Ok(report) => println!("{}\n{report}", path.to_string_lossy()),
+                    Err(err) => eprintln!(
+                        "Warning: skipping {} because it couldn't be parsed: {err}",
+                        path.to_string_lossy()
+                    ),
+                }
+            }
         }
         Commands::Play {
             filename,
             no_alt,
             lrc,
+            highlight,
+            keys,
+            all_words,
+            word_separators,
+            repeat_count,
+            speed,
+            preserve_pitch,
+            repair_timings,
+            mouse,
+            ascii,
+            resume,
+            no_resume,
+            range,
+        } => {
+            let (since, until) = range.parse()?;
+            let key_bindings = match keys {
+                Some(keys) => KeyBindings::parse(&read_to_string(keys)?)?,
+                None => KeyBindings::default(),
+            };
+            let options = PlayOptions {
+                use_alternate_screen: !no_alt,
+                highlight_style: *highlight,
+                raw_jamos: cli.raw,
+                select_all_words: *all_words,
+                word_separators: word_separators.clone(),
+                repair_timings: *repair_timings,
+                mouse: *mouse,
+                ascii: *ascii,
+                resume: *resume && !*no_resume,
+                lrc_encoding: cli.encoding.clone(),
+                nfd: cli.nfd,
+                repeat_count: *repeat_count,
+                speed: *speed,
+                preserve_pitch: *preserve_pitch,
+                since,
+                until,
+            };
+            play::play(filename, lrc, options, key_bindings)?;
+        }
+        Commands::Introductions {
+            rate,
+            auto,
+            list_voices,
+            voice_a,
+            voice_b,
+        } => {
+            if *list_voices {
+                list_korean_voices()?;
+            } else {
+                run_introductions(*rate, !*auto, voice_a, voice_b)?;
+            }
+        }
+        Commands::Dictation {
+            wordlist,
+            rate,
+            fold_confusables,
+            repeat,
+        } => {
+            run_dictation(wordlist, *rate, *fold_confusables, repeat)?;
+        }
+        Commands::Record {
+            normalize,
+            device,
+            list_devices,
+        } => {
+            if *list_devices {
+                list_input_devices()?;
+            } else {
+                run_record(*normalize, device.as_deref())?;
+            }
+        }
+        Commands::Compare {
+            reference,
+            rounds,
+            normalize,
+            device,
         } => {
-            play::play(filename, !no_alt, lrc)?;
+            run_compare(reference, *rounds, *normalize, device.as_deref())?;
         }
-        Commands::Introductions { rate, auto } => {
-            run_introductions(*rate, !*auto)?;
+        Commands::Playback { filename, meter } => {
+            run_playback(filename, *meter)?;
         }
-        Commands::Record {} => {
-            run_record()?;
+        Commands::Merge { inputs, output } => {
+            let files = inputs
+                .iter()
+                .map(|input| parse_lrc(read_lyrics(Path::new(input))?))
+                .collect::<Result<Vec<_>>>()?;
+            write(output, merge_lyrics(&files).to_lrc())?;
+        }
+        Commands::DumpJamoStream { string } => {
+            for (index, jamo) in JamoStream::from_hangul_syllables(normalize(string)).enumerate() {
+                println!("{index}: {jamo:?}");
+            }
         }
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        JamoForm, describe_rule, display_decomposed, find_lyrics_lines, get_char_info,
+        get_jamo_hints,
+        hangul::{decompose_all_hangul_syllables, nfd_non_hangul},
+        lrc::{SimpleLyrics, parse_lrc},
+        parse_codepoints,
+        pronunciation::RuleKind,
+        romanize_lyrics, song_report, word_counts, word_stems,
+    };
+
+    #[test]
+    fn test_char_info_works() {
+        assert_eq!(
+            get_char_info('안', false),
+            "ch=안 (0xc548) Syllables initial=ㅇ (0x110b) (silent) medial=ㅏ (0x1161) final=ㄴ (0x11ab) (n)"
+        );
+    }
+
+    #[test]
+    fn test_raw_char_info_includes_conjoining_codepoints() {
+        let info = get_char_info('안', true);
+        assert!(info.contains("initial=ᄋ (U+110B)"));
+        assert!(info.contains("medial=ᅡ (U+1161)"));
+        assert!(info.contains("final=ᆫ (U+11AB)"));
+    }
+
+    #[test]
+    fn test_romanize_lyrics_works() {
+        let lrc = "[00:01.00]<00:01.00>밥을 <00:02.00>먹어요\n[00:03.00]<00:03.00>국물";
+        let lyrics = parse_lrc(lrc.to_string()).unwrap();
+        assert_eq!(
+            romanize_lyrics(&lyrics, false),
+            SimpleLyrics(vec![
+                (1000, "babeul meogeoyo".to_string()),
+                (3000, "gukmul".to_string()),
+            ])
+        );
+        assert_eq!(
+            romanize_lyrics(&lyrics, true),
+            SimpleLyrics(vec![
+                (1000, "babeul meogeoyo".to_string()),
+                (3000, "gungmul".to_string()),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_find_lyrics_lines_works() {
+        let lrc = "[00:12.34]너를 사랑해\n[01:05.00]내 사랑, 안녕!\n[01:30.00]Goodbye";
+        let lyrics = parse_lrc(lrc.to_string()).unwrap().to_simple();
+        assert_eq!(
+            find_lyrics_lines(&lyrics, "사랑", false),
+            vec![
+                &(12340, "너를 사랑해".to_string()),
+                &(65000, "내 사랑, 안녕!".to_string())
+            ]
+        );
+        // Spacing and punctuation are ignored.
+        assert_eq!(
+            find_lyrics_lines(&lyrics, "사랑안녕", false),
+            vec![&(65000, "내 사랑, 안녕!".to_string())]
+        );
+        // ㅐ and ㅔ only match when fuzzy.
+        assert!(find_lyrics_lines(&lyrics, "네 사랑", false).is_empty());
+        assert_eq!(
+            find_lyrics_lines(&lyrics, "네 사랑", true),
+            vec![&(65000, "내 사랑, 안녕!".to_string())]
+        );
+        assert!(find_lyrics_lines(&lyrics, "Goodbye", false).is_empty());
+    }
+
+    #[test]
+    fn test_char_info_labels_extended_jamo_role() {
+        assert_eq!(
+            get_char_info('\u{a960}', false),
+            "ch=\u{a960} (0xa960) JamoExtendedA role=Initial"
+        );
+        assert_eq!(
+            get_char_info('\u{d7cb}', false),
+            "ch=\u{d7cb} (0xd7cb) JamoExtendedB role=Final"
+        );
+        assert_eq!(
+            get_char_info('\u{302e}', false),
+            "ch=\u{302e} (0x302e) ToneMark tone=거성 (high tone)"
+        );
+    }
+
+    #[test]
+    fn test_display_decomposed_works() {
+        let decomposed = "\u{1112}\u{1161}\u{11ab}";
+        assert_eq!(
+            display_decomposed(decomposed, JamoForm::Conjoining),
+            decomposed
+        );
+        assert_eq!(display_decomposed(decomposed, JamoForm::Compat), "ㅎㅏㄴ");
+        assert_eq!(display_decomposed("hi!", JamoForm::Compat), "hi!");
+        let compat =
+            |value| display_decomposed(&decompose_all_hangul_syllables(value), JamoForm::Compat);
+        assert_eq!(compat("한글"), "ㅎㅏㄴ·ㄱㅡㄹ");
+        assert_eq!(compat("안녕 hi 이!"), "ㅇㅏㄴ·ㄴㅕㅇ hi ㅇㅣ!");
+    }
+
+    #[test]
+    fn test_song_report_works() {
+        let lrc = "[ti:노래]\n[ar:Someone]\n[00:01.00]안녕 안녕\n[01:02.50]잘 가";
+        assert_eq!(
+            song_report(lrc.to_owned()).unwrap(),
+            [
+                "  title           : 노래",
+                "  artist          : Someone",
+                "  lines           : 2",
+                "  duration        : 01:02",
+                "  unique syllables: 4",
+            ]
+            .join("\n")
+        );
+        assert!(
+            song_report("[00:01.00]hi".to_owned())
+                .unwrap()
+                .contains("title           : unknown")
+        );
+    }
+
+    #[test]
+    fn test_get_jamo_hints_works() {
+        assert_eq!(
+            get_jamo_hints('라', false),
+            vec![
+                "  ㄹ: 'l' at word begin, Spanish 'r' in middle, tongue back on palate",
+                "  ㅏ: 'a' as in 'father'",
+            ]
+        );
+        assert!(get_jamo_hints('a', false).is_empty());
+        assert_eq!(
+            get_jamo_hints('밥', false).last().unwrap(),
+            "  liaison→ bab- (before a vowel, vs. bap alone)"
+        );
+    }
+
+    #[test]
+    fn test_parse_codepoints() {
+        assert_eq!(parse_codepoints("U+AC00").unwrap(), "가");
+        assert_eq!(
+            parse_codepoints("u+1100 0x1161  11ab").unwrap(),
+            "\u{1100}\u{1161}\u{11ab}"
+        );
+        assert_eq!(parse_codepoints("").unwrap(), "");
+        assert_eq!(
+            parse_codepoints("U+D800").unwrap_err().to_string(),
+            "Invalid codepoint: U+D800"
+        );
+        assert!(parse_codepoints("U+110000").is_err());
+        assert!(parse_codepoints("가").is_err());
+    }
+
+    #[test]
+    fn test_word_stems() {
+        assert_eq!(
+            word_stems("저는 학교에서, 친구를 만나요"),
+            "저+는 학교+에서 친구+를 만나요"
+        );
+        assert_eq!(word_stems("hi"), "");
+    }
+
+    #[test]
+    fn test_describe_rule() {
+        assert_eq!(
+            describe_rule(RuleKind::Reinforcement),
+            "reinforcement             경음화 (reinforcement): 학교 → 학꾜"
+        );
+    }
+
+    #[test]
+    fn test_word_counts() {
+        let counts = word_counts("나는 학교에 가. 학교에서 친구를 만나. 나는, 나는!", false);
+        assert_eq!(
+            counts,
+            vec![
+                ("나는".to_owned(), 3),
+                ("학교에".to_owned(), 1),
+                ("가".to_owned(), 1),
+                ("학교에서".to_owned(), 1),
+                ("친구를".to_owned(), 1),
+                ("만나".to_owned(), 1),
+            ]
+        );
+        let counts = word_counts("나는 학교에 가. 학교에서 친구를 만나. 나는, 나는!", true);
+        assert_eq!(counts[0], ("나".to_owned(), 3));
+        assert_eq!(counts[1], ("학교".to_owned(), 2));
+    }
+
+    #[test]
+    fn test_nfd_input_keeps_words_intact() {
+        let text = nfd_non_hangul("cafe\u{301}: 나는 학교에서 나는");
+        assert_eq!(
+            word_counts(&text, false),
+            vec![("나는".to_owned(), 2), ("학교에서".to_owned(), 1)]
+        );
+        assert_eq!(word_stems(&text), "나+는 학교+에서 나+는");
+    }
+}
@@ -0,0 +1,91 @@
+/// Largest number that `read_number_sino` can read.
+pub const MAX_SINO_NUMBER: u64 = 9999;
+
+const SINO_DIGITS: [&str; 10] = ["영", "일", "이", "삼", "사", "오", "육", "칠", "팔", "구"];
+
+/// Sino-Korean names for each power of ten, starting with the
+/// thousands place.
+const SINO_PLACES: [&str; 4] = ["천", "백", "십", ""];
+
+/// Returns the Sino-Korean reading of the given number in Hangul,
+/// e.g. "십오" for 15.
+///
+/// Only numbers up to `MAX_SINO_NUMBER` are supported; larger ones
+/// are returned as digits. Native Korean readings (하나, 둘, etc.),
+/// which are used when counting some things, are not yet supported.
+pub fn read_number_sino(n: u64) -> String {
+    if n == 0 {
+        return SINO_DIGITS[0].to_owned();
+    }
+    if n > MAX_SINO_NUMBER {
+        return n.to_string();
+    }
+    let mut result = String::new();
+    let digits = [n / 1000, n / 100 % 10, n / 10 % 10, n % 10];
+    for (digit, place) in digits.into_iter().zip(SINO_PLACES) {
+        if digit == 0 {
+            continue;
+        }
+        // "일" is omitted before a place name, e.g. 10 is "십", not "일십".
+        if digit != 1 || place.is_empty() {
+            result.push_str(SINO_DIGITS[digit as usize]);
+        }
+        result.push_str(place);
+    }
+    result
+}
+
+/// Replaces every run of ASCII digits in the given string with its
+/// Sino-Korean reading in Hangul.
+///
+/// Runs that are too large to read are left unchanged.
+pub fn replace_digits_with_sino(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    let mut digits = String::new();
+
+    fn push_digits(result: &mut String, digits: &mut String) {
+        if digits.is_empty() {
+            return;
+        }
+        match digits.parse::<u64>() {
+            Ok(n) if n <= MAX_SINO_NUMBER => result.push_str(&read_number_sino(n)),
+            _ => result.push_str(digits),
+        }
+        digits.clear();
+    }
+
+    for ch in value.chars() {
+        if ch.is_ascii_digit() {
+            digits.push(ch);
+        } else {
+            push_digits(&mut result, &mut digits);
+            result.push(ch);
+        }
+    }
+    push_digits(&mut result, &mut digits);
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::numbers::{read_number_sino, replace_digits_with_sino};
+
+    #[test]
+    fn test_read_number_sino_works() {
+        assert_eq!(read_number_sino(0), "영");
+        assert_eq!(read_number_sino(3), "삼");
+        assert_eq!(read_number_sino(10), "십");
+        assert_eq!(read_number_sino(15), "십오");
+        assert_eq!(read_number_sino(110), "백십");
+        assert_eq!(read_number_sino(2025), "이천이십오");
+        assert_eq!(read_number_sino(9999), "구천구백구십구");
+        assert_eq!(read_number_sino(10000), "10000");
+    }
+
+    #[test]
+    fn test_replace_digits_with_sino_works() {
+        assert_eq!(replace_digits_with_sino("3개"), "삼개");
+        assert_eq!(replace_digits_with_sino("15 and 123456"), "십오 and 123456");
+    }
+}
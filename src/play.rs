@@ -1,108 +1,662 @@
 use anyhow::{Result, anyhow};
+use clap::ValueEnum;
 use crossterm::{
     QueueableCommand,
     cursor::{Hide, MoveTo, MoveToColumn, MoveToNextLine, Show},
-    event::{Event, KeyCode, KeyEvent, KeyModifiers, poll, read},
+    event::{
+        DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent, MouseButton, MouseEvent,
+        MouseEventKind, poll, read,
+    },
     execute,
-    style::{Attribute, Color, Print, PrintStyledContent, SetAttribute, Stylize},
+    style::{Attribute, Color, Print, PrintStyledContent, SetAttribute, StyledContent, Stylize},
     terminal::{
         Clear, ClearType, DisableLineWrap, EnableLineWrap, EnterAlternateScreen,
         LeaveAlternateScreen, disable_raw_mode, enable_raw_mode, size,
     },
 };
-use rodio::{Decoder, OutputStream, Sink};
+use log::info;
+use rodio::{Decoder, Sink, Source};
 use std::{
     borrow::Cow,
-    fs::{File, read_to_string},
-    io::{BufReader, Stdout, Write, stdout},
+    fs::{File, write},
+    io::{BufReader, Write, stdout},
     path::{Path, PathBuf},
-    time::Duration,
+    time::{Duration, Instant},
 };
 
+#[cfg(feature = "time-stretch")]
+use std::cell::Cell;
+
+#[cfg(feature = "time-stretch")]
+use crate::time_stretch::TimeStretch;
+
 use crate::{
+    audio::open_output,
+    glyphs::Glyphs,
     hangul::{
-        HangulCharClass, compose_all_hangul_jamos, count_jamos_in_syllable,
-        decompose_all_hangul_syllables, hangul_jamo_to_compat_with_fallback,
+        HangulCharClass, compose_all_hangul_jamos, count_hangul_syllables, count_jamos_in_syllable,
+        decompose_all_hangul_syllables, display_jamo, nfd_non_hangul, numbered_chars,
+        syllable_offsets,
+    },
+    jamo_stream::{JamoInStream, JamoStream, ModernJamo},
+    keybindings::{Action, KeyBindings},
+    logging::{log_to_file, log_to_stderr},
+    lrc::{Lyrics, SimpleLyrics, id_tag_lines, parse_lrc, read_lrc},
+    pronunciation::{
+        apply_pronunciation_rules_to_jamos, explain_pronunciation_rules, get_jamo_pronunciation,
     },
-    jamo_stream::{JamoInStream, JamoStream},
-    lrc::{Lyrics, parse_lrc},
-    pronunciation::{apply_pronunciation_rules_to_jamos, get_jamo_pronunciation},
-    romanize::{get_romanized_jamo, romanize_decomposed_hangul},
+    romanize::{get_romanized_jamo, romanize, romanize_initial_display},
+    session::{SessionState, load_session, save_session},
 };
 
 /// Amount to rewind, in seconds, when user presses the
-/// hotkey. If you change this, be sure to change `HELP_LINES`!
+/// hotkey.
 const REWIND_SECS: u64 = 2;
 
-const NUM_HELP_LINES: usize = 6;
+/// The slowest and fastest speeds that songs can be played at.
+const MIN_SPEED: f32 = 0.25;
+const MAX_SPEED: f32 = 4.0;
+
+/// The longest to wait for an event while playing before re-rendering,
+/// since the progress bar and playback time move along.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How long to pause between repetitions of a line.
+const REPEAT_GAP: Duration = Duration::from_millis(1500);
+
+/// When seeking to the previous word, words that started less than
+/// this long ago are skipped, so that pressing the key repeatedly
+/// keeps moving backward instead of restarting the same word.
+const PREV_WORD_GRACE: Duration = Duration::from_millis(500);
+
+/// How far the shift keys move lines of lyrics each time they're
+/// pressed, in milliseconds.
+const LINE_SHIFT_MILLIS: i64 = 100;
+
+/// The row of the terminal that the progress bar is on, if the length
+/// of the song is known.
+const PROGRESS_BAR_ROW: u16 = 1;
+
+/// The number of columns before the text of each line of lyrics, which
+/// is taken up by e.g. the "> " that marks the current one.
+const LYRICS_PREFIX_COLUMNS: usize = 2;
+
+/// The narrowest terminal, in columns, that the status bar shows the
+/// title in. Narrower terminals only get the playback icon.
+const MIN_STATUS_BAR_COLUMNS: usize = 20;
+
+/// How the current word, and the selected syllable within it, are
+/// emphasized in the lyrics.
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum HighlightStyle {
+    /// Underline the current word.
+    Underline,
+    /// Put a background color behind the current word.
+    Bg,
+    /// Make the current word bold.
+    Bold,
+}
+
+impl HighlightStyle {
+    /// Styles a syllable of the current word. The selected syllable
+    /// is always shown in blue, to distinguish it from its neighbors.
+    fn style_syllable<'a>(&self, syllable: &'a str, is_selected: bool) -> StyledContent<&'a str> {
+        let styled = match self {
+            HighlightStyle::Underline => syllable.attribute(Attribute::Underlined),
+            HighlightStyle::Bold => syllable.attribute(Attribute::Bold),
+            HighlightStyle::Bg => {
+                // Black on grey is legible, unlike the default foreground
+                // color of many dark terminal themes.
+                let styled = syllable.on(Color::Grey);
+                if is_selected {
+                    styled
+                } else {
+                    styled.with(Color::Black)
+                }
+            }
+        };
+        if is_selected {
+            styled.with(Color::Blue)
+        } else {
+            styled
+        }
+    }
+}
+
+/// The parts of audio playback that the player controls, so that it
+/// can be tested without an audio device.
+trait Playback {
+    fn is_paused(&self) -> bool;
+    fn play(&self);
+    fn pause(&self);
+    fn get_pos(&self) -> Duration;
+    fn try_seek(&self, pos: Duration) -> Result<()>;
+}
+
+impl Playback for Sink {
+    fn is_paused(&self) -> bool {
+        Sink::is_paused(self)
+    }
+
+    fn play(&self) {
+        Sink::play(self)
+    }
+
+    fn pause(&self) {
+        Sink::pause(self)
+    }
+
+    fn get_pos(&self) -> Duration {
+        Sink::get_pos(self)
+    }
+
+    fn try_seek(&self, pos: Duration) -> Result<()> {
+        Sink::try_seek(self, pos).map_err(|err| anyhow!("{err}"))
+    }
+}
+
+/// Options for `play`.
+pub struct PlayOptions {
+    /// Whether to use the terminal's alternate screen.
+    pub use_alternate_screen: bool,
+    pub highlight_style: HighlightStyle,
+    /// Whether to show jamos in their raw conjoining forms.
+    pub raw_jamos: bool,
+    /// Whether words that aren't Hangul can be selected too.
+    pub select_all_words: bool,
+    /// If set, the only characters (besides whitespace) that separate
+    /// words of Hangul from each other.
+    pub word_separators: Option<String>,
+    /// The encoding of the LRC file, if it isn't detected automatically.
+    pub lrc_encoding: Option<String>,
+    /// Whether to normalize everything in the lyrics that isn't Hangul
+    /// to Unicode NFD, as with `nfd_non_hangul`.
+    pub nfd: bool,
+    /// How many times to play the current line when repeating it.
+    pub repeat_count: usize,
+    /// How fast to play the song, e.g. 0.75 for three quarters of its
+    /// normal speed. Defaults to the resumed session's speed, or 1.
+    pub speed: Option<f32>,
+    /// Whether to keep the song's pitch when changing its speed. This
+    /// requires the `time-stretch` feature.
+    pub preserve_pitch: bool,
+    /// Whether to move words timed before the word preceding them to
+    /// that word's time, with `SyncedLyrics::repair`.
+    pub repair_timings: bool,
+    /// Whether clicking on a syllable selects it.
+    pub mouse: bool,
+    /// Whether to draw the UI with ASCII characters only, for terminals
+    /// that can't show the usual symbols.
+    pub ascii: bool,
+    /// Whether to save where playback was when quitting, and pick up
+    /// from there the next time the same audio file is played.
+    pub resume: bool,
+    /// If set, only lyrics at or after this time are shown, and playback
+    /// starts there.
+    pub since: Option<Duration>,
+    /// If set, only lyrics before this time are shown, and playback
+    /// loops back to `since` when it gets there.
+    pub until: Option<Duration>,
+}
+
+/// The state of playing a line over and over, for shadowing practice.
+struct Repeat {
+    /// Where the line starts.
+    start: Duration,
+    /// Where the line ends, i.e. where the next one starts.
+    end: Duration,
+    /// Which repetition is happening, starting from 1.
+    iteration: usize,
+    /// When to start the next repetition, if we're pausing between
+    /// two of them.
+    resume_at: Option<Instant>,
+}
+
+/// A sink that's playing a `TimeStretch`ed song. The sink only knows
+/// how much of the stretched audio it's played, so its positions are
+/// converted to and from the song's own time.
+#[cfg(feature = "time-stretch")]
+struct TimeStretchedSink {
+    sink: Sink,
+    speed: f32,
+    /// The song position that was last seeked to, which the sink
+    /// counts playback from.
+    seek_origin: Cell<Duration>,
+}
+
+#[cfg(feature = "time-stretch")]
+impl Playback for TimeStretchedSink {
+    fn is_paused(&self) -> bool {
+        self.sink.is_paused()
+    }
+
+    fn play(&self) {
+        self.sink.play()
+    }
+
+    fn pause(&self) {
+        self.sink.pause()
+    }
+
+    fn get_pos(&self) -> Duration {
+        let origin = self.seek_origin.get();
+        origin
+            + self
+                .sink
+                .get_pos()
+                .saturating_sub(origin)
+                .mul_f32(self.speed)
+    }
+
+    fn try_seek(&self, pos: Duration) -> Result<()> {
+        Playback::try_seek(&self.sink, pos)?;
+        self.seek_origin.set(pos);
+        Ok(())
+    }
+}
+
+/// Appends the given song to the sink, to be played at the given speed
+/// without changing its pitch.
+#[cfg(feature = "time-stretch")]
+fn append_time_stretched<S>(sink: Sink, source: S, speed: f32) -> Result<Box<dyn Playback>>
+where
+    S: Source<Item = i16> + Send + 'static,
+{
+    sink.append(TimeStretch::new(source, speed));
+    Ok(Box::new(TimeStretchedSink {
+        sink,
+        speed,
+        seek_origin: Cell::new(Duration::ZERO),
+    }))
+}
 
-const HELP_LINES: [&'static str; NUM_HELP_LINES] = [
-    "↑/↓   - prev/next lines",
-    "←/→   - prev/next syllable",
-    "Enter - play current line",
-    "Space - pause/unpause",
-    "B     - rewind 2 seconds",
-    "Esc   - quit",
-];
+#[cfg(not(feature = "time-stretch"))]
+fn append_time_stretched<S>(_sink: Sink, _source: S, _speed: f32) -> Result<Box<dyn Playback>> {
+    Err(anyhow!(
+        "Preserving pitch requires building with the time-stretch feature"
+    ))
+}
 
 struct App {
     title: String,
+    highlight_style: HighlightStyle,
+    /// Whether to show jamos in their raw conjoining forms.
+    raw_jamos: bool,
+    /// Whether words that aren't Hangul, e.g. loanwords written in the
+    /// Latin alphabet or numbers, can be selected too.
+    select_all_words: bool,
+    /// If set, the only characters (besides whitespace) that separate
+    /// words of Hangul from each other. Otherwise, any character that
+    /// isn't a Hangul syllable does.
+    word_separators: Option<String>,
     lyrics_lines_to_show: usize,
     first_lyrics_line: usize,
     curr_lyrics_line: usize,
     curr_word: usize,
     curr_syllable: usize,
     lyrics: Vec<(Duration, String)>,
-    sink: Sink,
+    /// How far each line of lyrics has been shifted from its timestamp
+    /// in the LRC file, in milliseconds, to fix lyrics that drift out of
+    /// sync.
+    line_shifts: Vec<i64>,
+    /// The LRC file the lyrics came from, which they're saved back to.
+    lrc_filename: PathBuf,
+    /// The ID tags of the LRC file, so saving it doesn't lose them.
+    lrc_tags: Vec<String>,
+    /// Every line of the LRC file, not just the ones being practiced,
+    /// with the same shifts as `lyrics`.
+    lrc_lyrics: Lyrics,
+    /// The start time of every word of the lyrics, in order, if they're
+    /// synced. Empty for simple lyrics.
+    word_times: Vec<Duration>,
+    /// Each line of the lyrics, as it's pronounced after sound changes.
+    pronounced_lyrics: Vec<String>,
+    /// Whether to show the pronounced form beneath each line of lyrics.
+    show_pronounced: bool,
+    /// Whether to scroll the lyrics so that the line being played stays
+    /// in the middle of them.
+    auto_scroll: bool,
+    /// Whether auto-scrolling is off until the next line starts playing,
+    /// because the user moved to another line themselves.
+    auto_scroll_suspended: bool,
+    /// The line that was playing the last time the lyrics auto-scrolled.
+    auto_scroll_line: Option<usize>,
+    /// Total length of the song, if known.
+    total_duration: Option<Duration>,
+    /// Where the section of the song being practiced starts, if it's
+    /// limited to one.
+    since: Option<Duration>,
+    /// Where the section of the song being practiced ends, if it's
+    /// limited to one.
+    until: Option<Duration>,
+    /// The time to seek to, in `mm:ss` format, while the user is typing it.
+    time_entry: Option<String>,
+    /// Message to show in the status bar until the next key press, e.g.
+    /// an error.
+    status_message: Option<String>,
+    key_bindings: KeyBindings,
+    /// Help for the key bindings, shown at the bottom of the screen.
+    help_lines: Vec<String>,
+    /// The characters that the UI is drawn with.
+    glyphs: Glyphs,
+    /// How many times to play the current line when repeating it.
+    repeat_count: usize,
+    /// The line being repeated, if any.
+    repeat: Option<Repeat>,
+    /// What needs to be redrawn on the next render.
+    redraw: Redraw,
+    /// The line of lyrics that was drawn with the playback icon, and
+    /// the icon it was drawn with, the last time the UI was rendered.
+    rendered_playback_line: Option<(usize, &'static str)>,
+    /// How wide the terminal was the last time the UI was rendered.
+    columns: usize,
+    sink: Box<dyn Playback>,
+}
+
+/// How much of the UI to redraw, so that slow terminals don't have to
+/// redraw everything each time playback moves along.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Redraw {
+    /// Everything, e.g. after navigating or resizing the terminal.
+    Full,
+    /// Only the status bar and the lines of lyrics whose playback icon
+    /// has changed.
+    Playback,
 }
 
 impl App {
     pub fn run(&mut self) -> Result<()> {
+        self.render()?;
         loop {
-            self.render()?;
-            let event = if self.sink.is_paused() {
-                read()?
-            } else {
-                // We're playing music, and parts of our UI
-                // depend on the playback state, so don't wait
-                // forever for an event before we force a
-                // re-render.
-                if !poll(Duration::from_millis(100))? {
-                    continue;
+            let view = self.view_state();
+            if let Some(event) = self.next_event()? {
+                self.redraw = Redraw::Full;
+                if matches!(event, Event::Key(_)) {
+                    self.status_message = None;
                 }
-                read()?
-            };
 
-            // If these lines are changed, be sure to change
-            // `HELP_LINES` too.
-            if event == key(KeyCode::Esc) {
-                break;
-            } else if event == key(KeyCode::Char(' ')) {
-                self.toggle_pause();
-            } else if event == key(KeyCode::Down) || event == key_ctrl(KeyCode::Char('n')) {
+                if self.time_entry.is_some() {
+                    self.handle_time_entry_event(event);
+                } else if let Event::Mouse(mouse_event) = event {
+                    self.handle_mouse_event(mouse_event);
+                } else {
+                    match self.key_bindings.get_action(&event) {
+                        Some(Action::Quit) if self.repeat.is_some() => self.repeat = None,
+                        Some(Action::Quit) => break,
+                        Some(action) => self.handle_action(action),
+                        None => {}
+                    }
+                }
+            }
+
+            let result = self.update_repeat(Instant::now());
+            self.show_if_error(result);
+            self.update_auto_scroll();
+            let result = self.update_section();
+            self.show_if_error(result);
+            if self.view_state() != view {
+                self.redraw = Redraw::Full;
+            }
+
+            self.render()?;
+        }
+
+        Ok(())
+    }
+
+    /// Waits for the next event. While paused, nothing on screen
+    /// changes on its own, so this blocks until there is one. While
+    /// playing, it gives up after `poll_timeout` and returns `None` so
+    /// the playback state can be re-rendered. The same goes for when a
+    /// line is being repeated, even if it's paused between repetitions.
+    fn next_event(&self) -> Result<Option<Event>> {
+        let is_active = !self.sink.is_paused() || self.repeat.is_some();
+        if is_active && !poll(self.poll_timeout())? {
+            return Ok(None);
+        }
+        Ok(Some(read()?))
+    }
+
+    /// How long to wait for an event while playing: until the next line
+    /// or word of lyrics starts, so that it's highlighted right away
+    /// rather than up to a whole `POLL_INTERVAL` late, but no longer
+    /// than that.
+    fn poll_timeout(&self) -> Duration {
+        let pos = self.sink.get_pos();
+        let next_line = self
+            .lyrics
+            .iter()
+            .map(|(start, _)| *start)
+            .find(|start| *start > pos);
+        [next_line, get_next_word_time(&self.word_times, pos)]
+            .into_iter()
+            .flatten()
+            .map(|next| next - pos)
+            .fold(POLL_INTERVAL, Duration::min)
+    }
+
+    fn handle_action(&mut self, action: Action) {
+        // Seeking elsewhere would fight with the repetition.
+        if matches!(
+            action,
+            Action::PlayLine
+                | Action::Rewind
+                | Action::SeekPrevWord
+                | Action::SeekNextWord
+                | Action::SeekToTime
+        ) {
+            self.repeat = None;
+        }
+        match action {
+            Action::Quit => {}
+            Action::Pause => {
+                if self.repeat.take().is_some() {
+                    self.sink.pause();
+                } else {
+                    self.toggle_pause();
+                }
+            }
+            Action::RepeatLine => {
+                let result = self.start_repeat();
+                self.show_if_error(result);
+            }
+            Action::NextLine => {
+                self.auto_scroll_suspended = true;
                 self.go_to_next_line();
-            } else if event == key(KeyCode::Up) || event == key_ctrl(KeyCode::Char('p')) {
+            }
+            Action::PrevLine => {
+                self.auto_scroll_suspended = true;
                 self.go_to_prev_line();
-            } else if event == key(KeyCode::Left) || event == key_ctrl(KeyCode::Char('b')) {
-                self.select_prev_syllable();
-            } else if event == key(KeyCode::Right) || event == key_ctrl(KeyCode::Char('f')) {
-                self.select_next_syllable();
-            } else if event == key(KeyCode::Enter) {
-                self.seek_to_current_lyric()?;
-            } else if event == key(KeyCode::Char('b')) {
-                self.seek_backward()?;
             }
+            Action::PrevSyllable => self.select_prev_syllable(),
+            Action::NextSyllable => self.select_next_syllable(),
+            Action::PlayLine => self.show_if_error(self.seek_to_current_lyric()),
+            Action::Rewind => self.show_if_error(self.seek_backward()),
+            Action::SeekPrevWord => self.show_if_error(self.seek_to_prev_word()),
+            Action::SeekNextWord => self.show_if_error(self.seek_to_next_word()),
+            Action::TogglePronounced => self.toggle_pronounced(),
+            Action::ToggleAutoScroll => {
+                self.auto_scroll = !self.auto_scroll;
+                self.auto_scroll_suspended = false;
+                let state = if self.auto_scroll { "on" } else { "off" };
+                self.status_message = Some(format!("Auto-scroll {state}"));
+            }
+            Action::ShiftLinesEarlier => self.shift_lines(-LINE_SHIFT_MILLIS),
+            Action::ShiftLinesLater => self.shift_lines(LINE_SHIFT_MILLIS),
+            Action::SaveLyrics => {
+                let result = self.save_lyrics();
+                self.show_if_error(result);
+            }
+            Action::SeekToTime => self.time_entry = Some(String::new()),
         }
+    }
 
-        Ok(())
+    /// Shows the given error in the status bar, if there is one.
+    ///
+    /// This is used for errors that shouldn't end the whole session,
+    /// e.g. failing to seek in a format that doesn't support it.
+    fn show_if_error(&mut self, result: Result<()>) {
+        if let Err(err) = result {
+            self.status_message = Some(err.to_string());
+        }
+    }
+
+    fn handle_time_entry_event(&mut self, event: Event) {
+        let Some(entry) = &mut self.time_entry else {
+            return;
+        };
+        if event == key(KeyCode::Esc) {
+            self.time_entry = None;
+        } else if event == key(KeyCode::Enter) {
+            let entry = self.time_entry.take().unwrap_or_default();
+            if let Err(err) = self.seek_to_time(&entry) {
+                self.status_message = Some(err.to_string());
+            }
+        } else if event == key(KeyCode::Backspace) {
+            entry.pop();
+        } else if let Event::Key(KeyEvent {
+            code: KeyCode::Char(ch),
+            ..
+        }) = event
+            && (ch.is_ascii_digit() || ch == ':')
+        {
+            entry.push(ch);
+        }
+    }
+
+    /// Selects the syllable that was clicked on, if any.
+    fn handle_mouse_event(&mut self, event: MouseEvent) {
+        let is_drag = event.kind == MouseEventKind::Drag(MouseButton::Left);
+        if event.row == PROGRESS_BAR_ROW
+            && self.total_duration.is_some()
+            && (is_drag || event.kind == MouseEventKind::Down(MouseButton::Left))
+        {
+            let result = self.seek_to_progress_bar_column(event.column as usize);
+            self.show_if_error(result);
+            return;
+        }
+        if event.kind != MouseEventKind::Down(MouseButton::Left) {
+            return;
+        }
+        let Some(line_idx) = self.get_lyrics_line_at_row(event.row as usize) else {
+            return;
+        };
+        let column = (event.column as usize).checked_sub(LYRICS_PREFIX_COLUMNS);
+        let Some((word, syllable)) =
+            column.and_then(|column| self.get_syllable_at(&self.lyrics[line_idx].1, column))
+        else {
+            return;
+        };
+        self.curr_lyrics_line = line_idx;
+        self.curr_word = word;
+        self.curr_syllable = syllable;
+    }
+
+    /// Seeks to the part of the song shown at the given column of the
+    /// progress bar, if there's one there.
+    fn seek_to_progress_bar_column(&mut self, column: usize) -> Result<()> {
+        let (Some(total_duration), Some(bar_width)) =
+            (self.total_duration, self.progress_bar_width(self.columns))
+        else {
+            return Ok(());
+        };
+        let Some(pos) = progress_bar_position(column, bar_width, total_duration) else {
+            return Ok(());
+        };
+        // Seeking elsewhere would fight with the repetition.
+        self.repeat = None;
+        self.seek_to(pos)
+    }
+
+    /// Returns the index of the line of lyrics shown at the given row of
+    /// the terminal, mirroring the layout of `render_to`. Rows showing
+    /// the pronunciation of a line don't count.
+    fn get_lyrics_line_at_row(&self, row: usize) -> Option<usize> {
+        let status_bar_rows = if self.total_duration.is_some() { 2 } else { 1 };
+        let rows_per_line = if self.show_pronounced { 2 } else { 1 };
+        let offset = row.checked_sub(status_bar_rows)?;
+        if offset % rows_per_line != 0 || offset / rows_per_line >= self.visible_lyrics_lines() {
+            return None;
+        }
+        let line_idx = self.first_lyrics_line + offset / rows_per_line;
+        (line_idx < self.lyrics.len()).then_some(line_idx)
+    }
+
+    /// Returns the word and syllable shown at the given column of the
+    /// given line of lyrics (not counting the line's prefix), mirroring
+    /// how `render_lyrics` numbers them. Returns None if there isn't a
+    /// selectable syllable there.
+    fn get_syllable_at(&self, line: &str, column: usize) -> Option<(usize, usize)> {
+        let mut start_column = 0;
+        let mut word_idx = 0;
+        for (class, str) in self.split_into_words(line) {
+            let is_selectable = self.is_selectable_word(class, str);
+            for (_, char, syllable_idx) in numbered_chars(str) {
+                let end_column = start_column + char_width(char);
+                if (start_column..end_column).contains(&column) {
+                    if !is_selectable {
+                        return None;
+                    } else if class != HangulCharClass::Syllables {
+                        return Some((word_idx, 0));
+                    } else {
+                        return syllable_idx.map(|syllable_idx| (word_idx, syllable_idx));
+                    }
+                }
+                start_column = end_column;
+            }
+            if is_selectable {
+                word_idx += 1;
+            }
+        }
+        None
+    }
+
+    /// Splits the given line into chunks, like `HangulCharClass::split`,
+    /// except that if `word_separators` is set, runs of syllables that
+    /// are only separated by other characters are kept together as one
+    /// word.
+    fn split_into_words<'a>(&self, line: &'a str) -> Vec<(HangulCharClass, &'a str)> {
+        split_into_words(line, self.word_separators.as_deref())
+    }
+
+    /// Whether the given chunk of a line, as returned by
+    /// `split_into_words`, is a word that can be selected.
+    fn is_selectable_word(&self, class: HangulCharClass, word: &str) -> bool {
+        match class {
+            HangulCharClass::Syllables => true,
+            // Punctuation and spacing aren't words.
+            HangulCharClass::None => {
+                self.select_all_words && word.chars().any(char::is_alphanumeric)
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns the selected word if it isn't Hangul, and so can't be
+    /// analyzed.
+    fn get_non_hangul_selection(&self) -> Option<&str> {
+        let (_, line) = self.lyrics.get(self.curr_lyrics_line)?;
+        let (class, word) = self
+            .split_into_words(line)
+            .into_iter()
+            .filter(|(class, word)| self.is_selectable_word(*class, word))
+            .nth(self.curr_word)?;
+        if class == HangulCharClass::Syllables {
+            None
+        } else {
+            Some(word.trim())
+        }
     }
 
     fn get_selection(&self) -> Option<(Selection, Selection)> {
         if let Some((_, line)) = self.lyrics.get(self.curr_lyrics_line) {
             let mut word_idx = 0;
-            for (class, word) in HangulCharClass::split(&line) {
-                if class == HangulCharClass::Syllables {
+            for (class, word) in self.split_into_words(line) {
+                if self.is_selectable_word(class, word) {
                     if word_idx == self.curr_word {
+                        if class != HangulCharClass::Syllables {
+                            return None;
+                        }
                         let selection = Selection::new(Cow::Borrowed(word), self.curr_syllable);
                         let pronounced_word =
                             compose_all_hangul_jamos(apply_pronunciation_rules_to_jamos(
@@ -127,116 +681,264 @@ impl App {
     }
 
     fn get_playback_line_idx(&self) -> Option<usize> {
-        let sink_pos = self.sink.get_pos();
-        let mut latest_idx = None;
-        for (idx, (pos, _)) in self.lyrics.iter().enumerate() {
-            if pos <= &sink_pos {
-                latest_idx = Some(idx);
-            } else {
-                return latest_idx;
+        get_line_idx_at(&self.lyrics, self.sink.get_pos())
+    }
+
+    /// Returns what determines which lines of lyrics are shown and how
+    /// the selection is drawn, so that changes to it can be detected.
+    fn view_state(&self) -> (usize, usize, usize, usize) {
+        (
+            self.first_lyrics_line,
+            self.curr_lyrics_line,
+            self.curr_word,
+            self.curr_syllable,
+        )
+    }
+
+    pub fn render(&mut self) -> Result<()> {
+        let mut stdout = stdout();
+        let (columns, rows) = size()?;
+        self.render_dirty_to(&mut stdout, columns as usize, rows)?;
+        stdout.flush()?;
+        Ok(())
+    }
+
+    /// Returns the line of lyrics that should be drawn with the playback
+    /// icon, along with the icon.
+    fn playback_line(&self) -> Option<(usize, &'static str)> {
+        self.get_playback_line_idx()
+            .map(|idx| (idx, self.playback_icon()))
+    }
+
+    /// Returns the visible lines of lyrics that need to be redrawn
+    /// because the playback icon has moved onto or off of them since
+    /// the last render.
+    fn dirty_lyrics_lines(&self) -> Vec<usize> {
+        let playback_line = self.playback_line();
+        if playback_line == self.rendered_playback_line {
+            return vec![];
+        }
+        let visible = self.first_lyrics_line..self.first_lyrics_line + self.visible_lyrics_lines();
+        let mut lines: Vec<usize> = [self.rendered_playback_line, playback_line]
+            .into_iter()
+            .flatten()
+            .map(|(idx, _)| idx)
+            .filter(|idx| visible.contains(idx) && *idx < self.lyrics.len())
+            .collect();
+        lines.dedup();
+        lines
+    }
+
+    /// Renders whatever has changed since the last render, which is
+    /// everything if `redraw` is `Redraw::Full`.
+    fn render_dirty_to(
+        &mut self,
+        stdout: &mut impl Write,
+        columns: usize,
+        rows: u16,
+    ) -> Result<()> {
+        match self.redraw {
+            Redraw::Full => self.render_to(stdout, columns, rows)?,
+            Redraw::Playback => {
+                stdout.queue(MoveTo(0, 0))?;
+                self.render_status_bar(stdout, columns)?;
+                let status_bar_height = if self.total_duration.is_some() { 2 } else { 1 };
+                let line_height = if self.show_pronounced { 2 } else { 1 };
+                let playback_line_idx = self.get_playback_line_idx();
+                for idx in self.dirty_lyrics_lines() {
+                    let row = status_bar_height + (idx - self.first_lyrics_line) * line_height;
+                    stdout.queue(MoveTo(0, row as u16))?;
+                    self.render_lyrics_line(stdout, idx, playback_line_idx)?;
+                }
             }
         }
-        None
+        self.redraw = Redraw::Playback;
+        self.rendered_playback_line = self.playback_line();
+        self.columns = columns;
+        Ok(())
     }
 
-    pub fn render(&self) -> Result<()> {
-        let mut stdout = stdout();
+    /// Renders the whole UI to the given terminal output, which is
+    /// assumed to have the given size. Tiny terminals get a compact
+    /// version of the UI that may not show everything, rather than
+    /// a panic.
+    fn render_to(&self, stdout: &mut impl Write, columns: usize, rows: u16) -> Result<()> {
         stdout.queue(MoveTo(0, 0))?;
-        self.render_status_bar(&mut stdout)?;
-        self.render_lyrics(&mut stdout)?;
-        self.render_selection_info(&mut stdout)?;
-        stdout.queue(MoveTo(0, size()?.1 - help_lines_two_column_height() as u16))?;
-        self.render_help(&mut stdout)?;
-        stdout.flush()?;
+        self.render_status_bar(stdout, columns)?;
+        self.render_lyrics(stdout)?;
+        self.render_selection_info(stdout, columns)?;
+        let help_height = self.help_height(columns) as u16;
+        stdout.queue(MoveTo(0, rows.saturating_sub(help_height)))?;
+        self.render_help(stdout, columns)?;
         Ok(())
     }
 
     fn playback_icon(&self) -> &'static str {
         if self.sink.is_paused() {
-            "⏸︎"
+            self.glyphs.paused
+        } else {
+            self.glyphs.playing
+        }
+    }
+
+    /// Returns what to show in the status bar in place of the title.
+    fn status_text(&self) -> String {
+        if let Some(entry) = &self.time_entry {
+            format!("Seek to (mm:ss): {entry}_")
+        } else if let Some(message) = &self.status_message {
+            message.clone()
+        } else if let Some(repeat) = &self.repeat {
+            format!("REPEAT {}/{}", repeat.iteration, self.repeat_count)
         } else {
-            "⏵︎"
+            self.title.clone()
         }
     }
 
-    fn render_status_bar(&self, stdout: &mut Stdout) -> Result<()> {
+    fn render_status_bar(&self, stdout: &mut impl Write, columns: usize) -> Result<()> {
         stdout.queue(SetAttribute(Attribute::Reverse))?;
-        let columns = size()?.0 as usize;
-        stdout.queue(Print(format!(
-            " HANGUL-FUN {:>width$.width$} {} ",
-            self.title,
-            self.playback_icon(),
-            width = columns - 15
-        )))?;
+        if columns < MIN_STATUS_BAR_COLUMNS {
+            stdout.queue(Print(format!(" {} ", self.playback_icon())))?;
+        } else {
+            let icon = self.playback_icon();
+            let icon_width: usize = icon.chars().map(char_width).sum();
+            stdout.queue(Print(format!(
+                " HANGUL-FUN {:>width$.width$} {icon} ",
+                self.status_text(),
+                width = columns.saturating_sub(14 + icon_width)
+            )))?;
+        }
         stdout.queue(SetAttribute(Attribute::NoReverse))?;
         stdout.queue(MoveToNextLine(1))?;
+        if let (Some(total_duration), Some(bar_width)) =
+            (self.total_duration, self.progress_bar_width(columns))
+        {
+            let pos = self.sink.get_pos().min(total_duration);
+            let fraction = pos.as_secs_f64() / total_duration.as_secs_f64().max(f64::EPSILON);
+            stdout.queue(PrintStyledContent(
+                progress_bar(fraction, bar_width, &self.glyphs).with(Color::DarkGrey),
+            ))?;
+            stdout.queue(Print(self.progress_times(total_duration)))?;
+            stdout.queue(Clear(ClearType::UntilNewLine))?;
+            stdout.queue(MoveToNextLine(1))?;
+        }
         Ok(())
     }
 
-    fn render_lyrics(&self, stdout: &mut Stdout) -> Result<()> {
-        let lyrics = &self.lyrics;
+    /// Returns the times shown after the progress bar, e.g.
+    /// " 01:05 / 03:30".
+    fn progress_times(&self, total_duration: Duration) -> String {
+        let pos = self.sink.get_pos().min(total_duration);
+        format!(" {} / {}", format_mm_ss(pos), format_mm_ss(total_duration))
+    }
+
+    /// Returns how wide the progress bar is in a terminal of the given
+    /// width, if there is one. It starts at the first column.
+    fn progress_bar_width(&self, columns: usize) -> Option<usize> {
+        let total_duration = self.total_duration?;
+        Some(columns.saturating_sub(self.progress_times(total_duration).chars().count()))
+    }
+
+    /// Returns how many columns of help fit in the given width.
+    fn help_columns(&self, columns: usize) -> usize {
+        let widest = self
+            .help_lines
+            .iter()
+            .map(|line| line.chars().count())
+            .max()
+            .unwrap_or(0);
+        if columns / 2 > widest { 2 } else { 1 }
+    }
+
+    fn help_height(&self, columns: usize) -> usize {
+        self.help_lines.len().div_ceil(self.help_columns(columns))
+    }
+
+    fn render_lyrics(&self, stdout: &mut impl Write) -> Result<()> {
         let mut i = self.first_lyrics_line;
         let playback_line_idx = self.get_playback_line_idx();
-        loop {
-            let Some((_, line)) = lyrics.get(i) else {
+        while i < self.lyrics.len() {
+            self.render_lyrics_line(stdout, i, playback_line_idx)?;
+            i += 1;
+            if i >= self.first_lyrics_line + self.visible_lyrics_lines() {
                 break;
-            };
-            if i == self.curr_lyrics_line {
-                stdout.queue(Print("> "))?;
-                let mut word_idx = 0;
-                for (class, str) in HangulCharClass::split(&line) {
-                    if class == HangulCharClass::Syllables {
-                        if word_idx == self.curr_word {
-                            let mut syllable_idx = 0;
-                            for (idx, char) in str.char_indices() {
-                                let syllable = (&str[idx..idx + char.len_utf8()]).on(Color::Grey);
-                                if syllable_idx == self.curr_syllable {
-                                    stdout.queue(PrintStyledContent(syllable.with(Color::Blue)))?;
-                                } else {
-                                    stdout
-                                        .queue(PrintStyledContent(syllable.with(Color::Black)))?;
-                                }
-                                syllable_idx += 1;
-                            }
-                        } else {
-                            stdout.queue(Print(str))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Renders the given line of lyrics, followed by its pronunciation
+    /// if that's being shown.
+    fn render_lyrics_line(
+        &self,
+        stdout: &mut impl Write,
+        i: usize,
+        playback_line_idx: Option<usize>,
+    ) -> Result<()> {
+        let line = &self.lyrics[i].1;
+        if i == self.curr_lyrics_line {
+            stdout.queue(Print("> "))?;
+            let mut word_idx = 0;
+            for (class, str) in self.split_into_words(line) {
+                if self.is_selectable_word(class, str) {
+                    if word_idx == self.curr_word && class != HangulCharClass::Syllables {
+                        let word = self.highlight_style.style_syllable(str, true);
+                        stdout.queue(PrintStyledContent(word))?;
+                    } else if word_idx == self.curr_word {
+                        // Words can contain punctuation between their
+                        // syllables, if it isn't one of the word
+                        // separators.
+                        for (idx, char, syllable_idx) in numbered_chars(str) {
+                            let syllable = self.highlight_style.style_syllable(
+                                &str[idx..idx + char.len_utf8()],
+                                syllable_idx == Some(self.curr_syllable),
+                            );
+                            stdout.queue(PrintStyledContent(syllable))?;
                         }
-                        word_idx += 1;
                     } else {
                         stdout.queue(Print(str))?;
                     }
-                }
-            } else {
-                if Some(i) == playback_line_idx {
-                    stdout.queue(PrintStyledContent(self.playback_icon().with(Color::Grey)))?;
-                    stdout.queue(Print(" "))?;
+                    word_idx += 1;
                 } else {
-                    stdout.queue(Print("  "))?;
+                    stdout.queue(Print(str))?;
                 }
-                stdout.queue(Print(&line))?;
             }
+        } else {
+            if Some(i) == playback_line_idx {
+                let icon = self.playback_icon();
+                let icon_width: usize = icon.chars().map(char_width).sum();
+                stdout.queue(PrintStyledContent(icon.with(Color::Grey)))?;
+                let padding = LYRICS_PREFIX_COLUMNS.saturating_sub(icon_width);
+                stdout.queue(Print(" ".repeat(padding)))?;
+            } else {
+                stdout.queue(Print("  "))?;
+            }
+            stdout.queue(Print(&line))?;
+        }
+        stdout.queue(Clear(ClearType::UntilNewLine))?;
+        stdout.queue(MoveToNextLine(1))?;
+        if self.show_pronounced {
+            let pronounced = &self.pronounced_lyrics[i];
+            stdout.queue(Print("  "))?;
+            stdout.queue(PrintStyledContent(
+                pronounced.as_str().with(Color::DarkGrey),
+            ))?;
             stdout.queue(Clear(ClearType::UntilNewLine))?;
             stdout.queue(MoveToNextLine(1))?;
-            i += 1;
-            if i >= self.first_lyrics_line + self.lyrics_lines_to_show {
-                break;
-            }
         }
         Ok(())
     }
 
-    fn render_horizontal_line(&self, stdout: &mut Stdout) -> Result<()> {
-        let cols = size()?.0 as usize;
-        let mut line = String::with_capacity(cols);
-        for _ in 0..cols {
-            line.push('⎯');
+    fn render_horizontal_line(&self, stdout: &mut impl Write, columns: usize) -> Result<()> {
+        let mut line = String::with_capacity(columns);
+        for _ in 0..columns {
+            line.push(self.glyphs.horizontal_line);
         }
         stdout.queue(Print(line))?;
         stdout.queue(MoveToNextLine(1))?;
         Ok(())
     }
 
-    fn render_cleared_lines(&self, stdout: &mut Stdout, count: usize) -> Result<()> {
+    fn render_cleared_lines(&self, stdout: &mut impl Write, count: usize) -> Result<()> {
         for _ in 0..count {
             stdout.queue(Clear(ClearType::CurrentLine))?;
             stdout.queue(MoveToNextLine(1))?;
@@ -244,75 +946,93 @@ impl App {
         Ok(())
     }
 
-    fn render_selection_info(&self, stdout: &mut Stdout) -> Result<()> {
+    /// Prints a line of the selection info, truncated to fit on one
+    /// line of the terminal.
+    fn render_info_line(&self, stdout: &mut impl Write, text: &str, columns: usize) -> Result<()> {
+        stdout.queue(Print(truncate_to_width(
+            text,
+            columns,
+            self.glyphs.ellipsis,
+        )))?;
+        stdout.queue(Clear(ClearType::UntilNewLine))?;
+        stdout.queue(MoveToNextLine(1))?;
+        Ok(())
+    }
+
+    fn render_selection_info(&self, stdout: &mut impl Write, columns: usize) -> Result<()> {
         if let Some((original_selection, pronounced_selection)) = self.get_selection() {
             let mut clear_extra_lines = 0;
-            self.render_horizontal_line(stdout)?;
-            stdout.queue(Print("Selected word: "))?;
-            stdout.queue(Print(&original_selection.word))?;
+            self.render_horizontal_line(stdout, columns)?;
+            let mut word_line = format!("Selected word: {}", original_selection.word);
             if pronounced_selection.word != original_selection.word {
-                stdout.queue(Print(format!(" → {}", &pronounced_selection.word)))?;
+                word_line.push_str(&format!(
+                    " {} {}",
+                    self.glyphs.arrow, &pronounced_selection.word
+                ));
             }
-            let decomposed = decompose_all_hangul_syllables(&pronounced_selection.word);
-            let romanized = romanize_decomposed_hangul(&decomposed);
-            stdout.queue(Print(format!(" ({romanized})")))?;
-            stdout.queue(Clear(ClearType::UntilNewLine))?;
-            stdout.queue(MoveToNextLine(1))?;
+            let romanized = romanize(&pronounced_selection.word);
+            word_line.push_str(&format!(" ({romanized})"));
+            self.render_info_line(stdout, &word_line, columns)?;
 
-            stdout.queue(Print(format!("Selected syllable: ")))?;
-            stdout.queue(Print(pronounced_selection.syllable_str()))?;
-            stdout.queue(Clear(ClearType::UntilNewLine))?;
-            stdout.queue(MoveToNextLine(1))?;
-            let initial_ch = pronounced_selection.initial_jamo.curr;
-            let initial_compat = hangul_jamo_to_compat_with_fallback(initial_ch);
-            let mut initial_rom =
-                get_romanized_jamo(&pronounced_selection.initial_jamo).unwrap_or("?");
-            if initial_rom == "" {
-                initial_rom = "silent";
+            let mut syllable_line =
+                format!("Selected syllable: {}", pronounced_selection.syllable_str());
+            let sound_changes = get_sound_changes(&original_selection.word, self.curr_syllable);
+            if !sound_changes.is_empty() {
+                syllable_line.push_str(&format!("  Sound change: {}", sound_changes.join(", ")));
             }
+            self.render_info_line(stdout, &syllable_line, columns)?;
+            let initial_ch = pronounced_selection.initial_jamo.curr;
+            let initial_compat = display_jamo(initial_ch, self.raw_jamos);
+            let initial_rom = romanize_initial_display(initial_ch);
             let initial_hint = get_jamo_pronunciation(&pronounced_selection.initial_jamo);
             let medial_ch = pronounced_selection.medial_jamo.curr;
-            let medial_compat = hangul_jamo_to_compat_with_fallback(medial_ch);
+            let medial_compat = display_jamo(medial_ch, self.raw_jamos);
             let medial_rom = get_romanized_jamo(&pronounced_selection.medial_jamo).unwrap_or("?");
             let medial_hint = get_jamo_pronunciation(&pronounced_selection.medial_jamo);
-            stdout.queue(Print(format!(
-                "  Initial: {initial_compat} ({initial_rom}) {initial_hint}"
-            )))?;
-            stdout.queue(Clear(ClearType::UntilNewLine))?;
-            stdout.queue(MoveToNextLine(1))?;
-            stdout.queue(Print(format!(
-                "  Medial : {medial_compat} ({medial_rom}) {medial_hint}"
-            )))?;
-            stdout.queue(Clear(ClearType::UntilNewLine))?;
-            stdout.queue(MoveToNextLine(1))?;
+            self.render_info_line(
+                stdout,
+                &format!("  Initial: {initial_compat} ({initial_rom}) {initial_hint}"),
+                columns,
+            )?;
+            self.render_info_line(
+                stdout,
+                &format!("  Medial : {medial_compat} ({medial_rom}) {medial_hint}"),
+                columns,
+            )?;
             if let Some(final_jamo) = pronounced_selection.final_jamo {
                 let final_ch = final_jamo.curr;
-                let final_compat = hangul_jamo_to_compat_with_fallback(final_ch);
+                let final_compat = display_jamo(final_ch, self.raw_jamos);
                 let final_rom = get_romanized_jamo(&final_jamo).unwrap_or("?");
                 let final_hint = get_jamo_pronunciation(&final_jamo);
-                stdout.queue(Print(format!(
-                    "  Final  : {final_compat} ({final_rom}) {final_hint}"
-                )))?;
-                stdout.queue(Clear(ClearType::UntilNewLine))?;
-                stdout.queue(MoveToNextLine(1))?;
+                self.render_info_line(
+                    stdout,
+                    &format!("  Final  : {final_compat} ({final_rom}) {final_hint}"),
+                    columns,
+                )?;
             } else {
                 clear_extra_lines += 1;
             }
-            self.render_horizontal_line(stdout)?;
+            self.render_horizontal_line(stdout, columns)?;
             self.render_cleared_lines(stdout, clear_extra_lines)?;
+        } else if let Some(word) = self.get_non_hangul_selection() {
+            self.render_horizontal_line(stdout, columns)?;
+            self.render_info_line(stdout, &format!("Selected: {word} (no analysis)"), columns)?;
+            self.render_horizontal_line(stdout, columns)?;
+            self.render_cleared_lines(stdout, 4)?;
         } else {
             self.render_cleared_lines(stdout, 7)?;
         }
         Ok(())
     }
 
-    fn render_help(&self, stdout: &mut Stdout) -> Result<()> {
-        let col_2 = size()?.0 / 2;
-        let height = help_lines_two_column_height();
+    fn render_help(&self, stdout: &mut impl Write, columns: usize) -> Result<()> {
+        let col_2 = (columns / 2) as u16;
+        let height = self.help_height(columns);
         for i in 0..height {
-            let first_col = HELP_LINES[i];
+            let first_col = self.help_lines[i].as_str();
             stdout.queue(PrintStyledContent(first_col.with(Color::DarkGrey)))?;
-            if let Some(&second_col) = HELP_LINES.get(height + i) {
+            if let Some(second_col) = self.help_lines.get(height + i) {
+                let second_col = second_col.as_str();
                 stdout.queue(MoveToColumn(col_2))?;
                 stdout.queue(PrintStyledContent(second_col.with(Color::DarkGrey)))?;
             }
@@ -327,10 +1047,62 @@ impl App {
             self.curr_lyrics_line += 1;
             self.curr_word = 0;
             self.curr_syllable = 0;
-            if self.first_lyrics_line + self.lyrics_lines_to_show <= self.curr_lyrics_line {
-                self.first_lyrics_line += 1;
-            }
+            self.scroll_to_current_line();
+        }
+    }
+
+    /// Returns how many lines of lyrics fit in the space allotted to
+    /// them, which is halved when each line is followed by its
+    /// pronunciation.
+    fn visible_lyrics_lines(&self) -> usize {
+        if self.show_pronounced {
+            (self.lyrics_lines_to_show / 2).max(1)
+        } else {
+            self.lyrics_lines_to_show
+        }
+    }
+
+    /// Scrolls the lyrics down, if needed, so the current line is visible.
+    fn scroll_to_current_line(&mut self) {
+        let visible_lines = self.visible_lyrics_lines();
+        if self.first_lyrics_line + visible_lines <= self.curr_lyrics_line {
+            self.first_lyrics_line = self.curr_lyrics_line + 1 - visible_lines;
+        }
+    }
+
+    /// Scrolls the lyrics so that the line being played is in the middle
+    /// of them, if auto-scrolling is on. This only moves the view, not
+    /// the selected line.
+    fn update_auto_scroll(&mut self) {
+        let playback_line_idx = self.get_playback_line_idx();
+        if playback_line_idx != self.auto_scroll_line {
+            self.auto_scroll_line = playback_line_idx;
+            self.auto_scroll_suspended = false;
+        }
+        if !self.auto_scroll || self.auto_scroll_suspended {
+            return;
+        }
+        if let Some(idx) = playback_line_idx {
+            self.first_lyrics_line =
+                centered_first_line(idx, self.visible_lyrics_lines(), self.lyrics.len());
+        }
+    }
+
+    /// Loops back to the start of the section of the song being
+    /// practiced, if playback has reached its end.
+    fn update_section(&mut self) -> Result<()> {
+        let Some(until) = self.until else {
+            return Ok(());
+        };
+        if self.sink.is_paused() || self.sink.get_pos() < until {
+            return Ok(());
         }
+        self.seek_to(self.since.unwrap_or_default())
+    }
+
+    fn toggle_pronounced(&mut self) {
+        self.show_pronounced = !self.show_pronounced;
+        self.scroll_to_current_line();
     }
 
     pub fn go_to_prev_line(&mut self) {
@@ -344,14 +1116,18 @@ impl App {
         }
     }
 
+    /// Returns the number of syllables in each selectable word of the
+    /// current line. Words that aren't Hangul count as one syllable.
     fn get_curr_line_word_lengths(&self) -> Vec<usize> {
-        HangulCharClass::split(&self.lyrics[self.curr_lyrics_line].1)
+        self.split_into_words(&self.lyrics[self.curr_lyrics_line].1)
             .into_iter()
             .filter_map(|(class, str)| {
-                if class != HangulCharClass::Syllables {
+                if !self.is_selectable_word(class, str) {
                     None
+                } else if class == HangulCharClass::Syllables {
+                    Some(count_hangul_syllables(str))
                 } else {
-                    Some(str.chars().count())
+                    Some(1)
                 }
             })
             .collect()
@@ -390,7 +1166,7 @@ impl App {
     }
 
     fn seek_to(&self, pos: Duration) -> Result<()> {
-        if let Err(err) = self.sink.try_seek(pos.clone()) {
+        if let Err(err) = self.sink.try_seek(pos) {
             return Err(anyhow!("Failed to seek: {err}"));
         }
         self.sink.play();
@@ -404,73 +1180,476 @@ impl App {
         Ok(())
     }
 
-    fn seek_backward(&self) -> Result<()> {
-        let curr_pos = self.sink.get_pos();
-        self.seek_to(curr_pos.saturating_sub(Duration::from_secs(REWIND_SECS)))
+    /// Seeks to the given `mm:ss` time, and makes the line of lyrics
+    /// being performed at that time the current one.
+    fn seek_to_time(&mut self, entry: &str) -> Result<()> {
+        let Some(pos) = parse_mm_ss(entry) else {
+            return Err(anyhow!("Invalid time '{entry}', expected mm:ss"));
+        };
+        if let Some(total_duration) = self.total_duration
+            && pos > total_duration
+        {
+            return Err(anyhow!(
+                "{} is past the end of the song ({})",
+                format_mm_ss(pos),
+                format_mm_ss(total_duration)
+            ));
+        }
+        self.seek_to(pos)?;
+        self.curr_lyrics_line = get_line_idx_at(&self.lyrics, pos).unwrap_or(0);
+        self.curr_word = 0;
+        self.curr_syllable = 0;
+        if self.first_lyrics_line > self.curr_lyrics_line {
+            self.first_lyrics_line = self.curr_lyrics_line;
+        }
+        self.scroll_to_current_line();
+        Ok(())
     }
-}
-
-struct Selection<'a> {
-    word: Cow<'a, str>,
-    syllable_idx: usize,
-    syllable: char,
-    initial_jamo: JamoInStream,
-    medial_jamo: JamoInStream,
-    final_jamo: Option<JamoInStream>,
-}
 
-impl<'a> Selection<'a> {
-    fn new(word: Cow<'a, str>, syllable: usize) -> Option<Self> {
-        let mut syllable_idx = 0;
-        for (idx, char) in word.char_indices() {
-            if syllable_idx == syllable {
-                let mut jamo_stream = JamoStream::from_hangul_syllables(&word);
-                jamo_stream.seek_to_syllable(syllable_idx);
-                let initial_jamo = jamo_stream.next().unwrap();
-                let medial_jamo = jamo_stream.next().unwrap();
-                let final_jamo = if count_jamos_in_syllable(char) == 3 {
-                    jamo_stream.next()
-                } else {
-                    None
-                };
-                return Some(Selection {
-                    word,
-                    syllable: char,
-                    syllable_idx: idx,
-                    initial_jamo,
-                    medial_jamo,
-                    final_jamo,
-                });
-            }
-            syllable_idx += 1;
+    /// Moves the selected line and every line after it (and their words,
+    /// if they're synced) by the given number of milliseconds.
+    fn shift_lines(&mut self, delta_ms: i64) {
+        let Some((start, _)) = self.lyrics.get(self.curr_lyrics_line) else {
+            return;
+        };
+        let start = *start;
+        let delta_ms = shift_lines_from(&mut self.lyrics, self.curr_lyrics_line, delta_ms);
+        for time in self.word_times.iter_mut().filter(|time| **time >= start) {
+            *time = shift_duration(*time, delta_ms);
         }
-        None
+        self.word_times.sort();
+        self.lrc_lyrics
+            .shift_from(start.as_millis() as u64, delta_ms);
+        for shift in &mut self.line_shifts[self.curr_lyrics_line..] {
+            *shift += delta_ms;
+        }
+        self.status_message = Some(format!(
+            "Lines {} on shifted by {:+}ms",
+            self.curr_lyrics_line + 1,
+            self.line_shifts[self.curr_lyrics_line]
+        ));
     }
 
-    fn syllable_str(&self) -> &str {
-        &self.word[self.syllable_idx..self.syllable_idx + self.syllable.len_utf8()]
+    /// Returns the LRC file with the lines as they've been shifted.
+    fn lrc_contents(&self) -> String {
+        let mut result = String::new();
+        for tag in &self.lrc_tags {
+            result.push_str(tag);
+            result.push('\n');
+        }
+        result + &self.lrc_lyrics.to_lrc()
     }
-}
 
-fn key(code: KeyCode) -> Event {
-    Event::Key(code.into())
+    /// Writes the lines as they've been shifted back to the LRC file.
+    /// It's always written as UTF-8, whatever encoding it was read as.
+    fn save_lyrics(&mut self) -> Result<()> {
+        write(&self.lrc_filename, self.lrc_contents()).map_err(|err| {
+            anyhow!(
+                "Couldn't save {}: {err}",
+                self.lrc_filename.to_string_lossy()
+            )
+        })?;
+        self.status_message = Some(format!("Saved {}", self.lrc_filename.to_string_lossy()));
+        Ok(())
+    }
+
+    fn seek_backward(&self) -> Result<()> {
+        let curr_pos = self.sink.get_pos();
+        self.seek_to(curr_pos.saturating_sub(Duration::from_secs(REWIND_SECS)))
+    }
+
+    /// Starts playing the current line over and over, `repeat_count`
+    /// times in all.
+    fn start_repeat(&mut self) -> Result<()> {
+        let Some((start, _)) = self.lyrics.get(self.curr_lyrics_line) else {
+            return Ok(());
+        };
+        let start = *start;
+        let end = get_line_end(&self.lyrics, self.curr_lyrics_line)
+            .or(self.until)
+            .or(self.total_duration);
+        let Some(end) = end else {
+            return Err(anyhow!(
+                "Can't repeat the last line, the length of the song is unknown"
+            ));
+        };
+        self.seek_to(start)?;
+        self.repeat = Some(Repeat {
+            start,
+            end,
+            iteration: 1,
+            resume_at: None,
+        });
+        Ok(())
+    }
+
+    /// Advances the line being repeated, if any: pauses at the end of
+    /// each repetition, and starts the next one when it's time.
+    fn update_repeat(&mut self, now: Instant) -> Result<()> {
+        let Some(repeat) = &mut self.repeat else {
+            return Ok(());
+        };
+        if let Some(resume_at) = repeat.resume_at {
+            if now < resume_at {
+                return Ok(());
+            }
+            repeat.resume_at = None;
+            repeat.iteration += 1;
+            let start = repeat.start;
+            let result = self.seek_to(start);
+            if result.is_err() {
+                self.repeat = None;
+            }
+            result
+        } else {
+            if self.sink.get_pos() < repeat.end {
+                return Ok(());
+            }
+            self.sink.pause();
+            if repeat.iteration >= self.repeat_count {
+                self.repeat = None;
+            } else {
+                repeat.resume_at = Some(now + REPEAT_GAP);
+            }
+            Ok(())
+        }
+    }
+
+    /// Seeks to the start of the previous word, if the lyrics are
+    /// synced, or rewinds `REWIND_SECS` seconds otherwise.
+    fn seek_to_prev_word(&self) -> Result<()> {
+        let curr_pos = self.sink.get_pos();
+        match get_prev_word_time(&self.word_times, curr_pos) {
+            Some(pos) => self.seek_to(pos),
+            None => self.seek_backward(),
+        }
+    }
+
+    /// Seeks to the start of the next word, if the lyrics are synced,
+    /// or skips ahead `REWIND_SECS` seconds otherwise.
+    fn seek_to_next_word(&self) -> Result<()> {
+        let curr_pos = self.sink.get_pos();
+        match get_next_word_time(&self.word_times, curr_pos) {
+            Some(pos) => self.seek_to(pos),
+            None => self.seek_to(curr_pos + Duration::from_secs(REWIND_SECS)),
+        }
+    }
+}
+
+/// Returns the names of the pronunciation rules that change the
+/// sound at either boundary of the given syllable of the word.
+fn get_sound_changes(word: &str, syllable_idx: usize) -> Vec<&'static str> {
+    let mut names = vec![];
+    for application in explain_pronunciation_rules(decompose_all_hangul_syllables(word)) {
+        let touches_syllable = application.syllable_index == syllable_idx
+            || application.syllable_index + 1 == syllable_idx;
+        if touches_syllable && !names.contains(&application.name) {
+            names.push(application.name);
+        }
+    }
+    names
+}
+
+struct Selection<'a> {
+    word: Cow<'a, str>,
+    syllable_idx: usize,
+    syllable: char,
+    initial_jamo: JamoInStream,
+    medial_jamo: JamoInStream,
+    final_jamo: Option<JamoInStream>,
 }
 
-fn key_ctrl(code: KeyCode) -> Event {
-    Event::Key(KeyEvent::new(code, KeyModifiers::CONTROL))
+impl<'a> Selection<'a> {
+    fn new(word: Cow<'a, str>, syllable: usize) -> Option<Self> {
+        let (idx, char) = *syllable_offsets(&word).get(syllable)?;
+        // The jamo stream numbers syllables by their initial consonants,
+        // which a stray jamo in the word would throw off, so seek by
+        // where the syllable is instead.
+        let stream_syllable = decompose_all_hangul_syllables(&word[..idx])
+            .chars()
+            .filter(|&ch| ModernJamo::is_initial_consonant(ch))
+            .count();
+        let mut jamo_stream = JamoStream::from_hangul_syllables(&word);
+        jamo_stream.seek_to_syllable(stream_syllable);
+        let initial_jamo = jamo_stream.next().unwrap();
+        let medial_jamo = jamo_stream.next().unwrap();
+        let final_jamo = if count_jamos_in_syllable(char) == 3 {
+            jamo_stream.next()
+        } else {
+            None
+        };
+        Some(Selection {
+            word,
+            syllable: char,
+            syllable_idx: idx,
+            initial_jamo,
+            medial_jamo,
+            final_jamo,
+        })
+    }
+
+    fn syllable_str(&self) -> &str {
+        &self.word[self.syllable_idx..self.syllable_idx + self.syllable.len_utf8()]
+    }
 }
 
-fn help_lines_two_column_height() -> usize {
-    (HELP_LINES.len() as f32 / 2.0).ceil() as usize
+/// Splits the given line into chunks, like `HangulCharClass::split`.
+///
+/// If `word_separators` is given, though, runs of syllables are only
+/// split into separate words by whitespace or the given characters, so
+/// e.g. "안녕,반가워" is one word unless `,` is a separator.
+fn split_into_words<'a>(
+    line: &'a str,
+    word_separators: Option<&str>,
+) -> Vec<(HangulCharClass, &'a str)> {
+    let chunks = HangulCharClass::split(line);
+    let Some(word_separators) = word_separators else {
+        return chunks;
+    };
+    let separates_words = |text: &str| {
+        text.chars()
+            .any(|ch| ch.is_whitespace() || word_separators.contains(ch))
+    };
+    let mut result = Vec::with_capacity(chunks.len());
+    let mut start = 0;
+    let mut idx = 0;
+    while idx < chunks.len() {
+        let (class, text) = chunks[idx];
+        let mut end = start + text.len();
+        if class == HangulCharClass::Syllables {
+            while let (Some((between_class, between)), Some((HangulCharClass::Syllables, next))) =
+                (chunks.get(idx + 1), chunks.get(idx + 2))
+                && *between_class != HangulCharClass::Syllables
+                && !separates_words(between)
+            {
+                end += between.len() + next.len();
+                idx += 2;
+            }
+        }
+        result.push((class, &line[start..end]));
+        start = end;
+        idx += 1;
+    }
+    result
 }
 
-fn lyrics_to_vec(lyrics: Lyrics) -> Vec<(Duration, String)> {
-    let simple_vec = match lyrics {
-        Lyrics::SimpleLyrics(simple_lyrics) => simple_lyrics.0,
-        Lyrics::SyncedLyrics(synced_lyrics) => synced_lyrics.to_simple().0,
+/// Returns how many columns of the terminal the given character takes
+/// up. Hangul syllables and other East Asian characters are twice as
+/// wide as Latin ones, while conjoining vowels and finals combine with
+/// the initial before them.
+fn char_width(ch: char) -> usize {
+    match ch {
+        '\u{0300}'..='\u{036f}'
+        | '\u{1160}'..='\u{11ff}'
+        | '\u{200b}'..='\u{200f}'
+        | '\u{302e}'..='\u{302f}'
+        | '\u{d7b0}'..='\u{d7ff}'
+        | '\u{fe00}'..='\u{fe0f}' => 0,
+        '\u{1100}'..='\u{115f}'
+        | '\u{2e80}'..='\u{303e}'
+        | '\u{3040}'..='\u{a4cf}'
+        | '\u{a960}'..='\u{a97f}'
+        | '\u{ac00}'..='\u{d7a3}'
+        | '\u{f900}'..='\u{faff}'
+        | '\u{fe30}'..='\u{fe4f}'
+        | '\u{ff00}'..='\u{ff60}'
+        | '\u{ffe0}'..='\u{ffe6}'
+        | '\u{1f300}'..='\u{1f64f}'
+        | '\u{1f900}'..='\u{1f9ff}' => 2,
+        _ => 1,
+    }
+}
+
+/// Truncates the given string so that it fits in the given number of
+/// columns, ending it with `ellipsis` if anything was cut off. Only
+/// whole characters are kept, so a syllable is never split.
+fn truncate_to_width<'a>(value: &'a str, width: usize, ellipsis: &str) -> Cow<'a, str> {
+    if value.chars().map(char_width).sum::<usize>() <= width {
+        return Cow::Borrowed(value);
+    }
+    let Some(limit) = width.checked_sub(ellipsis.chars().map(char_width).sum()) else {
+        return Cow::Borrowed("");
+    };
+    let mut result = String::with_capacity(value.len());
+    let mut used = 0;
+    for ch in value.chars() {
+        let ch_width = char_width(ch);
+        if used + ch_width > limit {
+            break;
+        }
+        used += ch_width;
+        result.push(ch);
+    }
+    result.push_str(ellipsis);
+    Cow::Owned(result)
+}
+
+/// Returns the part of the song shown at the given column of a progress
+/// bar of the given width, mirroring how `progress_bar` fills it.
+fn progress_bar_position(
+    column: usize,
+    width: usize,
+    total_duration: Duration,
+) -> Option<Duration> {
+    if column >= width {
+        return None;
+    }
+    Some(total_duration.mul_f64(column as f64 / width as f64))
+}
+
+/// Renders a progress bar of the given width, using partial block
+/// characters for sub-character precision.
+fn progress_bar(fraction: f64, width: usize, glyphs: &Glyphs) -> String {
+    let eighths = (fraction.clamp(0.0, 1.0) * (width * 8) as f64).round() as usize;
+    let full_blocks = eighths / 8;
+    let mut bar = String::with_capacity(width * 3);
+    for _ in 0..full_blocks {
+        bar.push(glyphs.progress_full);
+    }
+    if full_blocks < width {
+        bar.push(glyphs.progress_partial[eighths % 8]);
+        for _ in full_blocks + 1..width {
+            bar.push(' ');
+        }
+    }
+    bar
+}
+
+/// Formats the given duration as minutes and seconds, e.g. "01:30".
+pub fn format_mm_ss(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    format!("{:02}:{:02}", secs / 60, secs % 60)
+}
+
+/// Returns where to resume a session that was at the given position:
+/// there, if it's within the section being practiced, or else the
+/// start of the section.
+fn resume_position(
+    position: Duration,
+    since: Option<Duration>,
+    until: Option<Duration>,
+) -> Duration {
+    let start = since.unwrap_or(Duration::ZERO);
+    if position < start || until.is_some_and(|until| position >= until) {
+        start
+    } else {
+        position
+    }
+}
+
+/// Parses a time in `mm:ss` format, e.g. "1:30".
+fn parse_mm_ss(value: &str) -> Option<Duration> {
+    let (mins, secs) = value.split_once(':')?;
+    if mins.is_empty() || secs.len() != 2 {
+        return None;
+    }
+    let mins: u64 = mins.parse().ok()?;
+    let secs: u64 = secs.parse().ok()?;
+    if secs >= 60 {
+        return None;
+    }
+    Some(Duration::from_secs(mins * 60 + secs))
+}
+
+/// Returns the first line of lyrics to show so that the line at the
+/// given index is as close to the middle of the visible ones as it can
+/// be, without scrolling past either end of the lyrics.
+fn centered_first_line(idx: usize, visible_lines: usize, line_count: usize) -> usize {
+    idx.saturating_sub(visible_lines / 2)
+        .min(line_count.saturating_sub(visible_lines))
+}
+
+/// Returns the index of the line of lyrics being performed at the
+/// given position, or None if it's before the first line.
+fn get_line_idx_at(lyrics: &[(Duration, String)], pos: Duration) -> Option<usize> {
+    lyrics
+        .partition_point(|(line_pos, _)| *line_pos <= pos)
+        .checked_sub(1)
+}
+
+/// Returns when the line at the given index ends, as worked out by
+/// `SimpleLyrics::with_end_times`, or None if no later line starts
+/// after it.
+fn get_line_end(lyrics: &[(Duration, String)], idx: usize) -> Option<Duration> {
+    let lines = SimpleLyrics::from_pairs(
+        lyrics
+            .iter()
+            .map(|(pos, line)| (pos.as_millis() as u64, line.clone())),
+    );
+    let (start, end, _) = lines.with_end_times(Duration::ZERO).into_iter().nth(idx)?;
+    (end > start).then_some(end)
+}
+
+/// Moves the line at the given index, and every line after it, by the
+/// given number of milliseconds. Lines are never moved before the line
+/// preceding them, or the start of the song, so that they stay in
+/// order. Returns how far they were actually moved.
+fn shift_lines_from(lyrics: &mut [(Duration, String)], idx: usize, delta_ms: i64) -> i64 {
+    let Some((start, _)) = lyrics.get(idx) else {
+        return 0;
+    };
+    let min_start = match idx.checked_sub(1) {
+        Some(prev_idx) => lyrics[prev_idx].0,
+        None => Duration::ZERO,
+    };
+    let delta_ms = delta_ms.max(-(start.saturating_sub(min_start).as_millis() as i64));
+    for (pos, _) in &mut lyrics[idx..] {
+        *pos = shift_duration(*pos, delta_ms);
+    }
+    delta_ms
+}
+
+/// Moves the given position by the given number of milliseconds, but
+/// never before the start of the song.
+fn shift_duration(pos: Duration, delta_ms: i64) -> Duration {
+    let delta = Duration::from_millis(delta_ms.unsigned_abs());
+    if delta_ms >= 0 {
+        pos + delta
+    } else {
+        pos.saturating_sub(delta)
+    }
+}
+
+/// Returns the start time of the latest word that began more than
+/// `PREV_WORD_GRACE` before the given position.
+fn get_prev_word_time(word_times: &[Duration], pos: Duration) -> Option<Duration> {
+    let cutoff = pos.saturating_sub(PREV_WORD_GRACE);
+    let idx = word_times.partition_point(|time| *time < cutoff);
+    idx.checked_sub(1).map(|idx| word_times[idx])
+}
+
+/// Returns the start time of the first word that begins after the
+/// given position.
+fn get_next_word_time(word_times: &[Duration], pos: Duration) -> Option<Duration> {
+    let idx = word_times.partition_point(|time| *time <= pos);
+    word_times.get(idx).copied()
+}
+
+/// Returns the start time of every word of the given lyrics, in order,
+/// or nothing if they aren't synced.
+fn get_word_times(lyrics: &Lyrics) -> Vec<Duration> {
+    let Lyrics::SyncedLyrics(synced_lyrics) = lyrics else {
+        return vec![];
     };
+    let mut word_times: Vec<Duration> = synced_lyrics
+        .0
+        .iter()
+        .flat_map(|(_, words)| words.iter())
+        .map(|(millis, _)| Duration::from_millis(*millis))
+        .collect();
+    word_times.sort();
+    word_times.dedup();
+    word_times
+}
+
+fn key(code: KeyCode) -> Event {
+    Event::Key(code.into())
+}
 
-    simple_vec
+fn lyrics_to_vec(lyrics: Lyrics) -> Vec<(Duration, String)> {
+    lyrics
+        .to_simple()
+        .0
         .into_iter()
         .filter_map(|(millis, line)| {
             let trimmed_line = line.trim();
@@ -483,6 +1662,12 @@ fn lyrics_to_vec(lyrics: Lyrics) -> Vec<(Duration, String)> {
         .collect()
 }
 
+fn pronounce_line(line: &str) -> String {
+    compose_all_hangul_jamos(apply_pronunciation_rules_to_jamos(
+        decompose_all_hangul_syllables(line),
+    ))
+}
+
 fn get_title(audio_filename: &PathBuf, lrc_filename: &PathBuf) -> String {
     let audio = audio_filename
         .file_name()
@@ -503,8 +1688,9 @@ fn get_title(audio_filename: &PathBuf, lrc_filename: &PathBuf) -> String {
 
 pub fn play(
     audio_filename: &String,
-    use_alternate_screen: bool,
     lrc_filename: &Option<String>,
+    options: PlayOptions,
+    key_bindings: KeyBindings,
 ) -> Result<()> {
     let audio_filename = Path::new(audio_filename).to_path_buf();
     let lrc_filename = match lrc_filename {
@@ -519,46 +1705,319 @@ pub fn play(
             ));
         }
     }
+    info!("Using LRC file {}", lrc_filename.to_string_lossy());
     let title = get_title(&audio_filename, &lrc_filename);
-    let lyrics = lyrics_to_vec(parse_lrc(read_to_string(lrc_filename)?)?);
+    let session = if options.resume {
+        load_session(&audio_filename)?
+    } else {
+        None
+    };
+    // The section being practiced is picked up from the session too,
+    // unless another one is given.
+    let (since, until) = match &session {
+        Some(session) if options.since.is_none() && options.until.is_none() => {
+            (session.since, session.until)
+        }
+        _ => (options.since, options.until),
+    };
+    let lrc = read_lrc(&lrc_filename, options.lrc_encoding.as_deref())?;
+    let lrc_tags = id_tag_lines(&lrc);
+    // The lyrics are saved back as they were written, normalized or not.
+    let lrc_lyrics = parse_lrc(lrc.clone())?;
+    let lyrics = if options.nfd {
+        parse_lrc(nfd_non_hangul(&lrc))?
+    } else {
+        lrc_lyrics.clone()
+    };
+    let lyrics_duration = lyrics.total_duration();
+    let lyrics = match lyrics.between(since, until)? {
+        // Words timed out of order would make it unclear which one is
+        // being sung.
+        Lyrics::SyncedLyrics(synced_lyrics) if options.repair_timings => {
+            Lyrics::SyncedLyrics(synced_lyrics.repair())
+        }
+        lyrics => lyrics,
+    };
+    let word_times = get_word_times(&lyrics);
+    let lyrics = lyrics_to_vec(lyrics);
     if lyrics.is_empty() {
+        if since.is_some() || until.is_some() {
+            return Err(anyhow!(
+                "LRC file contains no lyrics in the given time range!"
+            ));
+        }
         return Err(anyhow!("LRC file contains no lyrics!"));
     }
-    let (_stream, stream_handle) = OutputStream::try_default()?;
+    info!("Parsed {} lines of lyrics", lyrics.len());
+    if let Some(speed) = options.speed
+        && !(MIN_SPEED..=MAX_SPEED).contains(&speed)
+    {
+        return Err(anyhow!(
+            "Speed must be between {MIN_SPEED} and {MAX_SPEED}, not {speed}"
+        ));
+    }
+    let (_stream, stream_handle) = open_output()?;
     let sink = Sink::try_new(&stream_handle)?;
-    let file = BufReader::new(File::open(audio_filename)?);
+    let file = BufReader::new(File::open(&audio_filename)?);
     let source = Decoder::new(file)?;
-    sink.append(source);
+    info!(
+        "Audio has {} channel(s) at {} Hz, total duration {:?}",
+        source.channels(),
+        source.sample_rate(),
+        source.total_duration()
+    );
+    let total_duration = source.total_duration().or(lyrics_duration);
+    let session =
+        session.map(|session| session.clamp(lyrics.len(), total_duration, MIN_SPEED, MAX_SPEED));
+    let speed = options
+        .speed
+        .or(session.as_ref().map(|session| session.speed))
+        .unwrap_or(1.0);
+    let volume = session.as_ref().map_or(1.0, |session| session.volume);
+    sink.set_volume(volume);
+    let sink: Box<dyn Playback> = if options.preserve_pitch {
+        append_time_stretched(sink, source, speed)?
+    } else {
+        sink.set_speed(speed);
+        sink.append(source);
+        Box::new(sink)
+    };
     sink.pause();
+    let pronounced_lyrics = lyrics
+        .iter()
+        .map(|(_, line)| pronounce_line(line))
+        .collect();
+    let line_shifts = vec![0; lyrics.len()];
+    let glyphs = Glyphs::new(options.ascii);
+    let (columns, rows) = size()?;
     let mut app = App {
         title,
+        highlight_style: options.highlight_style,
+        raw_jamos: options.raw_jamos,
+        select_all_words: options.select_all_words,
+        word_separators: options.word_separators,
         lyrics,
+        line_shifts,
+        lrc_filename,
+        lrc_tags,
+        lrc_lyrics,
+        word_times,
+        pronounced_lyrics,
+        show_pronounced: false,
+        auto_scroll: false,
+        auto_scroll_suspended: false,
+        auto_scroll_line: None,
+        time_entry: None,
+        status_message: None,
+        help_lines: key_bindings.help_lines(REWIND_SECS, &glyphs),
+        glyphs,
+        key_bindings,
+        total_duration,
+        since,
+        until,
+        repeat_count: options.repeat_count,
+        repeat: None,
+        redraw: Redraw::Full,
+        rendered_playback_line: None,
+        columns: columns as usize,
         sink,
-        lyrics_lines_to_show: size()?.1 as usize / 2,
+        lyrics_lines_to_show: rows as usize / 2,
         first_lyrics_line: 0,
         curr_lyrics_line: 0,
         curr_word: 0,
         curr_syllable: 0,
     };
-    if use_alternate_screen {
+    if let Some(session) = session {
+        info!("Resuming session: {session:?}");
+        let position = resume_position(session.position, since, until);
+        if position == session.position {
+            app.curr_lyrics_line = session.line;
+            app.scroll_to_current_line();
+        }
+        let result = app.seek_to(position);
+        app.show_if_error(result);
+    } else if let Some(since) = since {
+        let result = app.seek_to(since);
+        app.sink.pause();
+        app.show_if_error(result);
+    }
+    // Logging to stderr would corrupt the TUI.
+    log_to_file()?;
+    if options.use_alternate_screen {
         execute!(stdout(), EnterAlternateScreen)?;
     }
     execute!(stdout(), Hide, DisableLineWrap)?;
+    if options.mouse {
+        execute!(stdout(), EnableMouseCapture)?;
+    }
     enable_raw_mode()?;
     let result = app.run();
     disable_raw_mode()?;
+    if options.mouse {
+        execute!(stdout(), DisableMouseCapture)?;
+    }
     execute!(stdout(), EnableLineWrap, Show)?;
-    if use_alternate_screen {
+    if options.use_alternate_screen {
         execute!(stdout(), LeaveAlternateScreen)?;
     }
+    log_to_stderr();
+    if options.resume && result.is_ok() {
+        let session = SessionState {
+            line: app.curr_lyrics_line,
+            position: app.sink.get_pos(),
+            speed,
+            volume,
+            since,
+            until,
+        };
+        save_session(&audio_filename, &session)?;
+    }
     result
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crossterm::event::KeyModifiers;
     use std::path::PathBuf;
 
+    #[test]
+    fn test_progress_bar() {
+        let glyphs = Glyphs::UNICODE;
+        assert_eq!(progress_bar(0.0, 4, &glyphs), "    ");
+        assert_eq!(progress_bar(0.5, 4, &glyphs), "██  ");
+        assert_eq!(progress_bar(0.5625, 4, &glyphs), "██▎ ");
+        assert_eq!(progress_bar(1.0, 4, &glyphs), "████");
+        assert_eq!(progress_bar(2.0, 4, &glyphs), "████");
+        assert_eq!(progress_bar(0.5, 0, &glyphs), "");
+    }
+
+    #[test]
+    fn test_ascii_progress_bar() {
+        let glyphs = Glyphs::ASCII;
+        assert_eq!(progress_bar(0.5, 4, &glyphs), "##  ");
+        assert_eq!(progress_bar(0.625, 4, &glyphs), "##= ");
+        assert_eq!(progress_bar(1.0, 4, &glyphs), "####");
+    }
+
+    #[test]
+    fn test_format_mm_ss() {
+        assert_eq!(format_mm_ss(Duration::from_millis(83450)), "01:23");
+        assert_eq!(format_mm_ss(Duration::from_secs(0)), "00:00");
+    }
+
+    #[test]
+    fn test_parse_mm_ss() {
+        assert_eq!(parse_mm_ss("1:30"), Some(Duration::from_secs(90)));
+        assert_eq!(parse_mm_ss("00:05"), Some(Duration::from_secs(5)));
+        assert_eq!(parse_mm_ss("1:3"), None);
+        assert_eq!(parse_mm_ss("1:60"), None);
+        assert_eq!(parse_mm_ss(":30"), None);
+        assert_eq!(parse_mm_ss("130"), None);
+    }
+
+    #[test]
+    fn test_resume_position() {
+        let secs = Duration::from_secs;
+        assert_eq!(resume_position(secs(30), None, None), secs(30));
+        assert_eq!(
+            resume_position(secs(30), Some(secs(20)), Some(secs(40))),
+            secs(30)
+        );
+        assert_eq!(resume_position(secs(10), Some(secs(20)), None), secs(20));
+        assert_eq!(
+            resume_position(secs(40), Some(secs(20)), Some(secs(40))),
+            secs(20)
+        );
+        assert_eq!(resume_position(secs(40), None, Some(secs(40))), secs(0));
+    }
+
+    #[test]
+    fn test_get_line_idx_at() {
+        let lyrics = vec![
+            (Duration::from_secs(10), "First".to_owned()),
+            (Duration::from_secs(20), "Second".to_owned()),
+        ];
+        assert_eq!(get_line_idx_at(&lyrics, Duration::from_secs(5)), None);
+        assert_eq!(get_line_idx_at(&lyrics, Duration::from_secs(10)), Some(0));
+        assert_eq!(get_line_idx_at(&lyrics, Duration::from_secs(15)), Some(0));
+        assert_eq!(get_line_idx_at(&lyrics, Duration::from_secs(90)), Some(1));
+    }
+
+    #[test]
+    fn test_shift_lines_from() {
+        let mut lyrics = vec![
+            (Duration::from_secs(10), "First".to_owned()),
+            (Duration::from_secs(20), "Second".to_owned()),
+            (Duration::from_secs(30), "Third".to_owned()),
+        ];
+        assert_eq!(shift_lines_from(&mut lyrics, 1, 500), 500);
+        let times: Vec<Duration> = lyrics.iter().map(|(pos, _)| *pos).collect();
+        assert_eq!(
+            times,
+            vec![
+                Duration::from_secs(10),
+                Duration::from_millis(20500),
+                Duration::from_millis(30500)
+            ]
+        );
+        // Lines can't be moved before the line preceding them.
+        assert_eq!(shift_lines_from(&mut lyrics, 2, -20_000), -10_000);
+        assert_eq!(lyrics[2].0, Duration::from_millis(20500));
+        // Or before the start of the song.
+        assert_eq!(shift_lines_from(&mut lyrics, 0, -20_000), -10_000);
+        assert_eq!(lyrics[0].0, Duration::ZERO);
+        assert_eq!(shift_lines_from(&mut lyrics, 3, 100), 0);
+    }
+
+    #[test]
+    fn test_shifting_lines_accumulates() {
+        let mut app = test_app(&["하나", "둘", "셋"], false);
+        app.word_times = vec![Duration::from_secs(5), Duration::from_secs(12)];
+        app.curr_lyrics_line = 1;
+        app.handle_action(Action::ShiftLinesLater);
+        app.handle_action(Action::ShiftLinesLater);
+        assert_eq!(app.lyrics[1].0, Duration::from_millis(10200));
+        assert_eq!(app.lyrics[2].0, Duration::from_millis(20200));
+        assert_eq!(
+            app.word_times,
+            vec![Duration::from_secs(5), Duration::from_millis(12200)]
+        );
+        app.curr_lyrics_line = 2;
+        app.handle_action(Action::ShiftLinesEarlier);
+        assert_eq!(app.line_shifts, vec![0, 200, 100]);
+        assert_eq!(
+            app.status_message.as_deref(),
+            Some("Lines 3 on shifted by +100ms")
+        );
+        app.lrc_tags = vec!["[ar:Band]".to_owned()];
+        assert_eq!(
+            app.lrc_contents(),
+            "[ar:Band]\n[00:00.000]하나\n[00:10.200]둘\n[00:20.100]셋\n"
+        );
+    }
+
+    #[test]
+    fn test_lines_sharing_a_timestamp() {
+        let lyrics = vec![
+            (Duration::from_secs(10), "Lead".to_owned()),
+            (Duration::from_secs(10), "Backing".to_owned()),
+            (Duration::from_secs(20), "Next".to_owned()),
+        ];
+        assert_eq!(get_line_idx_at(&lyrics, Duration::from_secs(10)), Some(1));
+        assert_eq!(get_line_idx_at(&lyrics, Duration::from_secs(15)), Some(1));
+        assert_eq!(get_line_end(&lyrics, 0), Some(Duration::from_secs(20)));
+        assert_eq!(get_line_end(&lyrics, 1), Some(Duration::from_secs(20)));
+        assert_eq!(get_line_end(&lyrics, 2), None);
+        assert_eq!(get_line_end(&lyrics, 3), None);
+    }
+
+    #[test]
+    fn test_pronounce_line() {
+        assert_eq!(pronounce_line("밥을 먹어요"), "바블 머거요");
+        assert_eq!(pronounce_line("hello"), "hello");
+    }
+
     #[test]
     fn test_get_title_same_stem() {
         let audio = PathBuf::from("/path/to/song.mp3");
@@ -594,4 +2053,627 @@ mod tests {
         let title = get_title(&audio, &lrc);
         assert_eq!(title, "");
     }
+
+    /// Playback that can't be seeked, like some compressed formats.
+    struct UnseekablePlayback {
+        is_paused: std::cell::Cell<bool>,
+    }
+
+    impl Playback for UnseekablePlayback {
+        fn is_paused(&self) -> bool {
+            self.is_paused.get()
+        }
+
+        fn play(&self) {
+            self.is_paused.set(false);
+        }
+
+        fn pause(&self) {
+            self.is_paused.set(true);
+        }
+
+        fn get_pos(&self) -> Duration {
+            Duration::from_secs(5)
+        }
+
+        fn try_seek(&self, _pos: Duration) -> Result<()> {
+            Err(anyhow!("seeking is not supported"))
+        }
+    }
+
+    #[derive(Default)]
+    struct SeekablePlayback {
+        is_paused: std::cell::Cell<bool>,
+        pos: std::cell::Cell<Duration>,
+    }
+
+    impl Playback for SeekablePlayback {
+        fn is_paused(&self) -> bool {
+            self.is_paused.get()
+        }
+
+        fn play(&self) {
+            self.is_paused.set(false);
+        }
+
+        fn pause(&self) {
+            self.is_paused.set(true);
+        }
+
+        fn get_pos(&self) -> Duration {
+            self.pos.get()
+        }
+
+        fn try_seek(&self, pos: Duration) -> Result<()> {
+            self.pos.set(pos);
+            Ok(())
+        }
+    }
+
+    /// Creates an app with a line of lyrics every ten seconds, and
+    /// playback that can't be seeked.
+    fn test_app(lines: &[&str], select_all_words: bool) -> App {
+        let key_bindings = KeyBindings::default();
+        App {
+            title: "song".to_owned(),
+            highlight_style: HighlightStyle::Underline,
+            raw_jamos: false,
+            select_all_words,
+            word_separators: None,
+            lyrics_lines_to_show: 10,
+            first_lyrics_line: 0,
+            curr_lyrics_line: 0,
+            curr_word: 0,
+            curr_syllable: 0,
+            lyrics: lines
+                .iter()
+                .enumerate()
+                .map(|(idx, line)| (Duration::from_secs(10 * idx as u64), line.to_string()))
+                .collect(),
+            line_shifts: vec![0; lines.len()],
+            lrc_filename: PathBuf::from("song.lrc"),
+            lrc_tags: vec![],
+            lrc_lyrics: Lyrics::SimpleLyrics(SimpleLyrics(
+                lines
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, line)| (10000 * idx as u64, line.to_string()))
+                    .collect(),
+            )),
+            word_times: vec![],
+            pronounced_lyrics: lines.iter().map(|line| pronounce_line(line)).collect(),
+            show_pronounced: false,
+            auto_scroll: false,
+            auto_scroll_suspended: false,
+            auto_scroll_line: None,
+            total_duration: None,
+            since: None,
+            until: None,
+            time_entry: None,
+            status_message: None,
+            help_lines: key_bindings.help_lines(REWIND_SECS, &Glyphs::UNICODE),
+            glyphs: Glyphs::UNICODE,
+            key_bindings,
+            repeat_count: 3,
+            repeat: None,
+            redraw: Redraw::Full,
+            rendered_playback_line: None,
+            columns: 80,
+            sink: Box::new(UnseekablePlayback {
+                is_paused: std::cell::Cell::new(true),
+            }),
+        }
+    }
+
+    #[test]
+    fn test_failed_seek_shows_status_message() {
+        let mut app = test_app(&["안녕"], false);
+        for action in [
+            Action::PlayLine,
+            Action::Rewind,
+            Action::SeekPrevWord,
+            Action::SeekNextWord,
+        ] {
+            app.status_message = None;
+            app.handle_action(action);
+            assert_eq!(
+                app.status_message,
+                Some("Failed to seek: seeking is not supported".to_owned())
+            );
+            assert!(app.sink.is_paused());
+        }
+        // The app keeps responding to actions afterwards.
+        app.handle_action(Action::Pause);
+        assert!(!app.sink.is_paused());
+    }
+
+    #[test]
+    fn test_navigation_skips_non_hangul_words_by_default() {
+        let mut app = test_app(&["나는 BTS 좋아"], false);
+        assert_eq!(app.get_curr_line_word_lengths(), vec![2, 2]);
+        app.handle_action(Action::NextSyllable);
+        app.handle_action(Action::NextSyllable);
+        assert_eq!((app.curr_word, app.curr_syllable), (1, 0));
+        assert_eq!(app.get_selection().unwrap().0.word, "좋아");
+        assert_eq!(app.get_non_hangul_selection(), None);
+    }
+
+    #[test]
+    fn test_navigation_includes_non_hangul_words_when_enabled() {
+        let mut app = test_app(&["나는 BTS 좋아"], true);
+        assert_eq!(app.get_curr_line_word_lengths(), vec![2, 1, 2]);
+        app.handle_action(Action::NextSyllable);
+        app.handle_action(Action::NextSyllable);
+        assert_eq!((app.curr_word, app.curr_syllable), (1, 0));
+        assert!(app.get_selection().is_none());
+        assert_eq!(app.get_non_hangul_selection(), Some("BTS"));
+        app.handle_action(Action::NextSyllable);
+        assert_eq!(app.get_selection().unwrap().0.word, "좋아");
+        app.handle_action(Action::PrevSyllable);
+        assert_eq!(app.get_non_hangul_selection(), Some("BTS"));
+    }
+
+    #[test]
+    fn test_punctuation_is_never_selectable() {
+        let app = test_app(&["안녕, 친구! ... BTS"], true);
+        assert_eq!(app.get_curr_line_word_lengths(), vec![2, 2, 1]);
+        // Clicking it doesn't select it.
+        assert_eq!(app.get_syllable_at("안녕, 친구!", 4), None);
+    }
+
+    #[test]
+    fn test_render_on_tiny_terminal_does_not_panic() {
+        let mut app = test_app(&["안녕 BTS"], true);
+        app.total_duration = Some(Duration::from_secs(60));
+        for (columns, rows) in [(5, 10), (0, 0), (80, 24)] {
+            let mut output = vec![];
+            app.render_to(&mut output, columns, rows).unwrap();
+            assert!(!output.is_empty());
+        }
+        assert_eq!(app.help_columns(5), 1);
+        assert_eq!(app.help_columns(80), 2);
+    }
+
+    #[test]
+    fn test_ascii_selection_info() {
+        let mut app = test_app(&["학교에 가요"], false);
+        app.glyphs = Glyphs::ASCII;
+        let render = |app: &App, columns| {
+            let mut output = vec![];
+            app.render_selection_info(&mut output, columns).unwrap();
+            String::from_utf8(output).unwrap()
+        };
+        let info = render(&app, 80);
+        assert!(info.contains("Selected word: 학교에 -> 학꾜에"), "{info:?}");
+        let info = render(&app, 20);
+        assert!(info.contains("..."), "{info:?}");
+        assert!(!info.contains(['→', '…', '⎯']), "{info:?}");
+    }
+
+    #[test]
+    fn test_get_sound_changes() {
+        assert_eq!(get_sound_changes("학교", 0), vec!["경음화 (reinforcement)"]);
+        assert_eq!(get_sound_changes("학교", 1), vec!["경음화 (reinforcement)"]);
+        assert_eq!(get_sound_changes("한국어", 0), Vec::<&str>::new());
+        assert_eq!(get_sound_changes("한국어", 2), vec!["연음 (liaison)"]);
+    }
+
+    #[test]
+    fn test_word_times_snap_to_boundaries() {
+        let word_times: Vec<Duration> = [1000, 1500, 3000]
+            .into_iter()
+            .map(Duration::from_millis)
+            .collect();
+        let ms = Duration::from_millis;
+        assert_eq!(get_prev_word_time(&word_times, ms(2100)), Some(ms(1500)));
+        // Just after a word starts, go back to the one before it.
+        assert_eq!(get_prev_word_time(&word_times, ms(1600)), Some(ms(1000)));
+        assert_eq!(get_prev_word_time(&word_times, ms(1200)), None);
+        assert_eq!(get_next_word_time(&word_times, ms(1000)), Some(ms(1500)));
+        assert_eq!(get_next_word_time(&word_times, ms(0)), Some(ms(1000)));
+        assert_eq!(get_next_word_time(&word_times, ms(3000)), None);
+        assert_eq!(get_next_word_time(&[], ms(0)), None);
+    }
+
+    #[test]
+    fn test_get_word_times() {
+        let synced = parse_lrc("[00:01.00]<00:01.00>안녕 <00:01.50>하세요".to_owned()).unwrap();
+        assert_eq!(
+            get_word_times(&synced),
+            vec![Duration::from_millis(1000), Duration::from_millis(1500)]
+        );
+        let simple = parse_lrc("[00:01.00]안녕하세요".to_owned()).unwrap();
+        assert_eq!(get_word_times(&simple), vec![]);
+    }
+
+    #[test]
+    fn test_repeat_line_plays_it_repeat_count_times() {
+        let mut app = test_app(&["하나", "둘"], false);
+        app.sink = Box::new(SeekablePlayback::default());
+        app.sink.try_seek(Duration::from_secs(4)).unwrap();
+        app.handle_action(Action::RepeatLine);
+        let now = Instant::now();
+        for iteration in 1..=3 {
+            assert_eq!(app.status_text(), format!("REPEAT {iteration}/3"));
+            assert_eq!(app.sink.get_pos(), Duration::ZERO);
+            assert!(!app.sink.is_paused());
+            // Play through to the start of the next line.
+            app.sink.try_seek(Duration::from_secs(10)).unwrap();
+            app.update_repeat(now).unwrap();
+            assert!(app.sink.is_paused());
+            app.update_repeat(now).unwrap();
+            assert!(app.sink.is_paused());
+            app.update_repeat(now + REPEAT_GAP).unwrap();
+        }
+        assert!(app.repeat.is_none());
+        assert!(app.sink.is_paused());
+        assert_eq!(app.status_text(), "song");
+    }
+
+    #[test]
+    fn test_pause_cancels_repeat() {
+        let mut app = test_app(&["하나", "둘"], false);
+        app.sink = Box::new(SeekablePlayback::default());
+        app.handle_action(Action::RepeatLine);
+        assert!(app.repeat.is_some());
+        app.handle_action(Action::Pause);
+        assert!(app.repeat.is_none());
+        assert!(app.sink.is_paused());
+    }
+
+    #[test]
+    fn test_repeat_line_sharing_a_timestamp() {
+        let mut app = test_app(&["하나", "둘", "셋"], false);
+        app.lyrics[1].0 = Duration::ZERO;
+        app.sink = Box::new(SeekablePlayback::default());
+        app.handle_action(Action::RepeatLine);
+        assert_eq!(
+            app.repeat.as_ref().map(|repeat| repeat.end),
+            Some(Duration::from_secs(20))
+        );
+    }
+
+    #[test]
+    fn test_playback_loops_within_section() {
+        let mut app = test_app(&["하나", "둘"], false);
+        app.sink = Box::new(SeekablePlayback::default());
+        app.since = Some(Duration::from_secs(5));
+        app.until = Some(Duration::from_secs(15));
+        app.sink.try_seek(Duration::from_secs(14)).unwrap();
+        app.update_section().unwrap();
+        assert_eq!(app.sink.get_pos(), Duration::from_secs(14));
+        app.sink.try_seek(Duration::from_secs(15)).unwrap();
+        app.update_section().unwrap();
+        assert_eq!(app.sink.get_pos(), Duration::from_secs(5));
+        assert!(!app.sink.is_paused());
+
+        // Repeating the section's last line stops at the section's end.
+        app.curr_lyrics_line = 1;
+        app.handle_action(Action::RepeatLine);
+        assert_eq!(
+            app.repeat.as_ref().map(|repeat| repeat.end),
+            Some(Duration::from_secs(15))
+        );
+    }
+
+    #[test]
+    fn test_repeat_last_line_needs_known_duration() {
+        let mut app = test_app(&["하나"], false);
+        app.sink = Box::new(SeekablePlayback::default());
+        app.handle_action(Action::RepeatLine);
+        assert!(app.repeat.is_none());
+        assert!(app.status_message.is_some());
+        app.total_duration = Some(Duration::from_secs(30));
+        app.handle_action(Action::RepeatLine);
+        assert_eq!(
+            app.repeat.as_ref().map(|repeat| repeat.end),
+            Some(Duration::from_secs(30))
+        );
+    }
+
+    #[test]
+    fn test_truncate_to_width() {
+        let line = "Selected word: 안녕하세요 (annyeonghaseyo)";
+        assert_eq!(truncate_to_width(line, 20, "…"), "Selected word: 안녕…");
+        // There's no room for half of 하, so it's left out entirely.
+        assert_eq!(truncate_to_width(line, 21, "…"), "Selected word: 안녕…");
+        assert_eq!(truncate_to_width(line, 22, "…"), "Selected word: 안녕하…");
+        assert_eq!(truncate_to_width(line, 100, "…"), line);
+        // Conjoining jamos stay together with their initial.
+        assert_eq!(
+            truncate_to_width("ab\u{1112}\u{1161}\u{11ab}cd", 5, "…"),
+            "ab\u{1112}\u{1161}\u{11ab}…"
+        );
+        assert_eq!(truncate_to_width("안녕", 1, "…"), "…");
+        assert_eq!(truncate_to_width("안녕", 0, "…"), "");
+        assert_eq!(truncate_to_width(line, 20, "..."), "Selected word: 안...");
+        assert_eq!(truncate_to_width("안녕", 2, "..."), "");
+    }
+
+    #[test]
+    fn test_split_into_words() {
+        use HangulCharClass::{None as Other, Syllables};
+        let line = "안녕,반가워 친구-야 (hi)";
+        assert_eq!(
+            split_into_words(line, None),
+            vec![
+                (Syllables, "안녕"),
+                (Other, ","),
+                (Syllables, "반가워"),
+                (Other, " "),
+                (Syllables, "친구"),
+                (Other, "-"),
+                (Syllables, "야"),
+                (Other, " (hi)"),
+            ]
+        );
+        assert_eq!(
+            split_into_words(line, Some("-")),
+            vec![
+                (Syllables, "안녕,반가워"),
+                (Other, " "),
+                (Syllables, "친구"),
+                (Other, "-"),
+                (Syllables, "야"),
+                (Other, " (hi)"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_navigation_across_punctuation() {
+        let mut app = test_app(&["안녕,반가워 친구"], false);
+        assert_eq!(app.get_curr_line_word_lengths(), vec![2, 3, 2]);
+
+        app.word_separators = Some(String::new());
+        assert_eq!(app.get_curr_line_word_lengths(), vec![5, 2]);
+        for _ in 0..2 {
+            app.handle_action(Action::NextSyllable);
+        }
+        assert_eq!((app.curr_word, app.curr_syllable), (0, 2));
+        let (selection, _) = app.get_selection().unwrap();
+        assert_eq!(selection.word, "안녕,반가워");
+        assert_eq!(selection.syllable_str(), "반");
+        for _ in 0..3 {
+            app.handle_action(Action::NextSyllable);
+        }
+        assert_eq!((app.curr_word, app.curr_syllable), (1, 0));
+        assert_eq!(app.get_selection().unwrap().0.word, "친구");
+        app.handle_action(Action::PrevSyllable);
+        assert_eq!((app.curr_word, app.curr_syllable), (0, 4));
+        assert_eq!(app.get_selection().unwrap().0.syllable_str(), "워");
+    }
+
+    fn click(app: &mut App, column: u16, row: u16) {
+        app.handle_mouse_event(MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            column,
+            row,
+            modifiers: KeyModifiers::NONE,
+        });
+    }
+
+    #[test]
+    fn test_get_syllable_at() {
+        let app = test_app(&[], false);
+        let line = "안녕 hi 친구!";
+        let syllables: Vec<_> = (0..14).map(|col| app.get_syllable_at(line, col)).collect();
+        assert_eq!(
+            syllables,
+            vec![
+                Some((0, 0)),
+                Some((0, 0)),
+                Some((0, 1)),
+                Some((0, 1)),
+                None,
+                None,
+                None,
+                None,
+                Some((1, 0)),
+                Some((1, 0)),
+                Some((1, 1)),
+                Some((1, 1)),
+                None,
+                None,
+            ]
+        );
+        let app = test_app(&[], true);
+        assert_eq!(app.get_syllable_at(line, 6), Some((1, 0)));
+        assert_eq!(app.get_syllable_at(line, 8), Some((2, 0)));
+    }
+
+    #[test]
+    fn test_clicking_selects_syllable() {
+        let mut app = test_app(&["안녕", "반가워, 친구"], false);
+        // The status bar is the first row, and the "> " prefix takes up
+        // the first two columns.
+        click(&mut app, 10, 2);
+        assert_eq!(
+            (app.curr_lyrics_line, app.curr_word, app.curr_syllable),
+            (1, 1, 0)
+        );
+        click(&mut app, 5, 2);
+        assert_eq!(
+            (app.curr_lyrics_line, app.curr_word, app.curr_syllable),
+            (1, 0, 1)
+        );
+
+        // Clicking anything but a syllable does nothing.
+        for (column, row) in [(8, 2), (0, 1), (3, 0), (2, 3), (30, 1)] {
+            click(&mut app, column, row);
+            assert_eq!(
+                (app.curr_lyrics_line, app.curr_word, app.curr_syllable),
+                (1, 0, 1)
+            );
+        }
+
+        app.show_pronounced = true;
+        click(&mut app, 2, 2);
+        assert_eq!(app.curr_lyrics_line, 1);
+        click(&mut app, 2, 3);
+        assert_eq!(
+            (app.curr_lyrics_line, app.curr_word, app.curr_syllable),
+            (1, 0, 0)
+        );
+    }
+
+    #[test]
+    fn test_centered_first_line() {
+        // Near the start, the lyrics can't scroll up any further.
+        assert_eq!(centered_first_line(2, 10, 50), 0);
+        assert_eq!(centered_first_line(20, 10, 50), 15);
+        assert_eq!(centered_first_line(20, 9, 50), 16);
+        // Near the end, they can't scroll down any further.
+        assert_eq!(centered_first_line(48, 10, 50), 40);
+        // Everything fits.
+        assert_eq!(centered_first_line(3, 10, 5), 0);
+    }
+
+    #[test]
+    fn test_auto_scroll() {
+        let lines: Vec<String> = (0..30).map(|idx| format!("{idx}번")).collect();
+        let lines: Vec<&str> = lines.iter().map(|line| line.as_str()).collect();
+        let mut app = test_app(&lines, false);
+        app.sink = Box::new(SeekablePlayback::default());
+        app.sink.try_seek(Duration::from_secs(125)).unwrap();
+
+        // It's off by default.
+        app.update_auto_scroll();
+        assert_eq!(app.first_lyrics_line, 0);
+
+        app.handle_action(Action::ToggleAutoScroll);
+        app.update_auto_scroll();
+        assert_eq!(app.first_lyrics_line, 7);
+        assert_eq!(app.curr_lyrics_line, 0);
+
+        // Moving around manually stops it until the next line plays.
+        for _ in 0..3 {
+            app.handle_action(Action::NextLine);
+        }
+        app.first_lyrics_line = 0;
+        app.update_auto_scroll();
+        assert_eq!(app.first_lyrics_line, 0);
+        app.sink.try_seek(Duration::from_secs(130)).unwrap();
+        app.update_auto_scroll();
+        assert_eq!(app.first_lyrics_line, 8);
+        assert_eq!(app.curr_lyrics_line, 3);
+
+        app.handle_action(Action::ToggleAutoScroll);
+        app.sink.try_seek(Duration::from_secs(200)).unwrap();
+        app.update_auto_scroll();
+        assert_eq!(app.first_lyrics_line, 8);
+    }
+
+    #[test]
+    fn test_only_lines_whose_playback_icon_changed_are_dirty() {
+        let mut app = test_app(&["하나", "둘", "셋"], false);
+        app.lyrics = vec![
+            (Duration::from_secs(0), "하나".to_owned()),
+            (Duration::from_secs(10), "둘".to_owned()),
+            (Duration::from_secs(20), "셋".to_owned()),
+        ];
+        app.render_dirty_to(&mut vec![], 80, 24).unwrap();
+        assert_eq!(app.redraw, Redraw::Playback);
+        assert_eq!(app.rendered_playback_line, Some((0, app.glyphs.paused)));
+        assert_eq!(app.dirty_lyrics_lines(), Vec::<usize>::new());
+
+        // A poll tick where nothing changed only redraws the status bar.
+        let mut full = vec![];
+        app.render_to(&mut full, 80, 24).unwrap();
+        let mut partial = vec![];
+        app.render_dirty_to(&mut partial, 80, 24).unwrap();
+        assert!(partial.len() < full.len());
+
+        // Unpausing changes the icon on the playing line.
+        app.sink.play();
+        assert_eq!(app.dirty_lyrics_lines(), vec![0]);
+        app.render_dirty_to(&mut vec![], 80, 24).unwrap();
+        assert_eq!(app.dirty_lyrics_lines(), Vec::<usize>::new());
+
+        // Lines that aren't visible don't need redrawing.
+        app.rendered_playback_line = Some((2, app.glyphs.playing));
+        app.lyrics_lines_to_show = 2;
+        assert_eq!(app.dirty_lyrics_lines(), vec![0]);
+    }
+
+    #[test]
+    fn test_selected_syllable_matches_highlighted_one() {
+        let mut app = test_app(&["가ᄀ나 다"], false);
+        app.word_separators = Some(" ".to_owned());
+        app.curr_syllable = 1;
+        let mut output = vec![];
+        app.render_lyrics(&mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        let is_highlighted = |syllable: &str| {
+            let styled = app.highlight_style.style_syllable(syllable, true);
+            output.contains(&styled.to_string())
+        };
+        assert!(is_highlighted("나"));
+        assert!(!is_highlighted("가"));
+        let (selection, _) = app.get_selection().unwrap();
+        assert_eq!(selection.syllable, '나');
+        assert_eq!(selection.initial_jamo.curr, 'ᄂ');
+        assert_eq!(app.get_syllable_at("가ᄀ나 다", 2), None);
+        assert_eq!(app.get_syllable_at("가ᄀ나 다", 4), Some((0, 1)));
+    }
+
+    #[test]
+    fn test_progress_bar_position() {
+        let total = Duration::from_secs(200);
+        assert_eq!(progress_bar_position(0, 100, total), Some(Duration::ZERO));
+        assert_eq!(
+            progress_bar_position(50, 100, total),
+            Some(Duration::from_secs(100))
+        );
+        assert_eq!(
+            progress_bar_position(99, 100, total),
+            Some(Duration::from_secs(198))
+        );
+        assert_eq!(progress_bar_position(100, 100, total), None);
+        assert_eq!(progress_bar_position(0, 0, total), None);
+    }
+
+    #[test]
+    fn test_clicking_progress_bar_seeks() {
+        let mut app = test_app(&["안녕"], false);
+        app.sink = Box::new(SeekablePlayback::default());
+        app.total_duration = Some(Duration::from_secs(100));
+        // The bar is followed by " 00:00 / 01:40", leaving 50 columns.
+        app.columns = 64;
+        assert_eq!(app.progress_bar_width(app.columns), Some(50));
+        click(&mut app, 25, PROGRESS_BAR_ROW);
+        assert_eq!(app.sink.get_pos(), Duration::from_secs(50));
+        assert!(!app.sink.is_paused());
+        // Clicking on the times after the bar does nothing.
+        click(&mut app, 55, PROGRESS_BAR_ROW);
+        assert_eq!(app.sink.get_pos(), Duration::from_secs(50));
+    }
+
+    #[test]
+    fn test_poll_timeout_waits_until_next_line_or_word() {
+        let mut app = test_app(&["안녕", "친구"], false);
+        app.sink = Box::new(SeekablePlayback::default());
+        assert_eq!(app.poll_timeout(), POLL_INTERVAL);
+        app.seek_to(Duration::from_millis(9970)).unwrap();
+        assert_eq!(app.poll_timeout(), Duration::from_millis(30));
+        app.word_times = vec![Duration::from_millis(9990)];
+        assert_eq!(app.poll_timeout(), Duration::from_millis(20));
+        // There's nothing after the last line.
+        app.seek_to(Duration::from_secs(15)).unwrap();
+        assert_eq!(app.poll_timeout(), POLL_INTERVAL);
+    }
+
+    #[test]
+    fn test_clicking_progress_bar_row_without_duration_selects_syllable() {
+        // Without a total duration there's no progress bar, so its row
+        // shows the first line of lyrics.
+        let mut app = test_app(&["안녕", "친구"], false);
+        app.curr_lyrics_line = 1;
+        assert_eq!(app.total_duration, None);
+        click(&mut app, 4, PROGRESS_BAR_ROW);
+        assert_eq!(
+            (app.curr_lyrics_line, app.curr_word, app.curr_syllable),
+            (0, 0, 1)
+        );
+    }
 }
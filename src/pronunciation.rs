@@ -1,5 +1,11 @@
+use clap::ValueEnum;
+use std::collections::VecDeque;
+
 use crate::{
-    hangul::{compose_hangul_jamos_to_syllable, decompose_hangul_syllable_to_jamos},
+    hangul::{
+        compose_hangul_jamos_to_syllable, decompose_hangul_syllable_to_jamos, is_silent_initial,
+        to_conjoining_jamo, to_syllables,
+    },
     jamo_stream::{JamoInStream, JamoStream, ModernJamo},
 };
 use ModernJamo::*;
@@ -12,9 +18,9 @@ use ModernJamo::*;
 /// Master" by Talk to Me in Korean.
 pub fn get_jamo_pronunciation(jamo: &JamoInStream) -> &'static str {
     match jamo.curr {
-        // Initial, some finals
+        // Initial
         'ᄀ' => "'g' as in 'go', not as in 'giraffe'",
-        'ᄁ' | 'ᆩ' => "'ch' after 's', as in 'school'",
+        'ᄁ' => "'ch' after 's', as in 'school'",
         'ᄂ' => "'n' as in 'no', with tongue touching back of teeth",
         'ᄃ' => "'d' as in 'study' or first 't' in start, tongue on back of teeth",
         'ᄄ' => "'t' after 's', as in 'steal'",
@@ -23,7 +29,7 @@ pub fn get_jamo_pronunciation(jamo: &JamoInStream) -> &'static str {
         'ᄇ' => "'b' as in 'busy', same mouth shape as ㅁ",
         'ᄈ' => "'p' after 's', as in 'speech'",
         'ᄉ' => "'s' as in 'slow' or 'sh' as in 'sheep'",
-        'ᄊ' | 'ᆻ' => "'s' as in 'sit'",
+        'ᄊ' => "'s' as in 'sit'",
         'ᄋ' => "silent",
         'ᄌ' => "'j' as in 'Jill'",
         'ᄍ' => "tighten throat while pronouncing ㅈ",
@@ -53,25 +59,64 @@ pub fn get_jamo_pronunciation(jamo: &JamoInStream) -> &'static str {
         'ᅱ' => "'we' as in 'we' or 'weekend'",
         'ᅲ' => "'u' as in 'USA', like ㅣ+ㅜ",
         'ᅳ' => "'uh' with upper/lower teeth close and yucky face",
-        'ᅴ' => "ㅣ with any consonant except ㅇ, otherwise ㅡ then ㅣ",
+        'ᅴ' => match jamo.prev {
+            Some(prev) if ModernJamo::is_initial_consonant(prev) && !is_silent_initial(prev) => {
+                "'ee' as in 'feet', like ㅣ, since it follows a consonant"
+            }
+            _ => "ㅡ then ㅣ starting a word, ㅣ later in a word, ㅔ as possessive particle",
+        },
         'ᅵ' => "'ee' as in 'feet'",
 
-        // Final (bat-chim)
-        'ᆨ' | 'ᆿ' => "no sound, stop air like 'doc' in 'doctor'",
+        // Final (bat-chim). Tense and aspirated consonants aren't
+        // distinguished here: they're all unreleased stops.
+        'ᆨ' | 'ᆩ' | 'ᆿ' => "no sound, stop air like 'doc' in 'doctor'",
         'ᆫ' => "'n' as in 'can'",
-        'ᆮ' | 'ᆺ' | 'ᆽ' | 'ᆾ' | 'ᇀ' | 'ᇂ' => "'t' as in 'cat', no puff of air at all",
+        'ᆮ' | 'ᆺ' | 'ᆻ' | 'ᆽ' | 'ᆾ' | 'ᇀ' | 'ᇂ' => {
+            "'t' as in 'cat', no puff of air at all"
+        }
         'ᆯ' => "'l' with tongue touching roof of mouth",
         'ᆷ' => "'m' as in 'beam'",
-        'ᆸ' | 'ᇁ' => "'p' as in 'cap'",
+        'ᆸ' | 'ᇁ' => "'p' as in 'cap', lips closed, no puff of air at all",
         'ᆼ' => "'ng' as in 'ring'",
 
         _ => "",
     }
 }
 
+/// Replaces vowels that are indistinct in modern pronunciation
+/// with a single representative one, e.g. ㅐ with ㅔ, so that
+/// words can be compared by how they sound.
+///
+/// The given string should consist of Hangul jamos.
+pub fn fold_confusable_vowels<T: AsRef<str>>(value: T) -> String {
+    value
+        .as_ref()
+        .chars()
+        .map(|ch| match ch {
+            'ᅢ' => 'ᅦ',
+            'ᅤ' => 'ᅨ',
+            'ᅫ' | 'ᅬ' => 'ᅰ',
+            _ => ch,
+        })
+        .collect()
+}
+
+/// Like `fold_confusable_vowels`, but works on any string, including
+/// one made of Hangul syllables, and returns syllables.
+pub fn fold_confusable_vowels_in_syllables<T: AsRef<str>>(value: T) -> String {
+    to_syllables(fold_confusable_vowels(to_conjoining_jamo(value)))
+}
+
+/// Character that can be used to explicitly mark a boundary between
+/// morphemes, e.g. in compound words like "담+요". It is removed from the
+/// output of `apply_pronunciation_rules_to_jamos`.
+const MORPHEME_BOUNDARY: char = '+';
+
 struct RuleContext {
     /// The final consonant of one syllable.
     final_consonant: ModernJamo,
+    /// Whether the syllable is followed by an explicit morpheme boundary.
+    is_morpheme_boundary: bool,
     /// The initial consonant of the next syllable.
     next_initial_consonant: Option<ModernJamo>,
     /// The next syllable.
@@ -115,12 +160,22 @@ type PronunciationRule = fn(&RuleContext) -> RuleResult;
 /// Reinforcement/intensification rule as described here:
 ///
 /// https://www.missellykorean.com/korean-sound-change-rules-pdf/
+///
+/// The finals that trigger it are the ones pronounced as the stops
+/// [ㄱ], [ㄷ] or [ㅂ], as in article 23 of the Standard Pronunciation
+/// rules. Compound finals have already been simplified by the time
+/// this rule runs.
 fn reinforcement_rule(ctx: &RuleContext) -> RuleResult {
     match ctx.consonants() {
         (
-            // Note: it's very confusing which final consonants work
-            // here, the instructions have conflicting information.
-            FinalConsonant('ᆸ' | 'ᆨ' | 'ᆿ' | 'ᆮ' | 'ᆺ' | 'ᆽ' | 'ᆾ' | 'ᇀ'),
+            FinalConsonant(
+                // Pronounced [ㄱ]
+                'ᆨ' | 'ᆩ' | 'ᆿ'
+                // Pronounced [ㄷ]
+                | 'ᆮ' | 'ᆺ' | 'ᆻ' | 'ᆽ' | 'ᆾ' | 'ᇀ'
+                // Pronounced [ㅂ]
+                | 'ᆸ' | 'ᇁ',
+            ),
             Some(InitialConsonant(initial)),
         ) => {
             let strengthened = match initial {
@@ -144,9 +199,21 @@ fn reinforcement_rule(ctx: &RuleContext) -> RuleResult {
 }
 
 /// Nasalization rules are defined in Talk To Me in Korean's
-/// "Hangul Master" pg. 63.
+/// "Hangul Master" pg. 63. ㄹ also becomes ㄴ after ㄱ and ㅂ, which
+/// are then nasalized too, e.g. 독립 is pronounced 동닙, and ㄴ becomes
+/// ㄹ next to ㄹ, e.g. 설날 is pronounced 설랄 (article 19 and 20 of the
+/// Standard Pronunciation rules).
 fn nasalization_rule(ctx: &RuleContext) -> RuleResult {
     match ctx.consonants() {
+        (FinalConsonant('ᆨ' | 'ᆩ' | 'ᆿ'), Some(InitialConsonant('ᄅ'))) => {
+            RuleResult::ChangeBoth(FinalConsonant('ᆼ'), InitialConsonant('ᄂ'))
+        }
+        (FinalConsonant('ᆸ' | 'ᇁ'), Some(InitialConsonant('ᄅ'))) => {
+            RuleResult::ChangeBoth(FinalConsonant('ᆷ'), InitialConsonant('ᄂ'))
+        }
+        (FinalConsonant('ᆯ'), Some(InitialConsonant('ᄂ'))) => {
+            RuleResult::ChangeNextInitial(InitialConsonant('ᄅ'))
+        }
         (FinalConsonant('ᆨ' | 'ᆩ' | 'ᆿ'), Some(InitialConsonant('ᄂ'))) => {
             RuleResult::ChangeFinal(FinalConsonant('ᆼ'))
         }
@@ -174,7 +241,7 @@ fn nasalization_rule(ctx: &RuleContext) -> RuleResult {
 /// https://www.missellykorean.com/korean-sound-change-rules-pdf/
 fn resyllabification_rule(ctx: &RuleContext) -> RuleResult {
     match ctx.consonants() {
-        (FinalConsonant(ch), Some(InitialConsonant('ᄋ'))) => {
+        (FinalConsonant(ch), Some(InitialConsonant(next))) if is_silent_initial(next) => {
             let new_initial = match ch {
                 'ᆨ' => 'ᄀ',
                 'ᆩ' => 'ᄁ',
@@ -215,6 +282,17 @@ fn ttmik_resyllabification_rule(ctx: &RuleContext) -> RuleResult {
         (FinalConsonant('ᇂ'), Some(InitialConsonant('ᄌ'))) => {
             RuleResult::RemoveFinalAndChangeNextInitial(InitialConsonant('ᄎ'))
         }
+        // ㅎ aspirates a ㄱ, ㅂ or ㅈ before it too, e.g. 잡혀 is
+        // pronounced 자펴.
+        (FinalConsonant('ᆨ'), Some(InitialConsonant('ᄒ'))) => {
+            RuleResult::RemoveFinalAndChangeNextInitial(InitialConsonant('ᄏ'))
+        }
+        (FinalConsonant('ᆸ'), Some(InitialConsonant('ᄒ'))) => {
+            RuleResult::RemoveFinalAndChangeNextInitial(InitialConsonant('ᄑ'))
+        }
+        (FinalConsonant('ᆽ'), Some(InitialConsonant('ᄒ'))) => {
+            RuleResult::RemoveFinalAndChangeNextInitial(InitialConsonant('ᄎ'))
+        }
         _ => match (ctx.final_consonant, ctx.next_syllable) {
             (FinalConsonant('ᆮ'), Some('이')) => {
                 RuleResult::RemoveFinalAndChangeNextInitial(InitialConsonant('ᄌ'))
@@ -222,11 +300,42 @@ fn ttmik_resyllabification_rule(ctx: &RuleContext) -> RuleResult {
             (FinalConsonant('ᇀ'), Some('이')) | (FinalConsonant('ᆮ'), Some('히')) => {
                 RuleResult::RemoveFinalAndChangeNextInitial(InitialConsonant('ᄎ'))
             }
+            (FinalConsonant('ᆮ' | 'ᆺ'), _)
+                if ctx.next_initial_consonant == Some(InitialConsonant('ᄒ')) =>
+            {
+                RuleResult::RemoveFinalAndChangeNextInitial(InitialConsonant('ᄐ'))
+            }
             _ => RuleResult::NoChange,
         },
     }
 }
 
+/// ㄴ-insertion (ㄴ첨가) rule.
+///
+/// In compound words, a ㄴ is inserted before 이/야/여/요/유 when the
+/// preceding morpheme ends in a consonant, e.g. "담요" is pronounced
+/// "담뇨". Whether this applies depends on morphology, which we can't
+/// infer, so the rule only fires at explicit morpheme boundaries, e.g.
+/// "담+요".
+fn n_insertion_rule(ctx: &RuleContext) -> RuleResult {
+    if !ctx.is_morpheme_boundary {
+        return RuleResult::NoChange;
+    }
+    let Some(next_syllable) = ctx.next_syllable else {
+        return RuleResult::NoChange;
+    };
+    match (
+        ctx.consonants(),
+        decompose_hangul_syllable_to_jamos(next_syllable),
+    ) {
+        (
+            (FinalConsonant(_), Some(InitialConsonant('ᄋ'))),
+            Some((_, 'ᅵ' | 'ᅣ' | 'ᅧ' | 'ᅭ' | 'ᅲ', _)),
+        ) => RuleResult::ChangeNextInitial(InitialConsonant('ᄂ')),
+        _ => RuleResult::NoChange,
+    }
+}
+
 /// Compound consonant rules are defined in Talk To Me in Korean's
 /// "Hangul Master" pg. 57-59.
 fn compound_consonant_rule(ctx: &RuleContext) -> RuleResult {
@@ -242,6 +351,20 @@ fn compound_consonant_rule(ctx: &RuleContext) -> RuleResult {
         (FinalConsonant('ᆬ'), Some(InitialConsonant('ᄋ'))) => {
             (FinalConsonant('ᆫ'), Some(InitialConsonant('ᄌ')))
         }
+        // The reinforcement rule doesn't match on ᆫ, so like ㄺ followed
+        // by ㄱ, we reinforce the next initial here manually.
+        (FinalConsonant('ᆬ'), Some(InitialConsonant('ᄀ'))) => {
+            (FinalConsonant('ᆫ'), Some(InitialConsonant('ᄁ')))
+        }
+        (FinalConsonant('ᆬ'), Some(InitialConsonant('ᄃ'))) => {
+            (FinalConsonant('ᆫ'), Some(InitialConsonant('ᄄ')))
+        }
+        (FinalConsonant('ᆬ'), Some(InitialConsonant('ᄉ'))) => {
+            (FinalConsonant('ᆫ'), Some(InitialConsonant('ᄊ')))
+        }
+        (FinalConsonant('ᆬ'), Some(InitialConsonant('ᄌ'))) => {
+            (FinalConsonant('ᆫ'), Some(InitialConsonant('ᄍ')))
+        }
         (FinalConsonant('ᆬ'), _) => (FinalConsonant('ᆫ'), orig_next_initial),
 
         // Rules for ㄶ
@@ -335,43 +458,237 @@ fn compound_consonant_rule(ctx: &RuleContext) -> RuleResult {
     }
 }
 
+/// Identifies a pronunciation rule, so that rules can be selectively
+/// applied.
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum RuleKind {
+    CompoundConsonant,
+    NInsertion,
+    TtmikResyllabification,
+    Resyllabification,
+    Reinforcement,
+    Nasalization,
+}
+
+impl RuleKind {
+    fn rule(&self) -> PronunciationRule {
+        match self {
+            RuleKind::CompoundConsonant => compound_consonant_rule,
+            RuleKind::NInsertion => n_insertion_rule,
+            RuleKind::TtmikResyllabification => ttmik_resyllabification_rule,
+            RuleKind::Resyllabification => resyllabification_rule,
+            RuleKind::Reinforcement => reinforcement_rule,
+            RuleKind::Nasalization => nasalization_rule,
+        }
+    }
+
+    /// The name of the sound change that the rule describes, in Korean
+    /// with an English gloss, e.g. "경음화 (reinforcement)".
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            RuleKind::CompoundConsonant => "겹받침 (compound final)",
+            RuleKind::NInsertion => "ㄴ 첨가 (ㄴ insertion)",
+            RuleKind::TtmikResyllabification => "격음화/구개음화 (aspiration/palatalization)",
+            RuleKind::Resyllabification => "연음 (liaison)",
+            RuleKind::Reinforcement => "경음화 (reinforcement)",
+            RuleKind::Nasalization => "비음화 (nasalization)",
+        }
+    }
+
+    /// A word that the rule changes the pronunciation of, along with
+    /// how it's pronounced once just this rule has been applied.
+    pub fn example(&self) -> (&'static str, &'static str) {
+        match self {
+            RuleKind::CompoundConsonant => ("앉다", "안따"),
+            RuleKind::NInsertion => ("담+요", "담뇨"),
+            RuleKind::TtmikResyllabification => ("좋고", "조코"),
+            RuleKind::Resyllabification => ("한국어", "한구거"),
+            RuleKind::Reinforcement => ("학교", "학꾜"),
+            RuleKind::Nasalization => ("국물", "궁물"),
+        }
+    }
+}
+
+/// A pronunciation rule that changed the sound at a syllable boundary.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RuleApplication {
+    pub rule: RuleKind,
+    /// The rule's display name, as returned by `RuleKind::display_name`.
+    pub name: &'static str,
+    /// The index of the syllable whose final consonant is at the
+    /// boundary, counting from zero.
+    pub syllable_index: usize,
+}
+
 /// All pronunciation rules required for Hangul, in the order that they
 /// should be applied.
-const PRONUNCIATION_RULES: [PronunciationRule; 5] = [
-    compound_consonant_rule,
-    ttmik_resyllabification_rule,
-    resyllabification_rule,
-    reinforcement_rule,
-    nasalization_rule,
+pub const PRONUNCIATION_RULES: [RuleKind; 6] = [
+    RuleKind::CompoundConsonant,
+    RuleKind::NInsertion,
+    RuleKind::TtmikResyllabification,
+    RuleKind::Resyllabification,
+    RuleKind::Reinforcement,
+    RuleKind::Nasalization,
 ];
 
+/// Applies all pronunciation rules to the given Hangul jamos.
+///
+/// Morpheme boundaries can optionally be marked with `+`, e.g. "담+요",
+/// for rules that depend on them; these markers are removed.
 pub fn apply_pronunciation_rules_to_jamos<T: AsRef<str>>(value: T) -> String {
+    pronounce_iter(value).collect()
+}
+
+/// Like `apply_pronunciation_rules_to_jamos`, but only applies the given
+/// rules. They are always applied in the order of `PRONUNCIATION_RULES`,
+/// regardless of the order that they're given in.
+pub fn apply_pronunciation_rules_with<T: AsRef<str>>(value: T, rules: &[RuleKind]) -> String {
     let mut result = String::with_capacity(value.as_ref().len());
-    let mut skip_next_initial_consonant = false;
-    for jamo in JamoStream::from_jamos(value) {
+    result.extend(pronounce_iter_with(value, rules));
+    result
+}
+
+/// Like `apply_pronunciation_rules_to_jamos`, but yields the pronounced
+/// jamos lazily, so that the whole output doesn't need to be held in
+/// memory at once. (The input still is, since `JamoStream` collects it.)
+pub fn pronounce_iter<T: AsRef<str>>(value: T) -> impl Iterator<Item = char> {
+    pronounce_iter_with(value, &PRONUNCIATION_RULES)
+}
+
+/// Like `pronounce_iter`, but only applies the given rules, as with
+/// `apply_pronunciation_rules_with`.
+pub fn pronounce_iter_with<T: AsRef<str>>(
+    value: T,
+    rules: &[RuleKind],
+) -> impl Iterator<Item = char> + use<T> {
+    PronounceIter {
+        jamos: JamoStream::from_jamos(value),
+        rules: PRONUNCIATION_RULES
+            .iter()
+            .filter(|kind| rules.contains(kind))
+            .copied()
+            .collect(),
+        skip_next_initial_consonant: false,
+        pending: VecDeque::with_capacity(2),
+        syllable_count: 0,
+        applications: None,
+    }
+}
+
+/// Returns every pronunciation rule that would change the given Hangul
+/// jamos, along with the syllable boundary it applies to.
+pub fn explain_pronunciation_rules<T: AsRef<str>>(value: T) -> Vec<RuleApplication> {
+    let mut iter = PronounceIter {
+        jamos: JamoStream::from_jamos(value),
+        rules: PRONUNCIATION_RULES.to_vec(),
+        skip_next_initial_consonant: false,
+        pending: VecDeque::with_capacity(2),
+        syllable_count: 0,
+        applications: Some(vec![]),
+    };
+    iter.by_ref().for_each(drop);
+    iter.applications.unwrap_or_default()
+}
+
+/// Returns a rough score of how hard the given word is to pronounce
+/// correctly from its spelling: the number of pronunciation rules that
+/// change it, plus one for every compound final consonant and every
+/// vowel that's easily confused with another one. Higher is harder.
+///
+/// The word can be made of Hangul syllables or jamos.
+pub fn pronunciation_complexity<T: AsRef<str>>(word: T) -> usize {
+    let jamos = to_conjoining_jamo(word);
+    let rule_count = explain_pronunciation_rules(&jamos).len();
+    let folded = fold_confusable_vowels(&jamos);
+    let bonus_count = jamos
+        .chars()
+        .zip(folded.chars())
+        .filter(|&(ch, folded_ch)| is_compound_final(ch) || ch != folded_ch)
+        .count();
+    rule_count + bonus_count
+}
+
+/// Whether the given jamo is a final consonant made of two different
+/// consonants, e.g. ㄺ, only one of which is usually pronounced.
+fn is_compound_final(ch: char) -> bool {
+    matches!(ch, 'ᆪ' | 'ᆬ' | 'ᆭ' | 'ᆰ' | 'ᆱ' | 'ᆲ' | 'ᆳ' | 'ᆴ' | 'ᆵ' | 'ᆶ' | 'ᆹ')
+}
+
+struct PronounceIter {
+    jamos: JamoStream,
+    rules: Vec<RuleKind>,
+    /// Whether the next initial consonant has already been yielded,
+    /// because a rule applied to the final consonant before it.
+    skip_next_initial_consonant: bool,
+    /// Pronounced jamos that haven't been yielded yet. Each jamo
+    /// produces at most two of them.
+    pending: VecDeque<char>,
+    /// The number of syllables whose initial consonant has been seen.
+    syllable_count: usize,
+    /// The rules that have changed anything so far, if we're keeping
+    /// track of them.
+    applications: Option<Vec<RuleApplication>>,
+}
+
+impl Iterator for PronounceIter {
+    type Item = char;
+
+    fn next(&mut self) -> Option<char> {
+        loop {
+            if let Some(ch) = self.pending.pop_front() {
+                return Some(ch);
+            }
+            let jamo = self.jamos.next()?;
+            self.pronounce(jamo);
+        }
+    }
+}
+
+impl PronounceIter {
+    /// Adds the pronunciation of the given jamo to `pending`.
+    fn pronounce(&mut self, jamo: JamoInStream) {
         match ModernJamo::try_from_char(jamo.curr) {
             Some(ModernJamo::InitialConsonant(ch)) => {
-                if skip_next_initial_consonant {
-                    skip_next_initial_consonant = false;
+                self.syllable_count += 1;
+                if self.skip_next_initial_consonant {
+                    self.skip_next_initial_consonant = false;
                 } else {
-                    result.push(ch);
+                    self.pending.push_back(ch);
                 }
             }
             Some(ModernJamo::Vowel(ch)) => {
-                result.push(ch);
+                self.pending.push_back(ch);
             }
             Some(ModernJamo::FinalConsonant(ch)) => {
+                let is_morpheme_boundary = jamo.next == Some(MORPHEME_BOUNDARY);
+                let next_initial_consonant = if is_morpheme_boundary {
+                    // Look past the boundary, to the next syllable.
+                    jamo.next_syllable
+                        .and_then(decompose_hangul_syllable_to_jamos)
+                        .and_then(|(initial, _, _)| ModernJamo::try_from_char(initial))
+                } else {
+                    jamo.next
+                        .map(|char| ModernJamo::try_from_char(char))
+                        .flatten()
+                };
                 let mut ctx = RuleContext {
                     final_consonant: ModernJamo::FinalConsonant(ch),
-                    next_initial_consonant: jamo
-                        .next
-                        .map(|char| ModernJamo::try_from_char(char))
-                        .flatten(),
+                    is_morpheme_boundary,
+                    next_initial_consonant,
                     next_syllable: jamo.next_syllable,
                 };
                 let mut keep_final_consonant = true;
-                for rule in PRONUNCIATION_RULES {
-                    let result = rule(&ctx);
+                for kind in &self.rules {
+                    let result = kind.rule()(&ctx);
+                    if let Some(applications) = &mut self.applications
+                        && !matches!(result, RuleResult::NoChange)
+                    {
+                        applications.push(RuleApplication {
+                            rule: *kind,
+                            name: kind.display_name(),
+                            syllable_index: self.syllable_count.saturating_sub(1),
+                        });
+                    }
                     match result {
                         RuleResult::NoChange => {}
                         RuleResult::ChangeNextInitial(next_initial_consonant) => {
@@ -396,19 +713,22 @@ pub fn apply_pronunciation_rules_to_jamos<T: AsRef<str>>(value: T) -> String {
                     }
                 }
                 if keep_final_consonant {
-                    result.push(ctx.final_consonant.into());
+                    self.pending.push_back(ctx.final_consonant.into());
                 }
                 if let Some(next_initial_consonant) = ctx.next_initial_consonant {
-                    result.push(next_initial_consonant.into());
-                    skip_next_initial_consonant = true;
+                    self.pending.push_back(next_initial_consonant.into());
+                    self.skip_next_initial_consonant = true;
                 }
             }
             None => {
-                result.push(jamo.curr);
+                let is_between_jamos = jamo.prev.and_then(ModernJamo::try_from_char).is_some()
+                    && jamo.next.and_then(ModernJamo::try_from_char).is_some();
+                if !(jamo.curr == MORPHEME_BOUNDARY && is_between_jamos) {
+                    self.pending.push_back(jamo.curr);
+                }
             }
         }
     }
-    result
 }
 
 fn change_initial_consonant(syllable: char, initial: char) -> Option<char> {
@@ -426,7 +746,13 @@ fn change_initial_consonant(syllable: char, initial: char) -> Option<char> {
 mod tests {
     use crate::{
         hangul::{compose_all_hangul_jamos, decompose_all_hangul_syllables},
-        pronunciation::{apply_pronunciation_rules_to_jamos, change_initial_consonant},
+        jamo_stream::JamoStream,
+        pronunciation::{
+            PRONUNCIATION_RULES, RuleApplication, RuleKind, apply_pronunciation_rules_to_jamos,
+            apply_pronunciation_rules_with, change_initial_consonant, explain_pronunciation_rules,
+            fold_confusable_vowels, get_jamo_pronunciation, pronounce_iter,
+            pronunciation_complexity,
+        },
     };
 
     fn apply_syllables(value: &'static str) -> String {
@@ -444,10 +770,23 @@ mod tests {
         assert_eq!(change_initial_consonant('이', 'ᄂ'), Some('니'));
     }
 
+    #[test]
+    fn test_fold_confusable_vowels() {
+        let fold = |value| {
+            compose_all_hangul_jamos(fold_confusable_vowels(decompose_all_hangul_syllables(
+                value,
+            )))
+        };
+        assert_eq!(fold("개"), "게");
+        assert_eq!(fold("왜 외"), "웨 웨");
+        assert_eq!(fold("가"), "가");
+    }
+
     #[test]
     fn test_compound_consonant_rules_work() {
         test_pronounce("넋을", "넉쓸");
         test_pronounce("앉아", "안자");
+        test_pronounce("앉다", "안따");
         test_pronounce("않고", "안코");
         test_pronounce("않다", "안타");
         test_pronounce("않지", "안치");
@@ -456,6 +795,18 @@ mod tests {
         test_pronounce("읽고", "일꼬");
     }
 
+    #[test]
+    fn test_compound_finals_at_end_of_input_are_simplified() {
+        test_pronounce("닭", "닥");
+        test_pronounce("값", "갑");
+        test_pronounce("삶", "삼");
+        test_pronounce("흙", "흑");
+        test_pronounce("많", "만");
+        // The same goes for the end of a word, rather than the input.
+        test_pronounce("닭 값", "닥 갑");
+        test_pronounce("삶.", "삼.");
+    }
+
     #[test]
     fn test_nasalization_rules_work() {
         test_pronounce("국내", "궁내");
@@ -466,6 +817,9 @@ mod tests {
         test_pronounce("난로", "날로");
         test_pronounce("종로", "종노");
         test_pronounce("함락", "함낙");
+        test_pronounce("독립", "동닙");
+        test_pronounce("왕십리", "왕심니");
+        test_pronounce("설날", "설랄");
     }
 
     #[test]
@@ -486,6 +840,28 @@ mod tests {
         test_pronounce("곧이", "고지");
         test_pronounce("같이", "가치");
         test_pronounce("닫히", "다치");
+        test_pronounce("잡혀", "자펴");
+        test_pronounce("축하", "추카");
+        test_pronounce("젖히다", "저치다");
+        test_pronounce("못하다", "모타다");
+    }
+
+    #[test]
+    fn test_n_insertion_rule_works() {
+        test_pronounce("담+요", "담뇨");
+        test_pronounce("한+여름", "한녀름");
+        // Without a morpheme boundary, the rule doesn't apply.
+        test_pronounce("한여름", "하녀름");
+        // Boundaries that aren't between Hangul are left alone.
+        test_pronounce("1+1", "1+1");
+    }
+
+    #[test]
+    fn test_apply_only_some_rules_works() {
+        let jamos = decompose_all_hangul_syllables("작곡을");
+        let pronounced = apply_pronunciation_rules_with(jamos, &[RuleKind::Reinforcement]);
+        assert_eq!(compose_all_hangul_jamos(pronounced), "작꼭을".to_owned());
+        test_pronounce("작곡을", "작꼬글");
     }
 
     #[test]
@@ -496,4 +872,128 @@ mod tests {
         // Ensure h is silent.
         test_pronounce("좋아", "조아");
     }
+
+    #[test]
+    fn test_pronounce_iter_works() {
+        for (value, pronounced) in [
+            ("", ""),
+            ("밥을 먹어요", "바블 머거요"),
+            ("넋을 앉다 읽고", "넉쓸 안따 일꼬"),
+            ("국물, 신라!", "궁물, 실라!"),
+            ("hello 좋아요", "hello 조아요"),
+            ("같이+이", "가치이"),
+        ] {
+            let jamos = decompose_all_hangul_syllables(value);
+            assert_eq!(
+                compose_all_hangul_jamos(pronounce_iter(&jamos).collect::<String>()),
+                pronounced,
+                "{value}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_ui_vowel_hint_depends_on_context() {
+        let medial_hint = |value: &str| {
+            let jamo = JamoStream::from_hangul_syllables(value).nth(1).unwrap();
+            assert_eq!(jamo.curr, 'ᅴ');
+            get_jamo_pronunciation(&jamo)
+        };
+        assert!(medial_hint("의사").starts_with("ㅡ then ㅣ"));
+        assert!(medial_hint("희망").contains("follows a consonant"));
+    }
+
+    #[test]
+    fn test_final_tense_consonant_hints_differ_from_initial() {
+        let hint = |value: &str, idx: usize| {
+            let jamo = JamoStream::from_jamos(value).nth(idx).unwrap();
+            get_jamo_pronunciation(&jamo)
+        };
+        // 깎 is ᄁ, ᅡ, ᆩ.
+        let kkakk = "\u{1101}\u{1161}\u{11a9}";
+        assert_eq!(hint(kkakk, 0), "'ch' after 's', as in 'school'");
+        assert_eq!(hint(kkakk, 2), hint("\u{1100}\u{1161}\u{11a8}", 2));
+        // 있 is ᄋ, ᅵ, ᆻ.
+        let iss = "\u{110b}\u{1175}\u{11bb}";
+        assert_eq!(hint(iss, 2), "'t' as in 'cat', no puff of air at all");
+        assert_ne!(hint(iss, 2), hint("\u{110a}\u{1161}", 0));
+    }
+
+    #[test]
+    fn test_reinforcement_after_each_stop_final_works() {
+        // [ㄱ] finals
+        test_pronounce("국밥", "국빱");
+        test_pronounce("깎다", "깎따");
+        test_pronounce("부엌과", "부엌꽈");
+        // [ㄷ] finals. Note that e.g. 젓가락 is pronounced 젇까락, but
+        // finals are only neutralized when romanizing.
+        test_pronounce("숟가락", "숟까락");
+        test_pronounce("젓가락", "젓까락");
+        test_pronounce("있다", "있따");
+        test_pronounce("옷장", "옷짱");
+        test_pronounce("밭고랑", "밭꼬랑");
+        // [ㅂ] finals
+        test_pronounce("덮개", "덮깨");
+        test_pronounce("앞집", "앞찝");
+        // Other finals don't trigger it.
+        test_pronounce("손가락", "손가락");
+    }
+
+    #[test]
+    fn test_explain_pronunciation_rules() {
+        let applications = explain_pronunciation_rules(decompose_all_hangul_syllables("학교"));
+        assert_eq!(
+            applications,
+            vec![RuleApplication {
+                rule: RuleKind::Reinforcement,
+                name: "경음화 (reinforcement)",
+                syllable_index: 0,
+            }]
+        );
+        assert_eq!(
+            explain_pronunciation_rules(decompose_all_hangul_syllables("사랑")),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn test_rule_examples_are_accurate() {
+        for kind in PRONUNCIATION_RULES {
+            let (before, after) = kind.example();
+            let pronounced = compose_all_hangul_jamos(apply_pronunciation_rules_with(
+                decompose_all_hangul_syllables(before),
+                &[kind],
+            ));
+            assert_eq!(pronounced, after, "{kind:?}");
+            let applications = explain_pronunciation_rules(decompose_all_hangul_syllables(before));
+            assert!(
+                applications
+                    .iter()
+                    .any(|application| application.rule == kind),
+                "{kind:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_fillers_are_ignored() {
+        test_pronounce("학\u{1160}교", "학꾜");
+        test_pronounce("\u{3164}국물", "궁물");
+    }
+
+    #[test]
+    fn test_pronunciation_complexity() {
+        assert_eq!(pronunciation_complexity("나라"), 0);
+        // Liaison of ㄱ and reinforcement of ㄷ after ㅆ.
+        assert!(pronunciation_complexity("먹었다") > pronunciation_complexity("나라"));
+        assert!(pronunciation_complexity("먹었다") >= 2);
+        // ㅐ is easily confused with ㅔ.
+        assert_eq!(pronunciation_complexity("개"), 1);
+        // A compound final, which is also simplified by a rule.
+        assert_eq!(pronunciation_complexity("닭"), 2);
+        assert_eq!(
+            pronunciation_complexity(decompose_all_hangul_syllables("닭")),
+            2
+        );
+    }
 }
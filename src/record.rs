@@ -1,16 +1,60 @@
 use std::{
+    fs::File,
+    io::{BufReader, Write, stdout},
     sync::{Arc, Mutex},
     time::Duration,
 };
 
 use anyhow::{Result, anyhow};
 use cpal::traits::{DeviceTrait, HostTrait};
+use log::debug;
+use rodio::{Decoder, Sink};
 
-pub fn run_record() -> Result<()> {
+use crate::audio::open_output;
+
+/// The file that recordings are written to.
+pub const RECORDING_FILENAME: &str = "recording.wav";
+
+/// Length of each window of audio, in milliseconds, that a
+/// single level meter reading represents.
+const LEVEL_WINDOW_MILLIS: u128 = 50;
+
+/// Width of the level meter, in characters.
+const LEVEL_METER_WIDTH: usize = 40;
+
+/// The peak level, in dBFS, that recordings are normalized to.
+const NORMALIZED_PEAK_DBFS: f32 = -3.0;
+
+/// Prints the name of every audio input device.
+pub fn list_input_devices() -> Result<()> {
     let host = cpal::default_host();
-    let Some(device) = host.default_input_device() else {
-        return Err(anyhow!("Unable to query default audio input device"));
+    for device in host.input_devices()? {
+        println!("{}", device.name()?);
+    }
+    Ok(())
+}
+
+/// Returns the audio input device with the given name, or the default
+/// one if no name is given.
+fn find_input_device(host: &cpal::Host, name: Option<&str>) -> Result<cpal::Device> {
+    let Some(name) = name else {
+        return host
+            .default_input_device()
+            .ok_or_else(|| anyhow!("Unable to query default audio input device"));
     };
+    host.input_devices()?
+        .find(|device| device.name().is_ok_and(|device_name| device_name == name))
+        .ok_or_else(|| anyhow!("No audio input device is named {name:?} (see --list-devices)"))
+}
+
+/// Records audio to `RECORDING_FILENAME` from the input device with the
+/// given name, or the default one. If `normalize` is true, the
+/// recording is then made louder (or quieter) so that its peak is at
+/// `NORMALIZED_PEAK_DBFS`, which makes quiet recordings easier to
+/// compare against reference tracks.
+pub fn run_record(normalize: bool, device_name: Option<&str>) -> Result<()> {
+    let host = cpal::default_host();
+    let device = find_input_device(&host, device_name)?;
     if let Ok(name) = device.name() {
         println!("Using device {name:?}.");
     }
@@ -36,8 +80,8 @@ pub fn run_record() -> Result<()> {
         },
     };
     println!("Using stream config: {:?}", config);
-    const OUTFILE: &'static str = "recording.wav";
-    let writer = hound::WavWriter::create(OUTFILE, spec)?;
+    debug!("Writing {RECORDING_FILENAME} with {spec:?}");
+    let writer = hound::WavWriter::create(RECORDING_FILENAME, spec)?;
     let writer = Arc::new(Mutex::new(Some(writer)));
     let err_fn = move |err| {
         println!("ERROR: {:?}", err);
@@ -73,10 +117,204 @@ pub fn run_record() -> Result<()> {
         }
     };
     let duration = Duration::from_secs(5);
-    println!("Recording {duration:?} of audio to {OUTFILE}...");
+    println!("Recording {duration:?} of audio to {RECORDING_FILENAME}...");
     std::thread::sleep(duration);
     drop(stream);
     writer.lock().unwrap().take().unwrap().finalize()?;
     println!("Done recording.");
+    if normalize {
+        normalize_recording(RECORDING_FILENAME)?;
+    }
     Ok(())
 }
+
+/// Scales the given samples so that their peak amplitude is the given
+/// level, from 0.0 to 1.0, returning the gain that was applied. Silence
+/// is left alone, since it can't be made any louder.
+fn normalize_samples(samples: &mut [f32], target_peak: f32) -> f32 {
+    let peak = samples
+        .iter()
+        .fold(0.0_f32, |peak, sample| peak.max(sample.abs()));
+    if peak == 0.0 {
+        return 1.0;
+    }
+    let gain = target_peak / peak;
+    for sample in samples.iter_mut() {
+        *sample *= gain;
+    }
+    gain
+}
+
+/// Normalizes the given WAV file, which must contain float samples, in
+/// place.
+fn normalize_recording(filename: &str) -> Result<()> {
+    let mut reader = hound::WavReader::open(filename)?;
+    let spec = reader.spec();
+    if spec.sample_format != hound::SampleFormat::Float {
+        return Err(anyhow!(
+            "Only recordings with float samples can be normalized"
+        ));
+    }
+    let mut samples: Vec<f32> = reader.samples::<f32>().collect::<Result<_, _>>()?;
+    drop(reader);
+    let target_peak = 10_f32.powf(NORMALIZED_PEAK_DBFS / 20.0);
+    let gain = normalize_samples(&mut samples, target_peak);
+    println!(
+        "Normalized recording to {NORMALIZED_PEAK_DBFS} dBFS ({:+.1} dB).",
+        20.0 * gain.log10()
+    );
+    let mut writer = hound::WavWriter::create(filename, spec)?;
+    for sample in samples {
+        writer.write_sample(sample)?;
+    }
+    writer.finalize()?;
+    Ok(())
+}
+
+/// Reads the given WAV file and returns the peak amplitude, from
+/// 0.0 to 1.0, of each `LEVEL_WINDOW_MILLIS` window of it.
+fn read_peak_levels(filename: &str) -> Result<Vec<f32>> {
+    let mut reader = hound::WavReader::open(filename)?;
+    let spec = reader.spec();
+    let samples: Vec<f32> = match spec.sample_format {
+        hound::SampleFormat::Float => reader.samples::<f32>().collect::<Result<_, _>>()?,
+        hound::SampleFormat::Int => {
+            let max = (1_i64 << (spec.bits_per_sample - 1)) as f32;
+            reader
+                .samples::<i32>()
+                .map(|sample| sample.map(|sample| sample as f32 / max))
+                .collect::<Result<_, _>>()?
+        }
+    };
+    let window_len = (spec.sample_rate as u128 * spec.channels as u128 * LEVEL_WINDOW_MILLIS / 1000)
+        .max(1) as usize;
+    Ok(peak_levels(&samples, window_len))
+}
+
+fn peak_levels(samples: &[f32], window_len: usize) -> Vec<f32> {
+    samples
+        .chunks(window_len)
+        .map(|window| {
+            window
+                .iter()
+                .fold(0.0_f32, |peak, sample| peak.max(sample.abs()))
+                .min(1.0)
+        })
+        .collect()
+}
+
+/// Renders a level meter for the given level, from 0.0 to 1.0.
+fn level_meter(level: f32, width: usize) -> String {
+    let filled = (level.clamp(0.0, 1.0) * width as f32).round() as usize;
+    format!(
+        "[{}{}] {:>3.0}%",
+        "█".repeat(filled),
+        " ".repeat(width - filled),
+        level.clamp(0.0, 1.0) * 100.0
+    )
+}
+
+/// Plays the given WAV file, optionally showing a level meter
+/// while it plays.
+pub fn run_playback(filename: &str, show_level_meter: bool) -> Result<()> {
+    let levels = if show_level_meter {
+        Some(read_peak_levels(filename)?)
+    } else {
+        None
+    };
+    let (_stream, stream_handle) = open_output()?;
+    let sink = Sink::try_new(&stream_handle)?;
+    let file = BufReader::new(File::open(filename)?);
+    sink.append(Decoder::new(file)?);
+    println!("Playing {filename}...");
+    if let Some(levels) = levels {
+        let mut stdout = stdout();
+        while !sink.empty() {
+            let window = (sink.get_pos().as_millis() / LEVEL_WINDOW_MILLIS) as usize;
+            let level = levels.get(window).cloned().unwrap_or_default();
+            print!("\r{}", level_meter(level, LEVEL_METER_WIDTH));
+            stdout.flush()?;
+            std::thread::sleep(Duration::from_millis(LEVEL_WINDOW_MILLIS as u64));
+        }
+        println!();
+    } else {
+        sink.sleep_until_end();
+    }
+    println!("Done playing.");
+    Ok(())
+}
+
+/// Returns the order to play the recording and the reference clip in
+/// when comparing them: the recording first, then the reference, for
+/// the given number of rounds.
+fn comparison_playlist<'a>(recording: &'a str, reference: &'a str, rounds: usize) -> Vec<&'a str> {
+    [recording, reference].repeat(rounds)
+}
+
+/// Records audio, then plays the recording followed by the given
+/// reference clip, alternating between them for the given number of
+/// rounds, so they can be compared. The recording is made as with
+/// `run_record`.
+pub fn run_compare(
+    reference: &str,
+    rounds: usize,
+    normalize: bool,
+    device_name: Option<&str>,
+) -> Result<()> {
+    // Make sure the comparison can actually be played before asking the
+    // user to record anything.
+    Decoder::new(BufReader::new(File::open(reference)?))?;
+    let (_stream, stream_handle) = open_output()?;
+    run_record(normalize, device_name)?;
+    for (idx, filename) in comparison_playlist(RECORDING_FILENAME, reference, rounds)
+        .into_iter()
+        .enumerate()
+    {
+        println!("Round {}: playing {filename}...", idx / 2 + 1);
+        let sink = Sink::try_new(&stream_handle)?;
+        sink.append(Decoder::new(BufReader::new(File::open(filename)?))?);
+        sink.sleep_until_end();
+    }
+    println!("Done comparing.");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::record::{comparison_playlist, level_meter, normalize_samples, peak_levels};
+
+    #[test]
+    fn test_normalize_samples_works() {
+        let mut samples = [0.1, -0.25, 0.05];
+        assert_eq!(normalize_samples(&mut samples, 0.5), 2.0);
+        assert_eq!(samples, [0.2, -0.5, 0.1]);
+
+        let mut silence = [0.0, 0.0];
+        assert_eq!(normalize_samples(&mut silence, 0.5), 1.0);
+        assert_eq!(silence, [0.0, 0.0]);
+    }
+
+    #[test]
+    fn test_comparison_playlist_alternates() {
+        assert_eq!(
+            comparison_playlist("me.wav", "ref.wav", 2),
+            vec!["me.wav", "ref.wav", "me.wav", "ref.wav"]
+        );
+        assert!(comparison_playlist("me.wav", "ref.wav", 0).is_empty());
+    }
+
+    #[test]
+    fn test_peak_levels_works() {
+        assert_eq!(
+            peak_levels(&[0.1, -0.5, 0.25, 0.0, -2.0], 2),
+            vec![0.5, 0.25, 1.0]
+        );
+    }
+
+    #[test]
+    fn test_level_meter_works() {
+        assert_eq!(level_meter(0.0, 4), "[    ]   0%");
+        assert_eq!(level_meter(0.5, 4), "[██  ]  50%");
+        assert_eq!(level_meter(1.5, 4), "[████] 100%");
+    }
+}
@@ -1,4 +1,14 @@
-use crate::jamo_stream::{JamoInStream, JamoStream};
+use clap::ValueEnum;
+
+use crate::{
+    hangul::{
+        HangulCharClass, decompose_all_hangul_syllables, decompose_hangul_syllable_to_jamos,
+        halfwidth_jamos_to_conjoining, is_silent_initial,
+    },
+    jamo_stream::{JamoInStream, JamoStream, ModernJamo},
+    numbers::replace_digits_with_sino,
+    pronunciation::{PRONUNCIATION_RULES, RuleKind, apply_pronunciation_rules_with},
+};
 
 /// Get the romanization of a final consonant, when there is no vowel following it.
 fn get_final_with_no_next_vowel(ch: char) -> Option<&'static str> {
@@ -48,6 +58,21 @@ fn get_final_with_next_vowel(ch: char) -> Option<&'static str> {
     }
 }
 
+/// Get both romanizations of a final consonant, separated by a slash,
+/// e.g. "k/g" for ㄱ: first the one used when no vowel follows it, then
+/// the one used when a vowel follows it.
+///
+/// If both romanizations are the same, only one is returned.
+pub fn get_romanized_final_alternatives(ch: char) -> Option<String> {
+    let no_next_vowel = get_final_with_no_next_vowel(ch)?;
+    let next_vowel = get_final_with_next_vowel(ch)?;
+    if no_next_vowel == next_vowel {
+        Some(no_next_vowel.to_owned())
+    } else {
+        Some(format!("{no_next_vowel}/{next_vowel}"))
+    }
+}
+
 /// Get the romanization of a Hangul jamo.
 ///
 /// `is_next_vowel` represents whether the syllable
@@ -59,28 +84,14 @@ fn get_final_with_next_vowel(ch: char) -> Option<&'static str> {
 /// jamos, which will convert compound consonants
 /// to standard jamos.
 pub fn get_romanized_jamo(jamo: &JamoInStream) -> Option<&'static str> {
+    // ㄹㄹ is romanized "ll", e.g. 빨리 is "ppalli".
+    if jamo.curr == 'ᄅ' && jamo.prev == Some('ᆯ') {
+        return Some("l");
+    }
+    if let Some(initial) = get_romanized_initial(jamo.curr) {
+        return Some(initial);
+    }
     match jamo.curr {
-        // Initial
-        'ᄀ' => Some("g"),
-        'ᄁ' => Some("kk"),
-        'ᄂ' => Some("n"),
-        'ᄃ' => Some("d"),
-        'ᄄ' => Some("tt"),
-        'ᄅ' => Some("r"),
-        'ᄆ' => Some("m"),
-        'ᄇ' => Some("b"),
-        'ᄈ' => Some("pp"),
-        'ᄉ' => Some("s"),
-        'ᄊ' => Some("ss"),
-        'ᄋ' => Some(""), // silent
-        'ᄌ' => Some("j"),
-        'ᄍ' => Some("jj"),
-        'ᄎ' => Some("ch"),
-        'ᄏ' => Some("k"),
-        'ᄐ' => Some("t"),
-        'ᄑ' => Some("p"),
-        'ᄒ' => Some("h"),
-
         // Medial (vowel)
         'ᅡ' => Some("a"),
         'ᅢ' => Some("ae"),
@@ -114,25 +125,432 @@ pub fn get_romanized_jamo(jamo: &JamoInStream) -> Option<&'static str> {
     }
 }
 
+/// Get the romanization of an initial consonant, which doesn't depend on
+/// its surroundings. The silent ㅇ is romanized as an empty string.
+fn get_romanized_initial(ch: char) -> Option<&'static str> {
+    match ch {
+        'ᄀ' => Some("g"),
+        'ᄁ' => Some("kk"),
+        'ᄂ' => Some("n"),
+        'ᄃ' => Some("d"),
+        'ᄄ' => Some("tt"),
+        'ᄅ' => Some("r"),
+        'ᄆ' => Some("m"),
+        'ᄇ' => Some("b"),
+        'ᄈ' => Some("pp"),
+        'ᄉ' => Some("s"),
+        'ᄊ' => Some("ss"),
+        'ᄋ' => Some(""), // silent
+        'ᄌ' => Some("j"),
+        'ᄍ' => Some("jj"),
+        'ᄎ' => Some("ch"),
+        'ᄏ' => Some("k"),
+        'ᄐ' => Some("t"),
+        'ᄑ' => Some("p"),
+        'ᄒ' => Some("h"),
+        _ => None,
+    }
+}
+
+/// Get the romanization of an initial consonant for showing to the
+/// user, where an empty romanization would be confusing: the silent ㅇ
+/// is shown as "silent", and anything that isn't an initial consonant
+/// as "?".
+pub fn romanize_initial_display(ch: char) -> &'static str {
+    if is_silent_initial(ch) {
+        "silent"
+    } else {
+        get_romanized_initial(ch).unwrap_or("?")
+    }
+}
+
+/// Get the romanization of a vowel using diacritics to distinguish
+/// ㅓ from ㅗ and ㅡ from ㅜ, if it differs from the standard one.
+fn get_vowel_with_diacritics(ch: char) -> Option<&'static str> {
+    match ch {
+        'ᅥ' => Some("ŏ"),
+        'ᅧ' => Some("yŏ"),
+        'ᅳ' => Some("ŭ"),
+        'ᅴ' => Some("ŭi"),
+        _ => None,
+    }
+}
+
+/// A way of romanizing Hangul.
+#[derive(Debug, Clone, Copy, Default, PartialEq, ValueEnum)]
+pub enum RomanizationScheme {
+    /// Revised Romanization, the official system in South Korea.
+    #[default]
+    Revised,
+    /// Like Revised Romanization, but with a consistent three-way
+    /// contrast between consonants, for beginners: lax ones are written
+    /// as in Revised Romanization (ㄱ "g"), aspirated ones are followed by
+    /// an apostrophe for their puff of air (ㅋ "k'"), and tense ones
+    /// double the lax letter (ㄲ "gg").
+    BeginnerPhonetic,
+}
+
+/// Get the romanization of an aspirated or tense consonant in the
+/// beginner phonetic scheme, if it differs from the Revised one.
+fn get_beginner_phonetic_consonant(jamo: &JamoInStream) -> Option<&'static str> {
+    match jamo.curr {
+        'ᄏ' => Some("k'"),
+        'ᄐ' => Some("t'"),
+        'ᄑ' => Some("p'"),
+        'ᄎ' => Some("ch'"),
+        'ᄁ' => Some("gg"),
+        'ᄄ' => Some("dd"),
+        'ᄈ' => Some("bb"),
+        // Finals are only released when a vowel follows them.
+        _ if !jamo.is_final_consonant_followed_by_vowel() => None,
+        'ᆿ' => Some("k'"),
+        'ᇀ' => Some("t'"),
+        'ᇁ' => Some("p'"),
+        'ᆾ' => Some("ch'"),
+        'ᆩ' => Some("gg"),
+        _ => None,
+    }
+}
+
+/// Romanizes the given syllable as it sounds when the next syllable
+/// starts with a vowel, so that its final consonant carries over to it,
+/// e.g. "bab-" for 밥 (which is "bap" on its own).
+///
+/// Returns None if the character isn't a syllable with a (non-compound)
+/// final consonant.
+pub fn romanize_with_liaison(ch: char) -> Option<String> {
+    let (initial, medial, Some(final_ch)) = decompose_hangul_syllable_to_jamos(ch)? else {
+        return None;
+    };
+    let next_vowel = get_final_with_next_vowel(final_ch)?;
+    let open_syllable = romanize_decomposed_hangul(format!("{initial}{medial}"));
+    Some(format!("{open_syllable}{next_vowel}-"))
+}
+
+/// Romanizes the given Hangul, which may be written with precomposed
+/// syllables, conjoining jamos, or a mix of both.
+///
+/// Everything that isn't Hangul is passed through unchanged, as with
+/// `romanize_decomposed_hangul`.
+pub fn romanize(value: &str) -> String {
+    romanize_decomposed_hangul(decompose_all_hangul_syllables(value))
+}
+
 /// Romanizes the given sequence of Hangul jamos.
 ///
-/// (These should _not_ be Hangul syllables!)
+/// (These should _not_ be Hangul syllables! Precomposed syllables
+/// aren't jamos, so they're passed through unromanized, like any other
+/// character. Use `romanize` unless the input is known to already be
+/// decomposed, e.g. after applying pronunciation rules to it.)
+///
+/// Only Hangul jamos are changed: every other character, including
+/// punctuation, whitespace, casing and multi-byte characters such as
+/// emoji, is passed through byte-for-byte, in order. No normalization
+/// is done on them.
 pub fn romanize_decomposed_hangul<T: AsRef<str>>(value: T) -> String {
-    let mut result = String::with_capacity(value.as_ref().len());
+    romanize_jamos(value, RomanizeTextOptions::default())
+}
+
+/// Like `romanize_decomposed_hangul`, but romanizes vowels and initial
+/// ㄹ as specified by the `diacritics` and `word_initial_l` options.
+fn romanize_jamos<T: AsRef<str>>(value: T, options: RomanizeTextOptions) -> String {
+    romanize_jamos_aligned(value, options)
+        .into_iter()
+        .map(|(_, romanized)| romanized)
+        .collect()
+}
+
+/// Like `romanize_decomposed_hangul`, but returns each character of the
+/// given string along with the part of the romanization it's responsible
+/// for, so that e.g. initials, vowels and finals can be told apart.
+///
+/// Characters that aren't Hangul jamos are returned as their own
+/// romanization.
+pub fn romanize_aligned<T: AsRef<str>>(value: T) -> Vec<(char, String)> {
+    romanize_jamos_aligned(value, RomanizeTextOptions::default())
+}
+
+/// A piece of romanized text, as returned by `romanize_structured`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RomanizedToken {
+    /// A Hangul syllable, along with its romanization.
+    Hangul { source: char, roman: String },
+    /// A run of anything else, which is passed through unchanged.
+    Other(String),
+}
+
+/// Like `romanize_text`, but keeps track of which syllable each part of
+/// the romanization came from, so that e.g. a syllable and its
+/// romanization can be shown together. Concatenating the romanization of
+/// every token gives the same result as `romanize_text` with the same
+/// options.
+pub fn romanize_structured(value: &str, options: RomanizeTextOptions) -> Vec<RomanizedToken> {
+    let mut tokens: Vec<RomanizedToken> = vec![];
+    for jamos in sentence_jamos(value, options) {
+        let mut sentence = romanize_jamos_structured(&jamos, options);
+        if options.capitalize_sentences
+            && let Some(roman) = sentence
+                .iter_mut()
+                .map(|token| match token {
+                    RomanizedToken::Hangul { roman, .. } => roman,
+                    RomanizedToken::Other(other) => other,
+                })
+                .find(|roman| roman.contains(char::is_alphabetic))
+        {
+            *roman = capitalize_first_letter(roman);
+        }
+        for token in sentence {
+            match (tokens.last_mut(), token) {
+                (Some(RomanizedToken::Other(prev)), RomanizedToken::Other(other)) => {
+                    prev.push_str(&other)
+                }
+                (_, token) => tokens.push(token),
+            }
+        }
+    }
+    tokens
+}
+
+/// Like `romanize_structured`, but for jamos, which are romanized as
+/// specified by the given options, as with `romanize_jamos`.
+fn romanize_jamos_structured(jamos: &str, options: RomanizeTextOptions) -> Vec<RomanizedToken> {
+    let mut tokens: Vec<RomanizedToken> = vec![];
+    for (jamo, (_, romanized)) in
+        JamoStream::from_jamos(jamos).zip(romanize_jamos_aligned(jamos, options))
+    {
+        match (jamo.curr_syllable, tokens.last_mut()) {
+            (Some(source), _) if jamo.is_syllable_start => tokens.push(RomanizedToken::Hangul {
+                source,
+                roman: romanized,
+            }),
+            (Some(_), Some(RomanizedToken::Hangul { roman, .. })) => roman.push_str(&romanized),
+            (_, Some(RomanizedToken::Other(other))) => other.push_str(&romanized),
+            _ => tokens.push(RomanizedToken::Other(romanized)),
+        }
+    }
+    tokens
+}
+
+/// Like `romanize`, but inserts a hyphen between syllables whose
+/// boundary would otherwise be ambiguous, as Revised Romanization
+/// allows, e.g. 중앙 as "jung-ang" rather than "jungang", which could
+/// be read as 준강. Hyphens are only inserted where they're needed:
+///
+/// * after "ng" and before a vowel, e.g. 중앙 ("jung-ang"),
+/// * after "n" and before "g", e.g. 반구 ("ban-gu", not 방우),
+/// * between two vowels, e.g. 해운대 ("hae-undae", not 하은대).
+///
+/// Otherwise, the text is romanized as `romanize_text` does with the
+/// given options.
+pub fn romanize_disambiguated(value: &str, options: RomanizeTextOptions) -> String {
+    let mut result = String::with_capacity(value.len());
+    let mut prev_syllable: Option<String> = None;
+    for token in romanize_structured(value, options) {
+        match token {
+            RomanizedToken::Hangul { roman, .. } => {
+                if let Some(prev) = &prev_syllable
+                    && is_ambiguous_boundary(prev, &roman)
+                {
+                    result.push('-');
+                }
+                result.push_str(&roman);
+                prev_syllable = Some(roman);
+            }
+            RomanizedToken::Other(other) => {
+                result.push_str(&other);
+                prev_syllable = None;
+            }
+        }
+    }
+    result
+}
+
+/// Whether the romanizations of two adjacent syllables could be split
+/// into syllables differently when written together.
+fn is_ambiguous_boundary(prev: &str, next: &str) -> bool {
+    let is_vowel = |ch: char| matches!(ch, 'a' | 'e' | 'i' | 'o' | 'u' | 'ŏ' | 'ŭ');
+    let Some(first) = next.chars().next() else {
+        return false;
+    };
+    if prev.ends_with("ng") {
+        is_vowel(first) || first == 'w' || first == 'y'
+    } else if prev.ends_with('n') {
+        first == 'g'
+    } else {
+        prev.ends_with(is_vowel) && is_vowel(first)
+    }
+}
+
+fn romanize_jamos_aligned<T: AsRef<str>>(
+    value: T,
+    options: RomanizeTextOptions,
+) -> Vec<(char, String)> {
     let stream = JamoStream::from_jamos(value);
-    for jamo in stream {
-        if let Some(romanized) = get_romanized_jamo(&jamo) {
-            result.push_str(romanized);
+    stream
+        .map(|jamo| {
+            // Tone marks don't affect the romanization.
+            if HangulCharClass::from(jamo.curr) == HangulCharClass::ToneMark {
+                return (jamo.curr, String::new());
+            }
+            let alternative = if options.pronounced && is_ui_after_consonant(&jamo) {
+                Some("i")
+            } else if options.scheme == RomanizationScheme::BeginnerPhonetic
+                && let Some(phonetic) = get_beginner_phonetic_consonant(&jamo)
+            {
+                Some(phonetic)
+            } else if options.word_initial_l && is_word_initial_rieul(&jamo) {
+                Some("l")
+            } else if options.diacritics {
+                get_vowel_with_diacritics(jamo.curr)
+            } else {
+                None
+            };
+            let romanized = match alternative.or_else(|| get_romanized_jamo(&jamo)) {
+                Some(romanized) => romanized.to_owned(),
+                None => jamo.curr.to_string(),
+            };
+            (jamo.curr, romanized)
+        })
+        .collect()
+}
+
+/// Whether the given jamo is a ㅢ that follows a consonant, in which
+/// case it's pronounced like ㅣ, e.g. 희망 is pronounced 히망.
+fn is_ui_after_consonant(jamo: &JamoInStream) -> bool {
+    jamo.curr == 'ᅴ'
+        && jamo
+            .prev
+            .is_some_and(|prev| ModernJamo::is_initial_consonant(prev) && !is_silent_initial(prev))
+}
+
+/// Whether the given jamo is a ㄹ that starts a word, rather than
+/// following another syllable.
+fn is_word_initial_rieul(jamo: &JamoInStream) -> bool {
+    jamo.curr == 'ᄅ'
+        && jamo.is_syllable_start
+        && jamo
+            .prev
+            .is_none_or(|prev| HangulCharClass::from(prev) != HangulCharClass::Jamo)
+}
+
+/// The pronunciation rules that Revised Romanization reflects, which is
+/// all of them except reinforcement: tensing isn't romanized, e.g. 맛있다
+/// is "masitda" rather than "masitta".
+const ROMANIZED_RULES: [RuleKind; PRONUNCIATION_RULES.len() - 1] = without_reinforcement();
+
+/// Returns `PRONUNCIATION_RULES` without reinforcement, keeping the
+/// order they're applied in.
+const fn without_reinforcement() -> [RuleKind; PRONUNCIATION_RULES.len() - 1] {
+    let mut rules = [RuleKind::CompoundConsonant; PRONUNCIATION_RULES.len() - 1];
+    let mut count = 0;
+    let mut idx = 0;
+    while idx < PRONUNCIATION_RULES.len() {
+        if !matches!(PRONUNCIATION_RULES[idx], RuleKind::Reinforcement) {
+            rules[count] = PRONUNCIATION_RULES[idx];
+            count += 1;
+        }
+        idx += 1;
+    }
+    rules
+}
+
+/// Options for `romanize_text`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RomanizeTextOptions {
+    /// Collapse runs of whitespace into a single space, and trim
+    /// whitespace from the ends.
+    pub normalize_spacing: bool,
+    /// Capitalize the first letter of each sentence.
+    pub capitalize_sentences: bool,
+    /// Mark easily confused vowels with diacritics, e.g. "ŏ" for ㅓ.
+    pub diacritics: bool,
+    /// Read numbers written with digits using their Sino-Korean
+    /// pronunciation, e.g. "3" as "sam".
+    pub transliterate_digits: bool,
+    /// Romanize ㄹ at the start of a word as "l" rather than "r", e.g.
+    /// 라면 as "lamyeon", as Yale romanization does. Revised Romanization
+    /// always uses "r" here, but word-initial ㄹ mostly occurs in
+    /// loanwords, which often come from words with an "l" (e.g. 레몬,
+    /// "lemon"). Unlike in Yale romanization, ㄹ within a word, e.g.
+    /// between vowels, is still romanized as "r".
+    pub word_initial_l: bool,
+    /// Romanize the text as it's pronounced: apply pronunciation rules
+    /// (except for reinforcement, which Revised Romanization ignores)
+    /// first, and romanize ㅢ after a consonant as "i", e.g. 희망 as
+    /// "himang" rather than "huimang".
+    pub pronounced: bool,
+    /// How to romanize consonants.
+    pub scheme: RomanizationScheme,
+}
+
+/// Romanizes the given text, which may contain Hangul syllables
+/// or jamos (including halfwidth ones), one sentence at a time.
+///
+/// Sentences are ended by `.`, `?` or `!`.
+pub fn romanize_text(value: &str, options: RomanizeTextOptions) -> String {
+    let mut result = String::with_capacity(value.len());
+    for jamos in sentence_jamos(value, options) {
+        let romanized = romanize_jamos(jamos, options);
+        if options.capitalize_sentences {
+            result.push_str(&capitalize_first_letter(&romanized));
         } else {
-            result.push(jamo.curr);
+            result.push_str(&romanized);
         }
     }
     result
 }
 
+/// Splits the given text into the sentences that `romanize_text`
+/// romanizes, returning each one's jamos, once the text has been
+/// prepared as the given options say.
+fn sentence_jamos(value: &str, options: RomanizeTextOptions) -> Vec<String> {
+    let text = if options.normalize_spacing {
+        value.split_whitespace().collect::<Vec<_>>().join(" ")
+    } else {
+        value.to_owned()
+    };
+    let text = if options.transliterate_digits {
+        replace_digits_with_sino(&text)
+    } else {
+        text
+    };
+    let text = halfwidth_jamos_to_conjoining(text);
+    text.split_inclusive(['.', '?', '!'])
+        .map(|sentence| {
+            let jamos = decompose_all_hangul_syllables(sentence);
+            if options.pronounced {
+                apply_pronunciation_rules_with(jamos, &ROMANIZED_RULES)
+            } else {
+                jamos
+            }
+        })
+        .collect()
+}
+
+fn capitalize_first_letter(value: &str) -> String {
+    let Some(idx) = value.find(|ch: char| ch.is_alphabetic()) else {
+        return value.to_owned();
+    };
+    let mut chars = value[idx..].chars();
+    let first = chars.next().unwrap();
+    format!(
+        "{}{}{}",
+        &value[..idx],
+        first.to_uppercase(),
+        chars.as_str()
+    )
+}
+
 #[cfg(test)]
 mod test {
-    use crate::romanize::romanize_decomposed_hangul;
+    use crate::hangul::{HangulCharClass, decompose_all_hangul_syllables};
+    use crate::pronunciation::apply_pronunciation_rules_with;
+    use crate::romanize::{
+        ROMANIZED_RULES, RomanizationScheme, RomanizeTextOptions, RomanizedToken,
+        get_romanized_final_alternatives, romanize, romanize_aligned, romanize_decomposed_hangul,
+        romanize_disambiguated, romanize_initial_display, romanize_structured, romanize_text,
+        romanize_with_liaison,
+    };
 
     #[test]
     fn test_romanize_works() {
@@ -141,8 +559,378 @@ mod test {
         assert_eq!(romanize_decomposed_hangul("밥을"), "babeul".to_owned());
     }
 
+    fn romanize_pronounced(value: &str) -> String {
+        romanize_decomposed_hangul(apply_pronunciation_rules_with(
+            decompose_all_hangul_syllables(value),
+            &ROMANIZED_RULES,
+        ))
+    }
+
+    #[test]
+    fn test_romanize_pronounced_compound_finals_works() {
+        // ᆰ before ㄱ becomes ᆯ, and the ㄱ is reinforced to ㄲ.
+        assert_eq!(romanize_pronounced("읽고"), "ilkko".to_owned());
+        // ᆬ becomes ᆫ, and the ㄷ is reinforced to ㄸ.
+        assert_eq!(romanize_pronounced("앉다"), "antta".to_owned());
+        // ᆲ becomes ᆸ in 밟다. Reinforcing the ㄷ after it isn't romanized.
+        assert_eq!(romanize_pronounced("밟다"), "bapda".to_owned());
+    }
+
+    #[test]
+    fn test_romanize_aligned_works() {
+        assert_eq!(
+            romanize_aligned(decompose_all_hangul_syllables("밥을!")),
+            vec![
+                ('ᄇ', "b".to_owned()),
+                ('ᅡ', "a".to_owned()),
+                ('ᆸ', "b".to_owned()),
+                ('ᄋ', "".to_owned()),
+                ('ᅳ', "eu".to_owned()),
+                ('ᆯ', "l".to_owned()),
+                ('!', "!".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_romanize_structured_works() {
+        let hangul = |source, roman: &str| RomanizedToken::Hangul {
+            source,
+            roman: roman.to_owned(),
+        };
+        let tokens = romanize_structured("밥을 먹다!", RomanizeTextOptions::default());
+        assert_eq!(
+            tokens,
+            vec![
+                hangul('밥', "bab"),
+                hangul('을', "eul"),
+                RomanizedToken::Other(" ".to_owned()),
+                hangul('먹', "meok"),
+                hangul('다', "da"),
+                RomanizedToken::Other("!".to_owned()),
+            ]
+        );
+        let flat: String = tokens
+            .iter()
+            .map(|token| match token {
+                RomanizedToken::Hangul { roman, .. } => roman.as_str(),
+                RomanizedToken::Other(other) => other.as_str(),
+            })
+            .collect();
+        assert_eq!(flat, romanize("밥을 먹다!"));
+
+        let options = RomanizeTextOptions {
+            normalize_spacing: true,
+            capitalize_sentences: true,
+            diacritics: true,
+            pronounced: true,
+            ..Default::default()
+        };
+        let value = "밥을  먹어. 정말? 네";
+        let tokens = romanize_structured(value, options);
+        assert_eq!(tokens[0], hangul('바', "Ba"));
+        let flat: String = tokens
+            .iter()
+            .map(|token| match token {
+                RomanizedToken::Hangul { roman, .. } => roman.as_str(),
+                RomanizedToken::Other(other) => other.as_str(),
+            })
+            .collect();
+        assert_eq!(flat, romanize_text(value, options));
+    }
+
     #[test]
     fn test_non_hangul_is_unchanged() {
         assert_eq!(romanize_decomposed_hangul("hi"), "hi".to_owned());
     }
+
+    #[test]
+    fn test_non_hangul_runs_are_preserved_exactly() {
+        assert_eq!(romanize("Hello, 세계! (2024)"), "Hello, segye! (2024)");
+
+        for value in [
+            "Hello, 세계! (2024)",
+            "😀세계🇰🇷  ÜNÏCÖDÉ\ttab\r\n",
+            "  밥을   먹어요... ",
+        ] {
+            // Romanizing each Hangul run on its own, while leaving
+            // everything else alone, should give the same result.
+            let expected: String = HangulCharClass::split(value)
+                .into_iter()
+                .map(|(class, run)| match class {
+                    HangulCharClass::None => run.to_owned(),
+                    _ => romanize(run),
+                })
+                .collect();
+            assert_eq!(romanize(value), expected);
+        }
+    }
+
+    #[test]
+    fn test_romanized_final_alternatives_works() {
+        assert_eq!(get_romanized_final_alternatives('ᆨ'), Some("k/g".to_owned()));
+        assert_eq!(get_romanized_final_alternatives('ᆫ'), Some("n".to_owned()));
+        assert_eq!(get_romanized_final_alternatives('ᄀ'), None);
+    }
+
+    #[test]
+    fn test_romanize_text_works() {
+        let text = "안녕하세요.  저는   학생이에요!";
+        assert_eq!(
+            romanize_text(text, RomanizeTextOptions::default()),
+            "annyeonghaseyo.  jeoneun   haksaengieyo!".to_owned()
+        );
+        assert_eq!(
+            romanize_text(
+                text,
+                RomanizeTextOptions {
+                    normalize_spacing: true,
+                    capitalize_sentences: true,
+                    ..Default::default()
+                }
+            ),
+            "Annyeonghaseyo. Jeoneun haksaengieyo!".to_owned()
+        );
+    }
+
+    #[test]
+    fn test_romanize_text_with_halfwidth_jamos_works() {
+        // A halfwidth ㄱ, by itself and followed by a halfwidth ㅏ.
+        assert_eq!(
+            romanize_text("\u{ffa1}", RomanizeTextOptions::default()),
+            "g".to_owned()
+        );
+        assert_eq!(
+            romanize_text("\u{ffa1}\u{ffc2}", RomanizeTextOptions::default()),
+            "ga".to_owned()
+        );
+    }
+
+    #[test]
+    fn test_romanize_text_with_diacritics_works() {
+        assert_eq!(
+            romanize_text("어른", RomanizeTextOptions::default()),
+            "eoreun".to_owned()
+        );
+        assert_eq!(
+            romanize_text(
+                "어른",
+                RomanizeTextOptions {
+                    diacritics: true,
+                    ..Default::default()
+                }
+            ),
+            "ŏrŭn".to_owned()
+        );
+    }
+
+    #[test]
+    fn test_romanize_text_with_transliterated_digits_works() {
+        assert_eq!(
+            romanize_text("3개", RomanizeTextOptions::default()),
+            "3gae".to_owned()
+        );
+        assert_eq!(
+            romanize_text(
+                "3개",
+                RomanizeTextOptions {
+                    transliterate_digits: true,
+                    ..Default::default()
+                }
+            ),
+            "samgae".to_owned()
+        );
+    }
+
+    #[test]
+    fn test_romanize_text_with_word_initial_l_works() {
+        assert_eq!(
+            romanize_text("라면", RomanizeTextOptions::default()),
+            "ramyeon".to_owned()
+        );
+        let options = RomanizeTextOptions {
+            word_initial_l: true,
+            ..Default::default()
+        };
+        assert_eq!(romanize_text("라면", options), "lamyeon".to_owned());
+        // ㄹ between vowels is still "r".
+        assert_eq!(romanize_text("나라", options), "nara".to_owned());
+        // Each word has its own start.
+        assert_eq!(
+            romanize_text("라면 라디오", options),
+            "lamyeon ladio".to_owned()
+        );
+        // It works along with diacritics.
+        let options = RomanizeTextOptions {
+            diacritics: true,
+            ..options
+        };
+        assert_eq!(romanize_text("라면", options), "lamyŏn".to_owned());
+    }
+
+    #[test]
+    fn test_romanize_initial_display_works() {
+        assert_eq!(romanize_initial_display('ᄋ'), "silent");
+        assert_eq!(romanize_initial_display('ᄀ'), "g");
+        assert_eq!(romanize_initial_display('ᅡ'), "?");
+    }
+
+    #[test]
+    fn test_romanize_text_pronounced_ui_works() {
+        let pronounced = RomanizeTextOptions {
+            pronounced: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            romanize_text("희망", RomanizeTextOptions::default()),
+            "huimang".to_owned()
+        );
+        assert_eq!(romanize_text("희망", pronounced), "himang".to_owned());
+        // After the silent ㅇ, it's still "ui".
+        assert_eq!(
+            romanize_text("의자", RomanizeTextOptions::default()),
+            "uija".to_owned()
+        );
+        assert_eq!(romanize_text("의자", pronounced), "uija".to_owned());
+        // Pronunciation rules are applied too.
+        assert_eq!(romanize_text("밥을 먹어요", pronounced), "babeul meogeoyo");
+        assert_eq!(romanize_text("국물", pronounced), "gungmul");
+        // Except reinforcement, which isn't romanized.
+        assert_eq!(romanize_text("맛있다", pronounced), "masitda");
+    }
+
+    #[test]
+    fn test_romanization_matches_reference() {
+        let mut failures = vec![];
+        for line in include_str!("romanize_reference.tsv").lines() {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (word, expected) = line.split_once('\t').unwrap();
+            let actual = romanize_decomposed_hangul(apply_pronunciation_rules_with(
+                decompose_all_hangul_syllables(word),
+                &ROMANIZED_RULES,
+            ));
+            if actual != expected {
+                failures.push(format!("{word}: expected {expected}, got {actual}"));
+            }
+        }
+        assert!(failures.is_empty(), "\n{}", failures.join("\n"));
+    }
+
+    #[test]
+    fn test_tone_marks_are_ignored() {
+        assert_eq!(
+            romanize_decomposed_hangul(decompose_all_hangul_syllables("말\u{302e}씀\u{302f}")),
+            "malsseum"
+        );
+    }
+
+    #[test]
+    fn test_romanize_accepts_syllables_or_jamos() {
+        assert_eq!(romanize("밥"), "bap");
+        assert_eq!(romanize("\u{1107}\u{1161}\u{11b8}"), "bap");
+        assert_eq!(romanize("밥 \u{1106}\u{1165}\u{11a8}어!"), "bap meogeo!");
+        // The low-level function doesn't romanize syllables.
+        assert_eq!(romanize_decomposed_hangul("밥"), "밥");
+    }
+
+    #[test]
+    fn test_final_ng_does_not_link_to_next_vowel() {
+        // Unlike other finals, ㅇ stays in its own syllable before a vowel,
+        // rather than replacing the silent initial.
+        let pronounced = RomanizeTextOptions {
+            pronounced: true,
+            ..Default::default()
+        };
+        for (word, expected) in [
+            ("강아지", "gangaji"),
+            ("종이", "jongi"),
+            ("생일", "saengil"),
+        ] {
+            assert_eq!(
+                romanize_text(word, RomanizeTextOptions::default()),
+                expected
+            );
+            assert_eq!(romanize_text(word, pronounced), expected);
+        }
+        assert_eq!(
+            romanize_aligned(decompose_all_hangul_syllables("종이")),
+            vec![
+                ('ᄌ', "j".to_owned()),
+                ('ᅩ', "o".to_owned()),
+                ('ᆼ', "ng".to_owned()),
+                ('ᄋ', "".to_owned()),
+                ('ᅵ', "i".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_romanize_with_liaison() {
+        assert_eq!(romanize_with_liaison('밥'), Some("bab-".to_owned()));
+        assert_eq!(romanize_with_liaison('옷'), Some("os-".to_owned()));
+        assert_eq!(romanize_with_liaison('바'), None);
+        assert_eq!(romanize_with_liaison('닭'), None);
+        assert_eq!(romanize_with_liaison('a'), None);
+    }
+
+    #[test]
+    fn test_fillers_have_no_romanization() {
+        assert_eq!(romanize("ᄒ\u{1160}ᅡᆫ"), "han");
+        assert_eq!(romanize("\u{3164}안녕\u{ffa0}"), "annyeong");
+    }
+
+    #[test]
+    fn test_beginner_phonetic_scheme() {
+        let romanize_phonetic = |value: &str| {
+            let options = RomanizeTextOptions {
+                scheme: RomanizationScheme::BeginnerPhonetic,
+                ..Default::default()
+            };
+            romanize_text(value, options)
+        };
+        // The three-way contrast between lax, aspirated and tense.
+        assert_eq!(romanize_phonetic("기 키 끼"), "gi k'i ggi");
+        assert_eq!(romanize_phonetic("다 타 따"), "da t'a dda");
+        assert_eq!(romanize_phonetic("바 파 빠"), "ba p'a bba");
+        assert_eq!(romanize_phonetic("자 차 짜"), "ja ch'a jja");
+        assert_eq!(romanize_phonetic("사 싸"), "sa ssa");
+        // Finals are only aspirated or tense when a vowel follows them.
+        assert_eq!(romanize_phonetic("부엌 부엌에"), "bueok bueok'e");
+        assert_eq!(romanize_phonetic("밖 밖에"), "bak bagge");
+        // The default is Revised Romanization.
+        assert_eq!(
+            romanize_text("키 끼", RomanizeTextOptions::default()),
+            "ki kki"
+        );
+    }
+
+    #[test]
+    fn test_romanize_disambiguated() {
+        let hyphenate = |value| romanize_disambiguated(value, RomanizeTextOptions::default());
+        assert_eq!(hyphenate("중앙"), "jung-ang");
+        assert_eq!(hyphenate("중요"), "jung-yo");
+        assert_eq!(hyphenate("반구"), "ban-gu");
+        assert_eq!(hyphenate("해운대"), "hae-undae");
+        // Ordinary boundaries aren't hyphenated.
+        assert_eq!(hyphenate("사랑해"), "saranghae");
+        assert_eq!(hyphenate("밥을"), "babeul");
+        assert_eq!(hyphenate("안녕하세요"), "annyeonghaseyo");
+        // Neither are ones between words.
+        assert_eq!(hyphenate("가 아"), "ga a");
+        // The other options are honored too.
+        let options = RomanizeTextOptions {
+            normalize_spacing: true,
+            capitalize_sentences: true,
+            diacritics: true,
+            pronounced: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            romanize_disambiguated("정어.  중앙", options),
+            "Jŏng-ŏ. Jung-ang"
+        );
+        assert_eq!(romanize_disambiguated("신라", options), "Silla");
+    }
 }
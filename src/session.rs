@@ -0,0 +1,191 @@
+/// Where the player was when it was quit, so that practice can pick up
+/// there the next time the same song is played.
+///
+/// Sessions are saved as JSON next to the audio file, e.g.:
+///
+/// ```json
+/// {
+///   "line": 12,
+///   "position": { "secs": 83, "nanos": 250000000 },
+///   "speed": 0.75,
+///   "volume": 1.0,
+///   "since": { "secs": 60, "nanos": 0 },
+///   "until": null
+/// }
+/// ```
+use std::{
+    ffi::OsString,
+    fs::{read_to_string, write},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SessionState {
+    /// The index of the selected line of lyrics.
+    pub line: usize,
+    /// How far into the song playback was.
+    pub position: Duration,
+    /// How fast the song was playing.
+    pub speed: f32,
+    /// How loud the song was playing, where 1 is its original volume.
+    pub volume: f32,
+    /// Where the section of the song being practiced started, if it was
+    /// limited to one.
+    pub since: Option<Duration>,
+    /// Where the section of the song being practiced ended, if it was
+    /// limited to one.
+    pub until: Option<Duration>,
+}
+
+impl Default for SessionState {
+    fn default() -> Self {
+        SessionState {
+            line: 0,
+            position: Duration::ZERO,
+            speed: 1.0,
+            volume: 1.0,
+            since: None,
+            until: None,
+        }
+    }
+}
+
+impl SessionState {
+    /// Parses a saved session. Keys it doesn't know about are ignored,
+    /// and ones it's missing keep their defaults.
+    pub fn parse(value: &str) -> Result<Self> {
+        serde_json::from_str(value).map_err(|err| anyhow!("Invalid session: {err}"))
+    }
+
+    /// Returns the session in the format that `parse` reads.
+    pub fn to_json(&self) -> String {
+        // Nothing in the session can fail to serialize.
+        serde_json::to_string_pretty(self).unwrap() + "\n"
+    }
+
+    /// Makes the session fit the song it's being restored for, in case
+    /// its lyrics or audio have changed since it was saved.
+    pub fn clamp(
+        self,
+        line_count: usize,
+        total_duration: Option<Duration>,
+        min_speed: f32,
+        max_speed: f32,
+    ) -> Self {
+        let clamp_time = |time: Duration| match total_duration {
+            Some(total_duration) => time.min(total_duration),
+            None => time,
+        };
+        SessionState {
+            line: self.line.min(line_count.saturating_sub(1)),
+            position: clamp_time(self.position),
+            speed: self.speed.clamp(min_speed, max_speed),
+            volume: self.volume.max(0.0),
+            since: self.since.map(clamp_time),
+            until: self.until.map(clamp_time),
+        }
+    }
+}
+
+/// Returns where the session for the given audio file is saved, which
+/// is next to it with ".session" added to its name, so that songs that
+/// only differ in their extension don't share a session.
+pub fn session_filename(audio_filename: &Path) -> PathBuf {
+    let mut filename = OsString::from(audio_filename);
+    filename.push(".session");
+    PathBuf::from(filename)
+}
+
+/// Loads the session saved for the given audio file, if there is one.
+pub fn load_session(audio_filename: &Path) -> Result<Option<SessionState>> {
+    let filename = session_filename(audio_filename);
+    if !filename.exists() {
+        return Ok(None);
+    }
+    Ok(Some(SessionState::parse(&read_to_string(filename)?)?))
+}
+
+/// Saves the given session for the given audio file.
+pub fn save_session(audio_filename: &Path, state: &SessionState) -> Result<()> {
+    write(session_filename(audio_filename), state.to_json())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{path::Path, time::Duration};
+
+    use crate::session::{SessionState, session_filename};
+
+    #[test]
+    fn test_round_trip() {
+        let state = SessionState {
+            line: 12,
+            position: Duration::from_millis(83250),
+            speed: 0.75,
+            volume: 0.5,
+            since: Some(Duration::from_secs(60)),
+            until: None,
+        };
+        assert_eq!(SessionState::parse(&state.to_json()).unwrap(), state);
+    }
+
+    #[test]
+    fn test_parse_is_lenient_about_keys() {
+        let state = SessionState::parse(r#"{"line": 3, "shuffle": true}"#).unwrap();
+        assert_eq!(
+            state,
+            SessionState {
+                line: 3,
+                ..Default::default()
+            }
+        );
+        assert!(SessionState::parse(r#"{"line": "three"}"#).is_err());
+        assert!(SessionState::parse("line = 3").is_err());
+    }
+
+    #[test]
+    fn test_clamp() {
+        let state = SessionState {
+            line: 50,
+            position: Duration::from_secs(300),
+            speed: 10.0,
+            volume: -1.0,
+            since: Some(Duration::from_secs(250)),
+            until: Some(Duration::from_secs(400)),
+        };
+        assert_eq!(
+            state
+                .clone()
+                .clamp(20, Some(Duration::from_secs(200)), 0.25, 4.0),
+            SessionState {
+                line: 19,
+                position: Duration::from_secs(200),
+                speed: 4.0,
+                volume: 0.0,
+                since: Some(Duration::from_secs(200)),
+                until: Some(Duration::from_secs(200)),
+            }
+        );
+        let clamped = state.clamp(100, None, 0.25, 4.0);
+        assert_eq!(clamped.line, 50);
+        assert_eq!(clamped.position, Duration::from_secs(300));
+    }
+
+    #[test]
+    fn test_session_filename() {
+        assert_eq!(
+            session_filename(Path::new("/songs/song.mp3")),
+            Path::new("/songs/song.mp3.session")
+        );
+        assert_ne!(
+            session_filename(Path::new("song.mp3")),
+            session_filename(Path::new("song.flac"))
+        );
+    }
+}
@@ -0,0 +1,218 @@
+/// A printable summary of a word or line of Hangul, for reviewing
+/// it offline.
+use crate::{
+    hangul::{
+        compose_all_hangul_jamos, compose_hangul_jamos_to_syllable, decompose_all_hangul_syllables,
+        display_jamo, syllable_to_compat_jamo_string,
+    },
+    jamo_stream::{JamoInStream, JamoStream, ModernJamo},
+    pronunciation::{apply_pronunciation_rules_to_jamos, get_jamo_pronunciation},
+    romanize::{
+        RomanizeTextOptions, get_romanized_jamo, romanize, romanize_initial_display, romanize_text,
+    },
+};
+
+/// One jamo of a syllable, as shown in the study sheet.
+struct JamoRow {
+    position: &'static str,
+    jamo: String,
+    romanized: &'static str,
+    hint: &'static str,
+}
+
+impl JamoRow {
+    fn new(jamo: &JamoInStream) -> Option<Self> {
+        let (position, romanized) = match ModernJamo::try_from_char(jamo.curr)? {
+            ModernJamo::InitialConsonant(ch) => ("initial", romanize_initial_display(ch)),
+            ModernJamo::Vowel(_) => ("medial", get_romanized_jamo(jamo).unwrap_or("?")),
+            ModernJamo::FinalConsonant(_) => ("final", get_romanized_jamo(jamo).unwrap_or("?")),
+        };
+        Some(JamoRow {
+            position,
+            jamo: display_jamo(jamo.curr, false),
+            romanized,
+            hint: get_jamo_pronunciation(jamo),
+        })
+    }
+}
+
+/// Breaks the given string into its syllables, along with the rows
+/// for each of their jamos. Anything that isn't a Hangul syllable is
+/// skipped.
+fn get_syllable_rows(value: &str) -> Vec<(char, Vec<JamoRow>)> {
+    let mut syllables: Vec<(Vec<char>, Vec<JamoRow>)> = vec![];
+    for jamo in JamoStream::from_hangul_syllables(value) {
+        let Some(row) = JamoRow::new(&jamo) else {
+            continue;
+        };
+        if jamo.is_syllable_start {
+            syllables.push((vec![], vec![]));
+        }
+        if let Some((jamos, rows)) = syllables.last_mut() {
+            jamos.push(jamo.curr);
+            rows.push(row);
+        }
+    }
+    syllables
+        .into_iter()
+        .filter_map(|(jamos, rows)| {
+            Some((compose_hangul_jamos_to_syllable(jamos.into_iter())?, rows))
+        })
+        .collect()
+}
+
+/// Returns a study sheet for the given string, showing its romanization,
+/// how it's pronounced, and a breakdown of every jamo of every syllable
+/// with a pronunciation hint.
+///
+/// If `markdown` is true, the sheet is formatted as Markdown, with the
+/// breakdown as a table; otherwise it's aligned plain text.
+pub fn study_sheet(value: &str, markdown: bool) -> String {
+    let decomposed = decompose_all_hangul_syllables(value);
+    let romanized = romanize(value);
+    let pronounced = format!(
+        "{} ({})",
+        compose_all_hangul_jamos(apply_pronunciation_rules_to_jamos(&decomposed)),
+        romanize_pronounced(value)
+    );
+    let syllables = get_syllable_rows(value);
+    let mut lines = vec![];
+    if markdown {
+        lines.push(format!("# {value}"));
+        lines.push(String::new());
+        lines.push(format!("- **Romanized:** {romanized}"));
+        lines.push(format!("- **Pronounced:** {pronounced}"));
+        lines.push(String::new());
+        lines.push("| Syllable | Position | Jamo | Romanized | Hint |".to_owned());
+        lines.push("| --- | --- | --- | --- | --- |".to_owned());
+        for (syllable, rows) in &syllables {
+            for row in rows {
+                lines.push(format!(
+                    "| {syllable} | {} | {} | {} | {} |",
+                    row.position, row.jamo, row.romanized, row.hint
+                ));
+            }
+        }
+    } else {
+        lines.push(value.to_owned());
+        lines.push(format!("  romanized : {romanized}"));
+        lines.push(format!("  pronounced: {pronounced}"));
+        for (syllable, rows) in &syllables {
+            let syllable_romanized = romanize(&syllable.to_string());
+            let jamos = syllable_to_compat_jamo_string(*syllable).unwrap_or_default();
+            lines.push(String::new());
+            lines.push(format!("  {syllable} ({syllable_romanized}): {jamos}"));
+            for row in rows {
+                let mut line = format!("    {:<7}: {} ({})", row.position, row.jamo, row.romanized);
+                if !row.hint.is_empty() {
+                    line.push_str(&format!(" - {}", row.hint));
+                }
+                lines.push(line);
+            }
+        }
+    }
+    lines.join("\n")
+}
+
+/// Quotes the given field of a TSV row if it contains anything that
+/// would otherwise end it, doubling any quotes inside it.
+fn escape_tsv_field(value: &str) -> String {
+    if value.contains(['\t', '\n', '\r', '"']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_owned()
+    }
+}
+
+/// Romanizes the given string as it's pronounced, leaving out the sound
+/// changes that Revised Romanization doesn't reflect, e.g. 학교 is
+/// "hakgyo" rather than "hakkyo".
+fn romanize_pronounced(value: &str) -> String {
+    romanize_text(
+        value,
+        RomanizeTextOptions {
+            pronounced: true,
+            ..Default::default()
+        },
+    )
+}
+
+/// Returns a tab-separated flashcard for the given word or line, ready
+/// to import into e.g. Anki. Its fields are the word itself, its
+/// romanization, how it's pronounced, and the jamos of each of its
+/// syllables, e.g. "학교", "hakgyo", "학꾜 (hakgyo)" and "ㅎㅏㄱ ㄱㅛ".
+pub fn flashcard_row(value: &str) -> String {
+    let romanized = romanize_pronounced(value);
+    let pronounced = format!(
+        "{} ({romanized})",
+        compose_all_hangul_jamos(apply_pronunciation_rules_to_jamos(
+            decompose_all_hangul_syllables(value)
+        )),
+    );
+    let breakdown: Vec<String> = value
+        .chars()
+        .filter_map(syllable_to_compat_jamo_string)
+        .collect();
+    [value.to_owned(), romanized, pronounced, breakdown.join(" ")]
+        .iter()
+        .map(|field| escape_tsv_field(field))
+        .collect::<Vec<_>>()
+        .join("\t")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::study_sheet::{flashcard_row, study_sheet};
+
+    #[test]
+    fn test_flashcard_row() {
+        assert_eq!(
+            flashcard_row("학교"),
+            "학교\thakgyo\t학꾜 (hakgyo)\tㅎㅏㄱ ㄱㅛ"
+        );
+        // Compound finals are romanized as they're pronounced.
+        assert_eq!(
+            flashcard_row("읽고"),
+            "읽고\tilkko\t일꼬 (ilkko)\tㅇㅣㄺ ㄱㅗ"
+        );
+        // Fields that would break the row are quoted.
+        let row = flashcard_row("좋아\n\"요\"");
+        let fields: Vec<&str> = row.split('\t').collect();
+        assert_eq!(fields.len(), 4, "{row}");
+        assert_eq!(fields[0], "\"좋아\n\"\"요\"\"\"");
+    }
+
+    #[test]
+    fn test_study_sheet_shows_everything() {
+        let sheet = study_sheet("학교", false);
+        for expected in [
+            "학교",
+            "romanized : hakgyo",
+            "pronounced: 학꾜 (hakgyo)",
+            "  학 (hak): ㅎㅏㄱ",
+            "    initial: ㅎ (h)",
+            "    medial : ㅏ (a)",
+            "    final  : ㄱ (k)",
+            "  교 (gyo)",
+        ] {
+            assert!(sheet.contains(expected), "{expected:?} not in:\n{sheet}");
+        }
+    }
+
+    #[test]
+    fn test_study_sheet_markdown() {
+        let sheet = study_sheet("학교", true);
+        for expected in [
+            "# 학교",
+            "- **Romanized:** hakgyo",
+            "- **Pronounced:** 학꾜 (hakgyo)",
+            "| Syllable | Position | Jamo | Romanized | Hint |",
+            "| 학 | initial | ㅎ | h |",
+            "| 교 | medial | ㅛ | yo |",
+        ] {
+            assert!(sheet.contains(expected), "{expected:?} not in:\n{sheet}");
+        }
+        let sheet = study_sheet("좋아요!", true);
+        assert!(sheet.contains("| 요 | medial | ㅛ | yo |"), "{sheet}");
+    }
+}
@@ -0,0 +1,325 @@
+/// Changing the tempo of audio without changing its pitch, so that a
+/// song can be slowed down without distorting its vowels.
+///
+/// This uses WSOLA (waveform similarity overlap-add): the input is cut
+/// into overlapping windows that are spaced according to the speed,
+/// and each one is nudged to wherever it best lines up with the
+/// previous one before they're blended together.
+use std::{collections::VecDeque, f32::consts::PI, time::Duration};
+
+use rodio::{Source, source::SeekError};
+
+/// How long each window of audio is, in seconds.
+const WINDOW_SECS: f32 = 0.04;
+
+/// How far a window can be nudged from where it would nominally start,
+/// in seconds, to line it up with the previous one.
+const TOLERANCE_SECS: f32 = 0.01;
+
+/// Only every this many frames are compared when looking for the best
+/// place to start a window, which is plenty for finding where the
+/// waveforms line up.
+const SEARCH_STEP: usize = 2;
+
+/// A source that plays another one at a different speed, without
+/// changing its pitch.
+pub struct TimeStretch<S> {
+    inner: S,
+    speed: f32,
+    channels: usize,
+    /// The number of frames that output windows are apart, which is
+    /// half of a window.
+    hop: usize,
+    tolerance: usize,
+    /// The weights that each window is multiplied by before it's
+    /// blended with its neighbors.
+    window: Vec<f32>,
+    /// Interleaved input samples that may still be needed.
+    input: Vec<f32>,
+    /// The index of the first frame of `input`, counting from the
+    /// start of the inner source (or the last seek).
+    input_start: usize,
+    inner_finished: bool,
+    /// Where the next window would start in the input, if it didn't
+    /// need to line up with the previous one.
+    nominal_pos: f64,
+    /// Where the previous window would have continued in the input,
+    /// which the next window should resemble.
+    natural_pos: Option<usize>,
+    /// The second half of the previous window, to blend with the
+    /// first half of the next one.
+    tail: Vec<f32>,
+    output: VecDeque<i16>,
+    finished: bool,
+}
+
+impl<S: Source<Item = i16>> TimeStretch<S> {
+    /// Plays the given source at the given speed, e.g. 0.75 for three
+    /// quarters of its normal speed.
+    pub fn new(inner: S, speed: f32) -> Self {
+        let channels = inner.channels().max(1) as usize;
+        let sample_rate = inner.sample_rate() as f32;
+        let hop = ((sample_rate * WINDOW_SECS) as usize / 2).max(1);
+        let tolerance = (sample_rate * TOLERANCE_SECS) as usize;
+        // A periodic Hann window, whose overlapping halves always add
+        // up to one.
+        let window = (0..hop * 2)
+            .map(|idx| 0.5 - 0.5 * (PI * idx as f32 / hop as f32).cos())
+            .collect();
+        TimeStretch {
+            inner,
+            speed,
+            channels,
+            hop,
+            tolerance,
+            window,
+            input: vec![],
+            input_start: 0,
+            inner_finished: false,
+            nominal_pos: 0.0,
+            natural_pos: None,
+            tail: vec![0.0; hop * channels],
+            output: VecDeque::new(),
+            finished: false,
+        }
+    }
+
+    /// Returns the number of input frames after `input_start` that
+    /// have been read.
+    fn input_frames(&self) -> usize {
+        self.input.len() / self.channels
+    }
+
+    /// Reads from the inner source until the input reaches the given
+    /// frame, or the inner source runs out.
+    fn fill_input_to(&mut self, frame: usize) {
+        while !self.inner_finished && self.input_start + self.input_frames() < frame {
+            match self.inner.next() {
+                Some(sample) => self.input.push(sample as f32),
+                None => self.inner_finished = true,
+            }
+        }
+    }
+
+    /// Returns the sample of the given channel at the given input
+    /// frame, or silence if it's past the end of the input.
+    fn sample_at(&self, frame: usize, channel: usize) -> f32 {
+        let idx = (frame - self.input_start) * self.channels + channel;
+        self.input.get(idx).copied().unwrap_or(0.0)
+    }
+
+    /// Returns how similar the half-window of input starting at the
+    /// given frame is to the one starting at `natural_pos`.
+    fn similarity(&self, frame: usize, natural_pos: usize) -> f32 {
+        let mut total = 0.0;
+        for offset in (0..self.hop).step_by(SEARCH_STEP) {
+            for channel in 0..self.channels {
+                total += self.sample_at(frame + offset, channel)
+                    * self.sample_at(natural_pos + offset, channel);
+            }
+        }
+        total
+    }
+
+    /// Returns where the next window should start: the frame near its
+    /// nominal position that best continues the previous window.
+    fn choose_window_start(&self) -> usize {
+        let nominal = (self.nominal_pos.round() as usize).max(self.input_start);
+        let Some(natural_pos) = self.natural_pos else {
+            return nominal;
+        };
+        let first = nominal.saturating_sub(self.tolerance).max(self.input_start);
+        let last = nominal + self.tolerance;
+        (first..=last)
+            .step_by(SEARCH_STEP)
+            .map(|frame| (frame, self.similarity(frame, natural_pos)))
+            .fold((nominal, f32::MIN), |best, candidate| {
+                if candidate.1 > best.1 {
+                    candidate
+                } else {
+                    best
+                }
+            })
+            .0
+    }
+
+    /// Blends the next window into the output, producing `hop` more
+    /// frames of it.
+    fn process_window(&mut self) {
+        let nominal = self.nominal_pos.round() as usize;
+        self.fill_input_to(nominal + self.tolerance + self.hop * 2);
+        if self.inner_finished && nominal >= self.input_start + self.input_frames() {
+            // The input is used up, so all that's left is the fading
+            // out end of the previous window.
+            for sample in std::mem::take(&mut self.tail) {
+                self.output.push_back(to_i16(sample));
+            }
+            self.finished = true;
+            return;
+        }
+        let start = self.choose_window_start();
+        for frame in 0..self.hop * 2 {
+            let weight = self.window[frame];
+            for channel in 0..self.channels {
+                let sample = self.sample_at(start + frame, channel) * weight;
+                let tail_idx = frame * self.channels + channel;
+                if frame < self.hop {
+                    let blended = self.tail[tail_idx] + sample;
+                    self.output.push_back(to_i16(blended));
+                } else {
+                    self.tail[tail_idx - self.hop * self.channels] = sample;
+                }
+            }
+        }
+        self.natural_pos = Some(start + self.hop);
+        self.nominal_pos += self.hop as f64 * self.speed as f64;
+
+        // Forget any input that can't be part of a later window.
+        let next_nominal = self.nominal_pos.round() as usize;
+        let needed_from = (start + self.hop).min(next_nominal.saturating_sub(self.tolerance));
+        if needed_from > self.input_start {
+            let drop_frames = (needed_from - self.input_start).min(self.input_frames());
+            self.input.drain(..drop_frames * self.channels);
+            self.input_start += drop_frames;
+        }
+    }
+}
+
+fn to_i16(sample: f32) -> i16 {
+    sample.round().clamp(i16::MIN as f32, i16::MAX as f32) as i16
+}
+
+impl<S: Source<Item = i16>> Iterator for TimeStretch<S> {
+    type Item = i16;
+
+    fn next(&mut self) -> Option<i16> {
+        while self.output.is_empty() && !self.finished {
+            self.process_window();
+        }
+        self.output.pop_front()
+    }
+}
+
+impl<S: Source<Item = i16>> Source for TimeStretch<S> {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        self.channels as u16
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.inner
+            .total_duration()
+            .map(|duration| duration.div_f32(self.speed))
+    }
+
+    /// Seeks to the given position in the inner source, i.e. in the
+    /// song's own time rather than the stretched time.
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.inner.try_seek(pos)?;
+        self.input.clear();
+        self.input_start = 0;
+        self.inner_finished = false;
+        self.nominal_pos = 0.0;
+        self.natural_pos = None;
+        self.tail.fill(0.0);
+        self.output.clear();
+        self.finished = false;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{f32::consts::PI, time::Duration};
+
+    use rodio::{Source, source::SeekError};
+
+    use crate::time_stretch::TimeStretch;
+
+    const SAMPLE_RATE: u32 = 8000;
+
+    /// A mono sine wave at the given frequency.
+    struct Sine {
+        samples: std::vec::IntoIter<i16>,
+    }
+
+    impl Sine {
+        fn new(frequency: f32, secs: f32) -> Self {
+            let len = (SAMPLE_RATE as f32 * secs) as usize;
+            let samples: Vec<i16> = (0..len)
+                .map(|idx| {
+                    let t = idx as f32 / SAMPLE_RATE as f32;
+                    ((2.0 * PI * frequency * t).sin() * 10000.0) as i16
+                })
+                .collect();
+            Sine {
+                samples: samples.into_iter(),
+            }
+        }
+    }
+
+    impl Iterator for Sine {
+        type Item = i16;
+
+        fn next(&mut self) -> Option<i16> {
+            self.samples.next()
+        }
+    }
+
+    impl Source for Sine {
+        fn current_frame_len(&self) -> Option<usize> {
+            None
+        }
+
+        fn channels(&self) -> u16 {
+            1
+        }
+
+        fn sample_rate(&self) -> u32 {
+            SAMPLE_RATE
+        }
+
+        fn total_duration(&self) -> Option<Duration> {
+            None
+        }
+
+        fn try_seek(&mut self, _pos: Duration) -> Result<(), SeekError> {
+            Ok(())
+        }
+    }
+
+    /// Returns the number of times the samples go from negative to
+    /// non-negative, per second.
+    fn frequency_of(samples: &[i16]) -> f32 {
+        let crossings = samples
+            .windows(2)
+            .filter(|pair| pair[0] < 0 && pair[1] >= 0)
+            .count();
+        crossings as f32 / (samples.len() as f32 / SAMPLE_RATE as f32)
+    }
+
+    #[test]
+    fn test_slowing_down_keeps_pitch() {
+        let samples: Vec<i16> = TimeStretch::new(Sine::new(440.0, 1.0), 0.5).collect();
+        let secs = samples.len() as f32 / SAMPLE_RATE as f32;
+        assert!((secs - 2.0).abs() < 0.1, "stretched to {secs} seconds");
+        let frequency = frequency_of(&samples);
+        assert!((frequency - 440.0).abs() < 20.0, "frequency is {frequency}");
+    }
+
+    #[test]
+    fn test_normal_speed_keeps_length_and_pitch() {
+        let samples: Vec<i16> = TimeStretch::new(Sine::new(300.0, 1.0), 1.0).collect();
+        let secs = samples.len() as f32 / SAMPLE_RATE as f32;
+        assert!((secs - 1.0).abs() < 0.1, "stretched to {secs} seconds");
+        let frequency = frequency_of(&samples);
+        assert!((frequency - 300.0).abs() < 20.0, "frequency is {frequency}");
+    }
+}
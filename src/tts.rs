@@ -0,0 +1,194 @@
+/// Text-to-speech support using Korean voices, shared by any
+/// command that wants to speak Hangul out loud.
+use anyhow::Result;
+use tts::{Tts, Voice};
+
+/// Female Korean voices, in order of preference.
+pub const FEMALE_VOICES: [&str; 5] = [
+    "com.apple.voice.premium.ko-KR.Yuna",
+    "com.apple.voice.enhanced.ko-KR.Yuna",
+    "com.apple.voice.compact.ko-KR.Yuna",
+    "com.apple.eloquence.ko-KR.Grandma",
+    "*",
+];
+
+/// Male Korean voices, in order of preference.
+pub const MALE_VOICES: [&str; 4] = [
+    "com.apple.voice.enhanced.ko-KR.Minsu",
+    "com.apple.voice.compact.ko-KR.Minsu",
+    "com.apple.eloquence.ko-KR.Grandpa",
+    "*",
+];
+
+/// Returns the given voices, preceded by the given preferred voice
+/// (if any), so that it's tried first.
+pub fn with_preferred_voice<'a>(voice: &'a Option<String>, voices: &[&'a str]) -> Vec<&'a str> {
+    voice
+        .iter()
+        .map(|voice| voice.as_str())
+        .chain(voices.iter().cloned())
+        .collect()
+}
+
+/// Prints the ID and name of every available Korean voice.
+pub fn list_korean_voices() -> Result<()> {
+    let voices: Vec<Voice> = Tts::default()?
+        .voices()?
+        .into_iter()
+        .filter(|voice| voice.language() == "ko-KR")
+        .collect();
+    if voices.is_empty() {
+        println!("No Korean TTS voices are available.");
+    }
+    for voice in voices {
+        println!("{} ({})", voice.id(), voice.name());
+    }
+    Ok(())
+}
+
+pub trait Speaker {
+    fn speak(&mut self, text: &str) -> Result<()>;
+}
+
+struct StdoutSpeaker {
+    name: String,
+}
+
+impl Speaker for StdoutSpeaker {
+    fn speak(&mut self, text: &str) -> Result<()> {
+        println!("{}: {}", self.name, text);
+        Ok(())
+    }
+}
+
+pub struct TtsSpeaker {
+    name: String,
+    tts: Tts,
+    voice: Voice,
+    rate: f32,
+}
+
+impl TtsSpeaker {
+    /// Speaks the given text without printing it.
+    pub fn say(&mut self, text: &str) -> Result<()> {
+        self.tts.set_rate(self.rate)?;
+        self.tts.set_voice(&self.voice)?;
+        self.tts.speak(text, true)?;
+        #[cfg(target_os = "macos")]
+        {
+            use objc2_foundation::NSDate;
+            let run_loop = objc2_foundation::NSRunLoop::currentRunLoop();
+            loop {
+                let future = NSDate::dateWithTimeIntervalSinceNow(2.0);
+                run_loop.runUntilDate(&future);
+                if !self.tts.is_speaking()? {
+                    break;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Speaker for TtsSpeaker {
+    fn speak(&mut self, text: &str) -> Result<()> {
+        println!("{}: {}", self.name, text);
+        self.say(text)
+    }
+}
+
+/// Creates a speaker that uses the first available Korean voice
+/// out of the given preferred voices, where "*" matches any Korean
+/// voice.
+///
+/// Returns None if TTS isn't supported or no Korean voice is available.
+pub fn create_tts_speaker<T: AsRef<str>>(
+    tts: Option<Tts>,
+    name: String,
+    preferred_voices: &[T],
+    rate: Option<f32>,
+) -> Option<TtsSpeaker> {
+    let tts = tts?;
+    let features = tts.supported_features();
+    if !(features.is_speaking && features.voice && features.rate) {
+        return None;
+    }
+    let voices = tts.voices().ok()?;
+    let voice = preferred_voices.iter().find_map(|preferred_voice| {
+        for voice in &voices {
+            if voice.language() != "ko-KR" {
+                continue;
+            }
+            if preferred_voice.as_ref() == "*" {
+                return Some(voice.clone());
+            }
+            if voice.id() == preferred_voice.as_ref() {
+                return Some(voice.clone());
+            }
+        }
+        None
+    })?;
+    let mut rate = rate.unwrap_or(tts.min_rate());
+    if rate < tts.min_rate() {
+        rate = tts.min_rate();
+    } else if rate > tts.max_rate() {
+        rate = tts.max_rate();
+    }
+    println!(
+        "Initializing TTS voice '{}' at rate {}.",
+        voice.name(),
+        rate
+    );
+    Some(TtsSpeaker {
+        name,
+        tts,
+        voice,
+        rate,
+    })
+}
+
+/// Like `create_tts_speaker`, but falls back to a speaker that
+/// just prints to stdout if no Korean voice is available.
+pub fn create_speaker<T: AsRef<str>>(
+    tts: Option<Tts>,
+    name: String,
+    preferred_voices: &[T],
+    rate: Option<f32>,
+) -> Box<dyn Speaker> {
+    match create_tts_speaker(tts, name.clone(), preferred_voices, rate) {
+        Some(speaker) => Box::new(speaker),
+        None => Box::new(StdoutSpeaker { name }),
+    }
+}
+
+/// Speaks the given text using a Korean voice, if one is available.
+///
+/// If one isn't available, prints a notice and does nothing else.
+pub fn speak_korean(text: &str) -> Result<()> {
+    match create_tts_speaker(
+        Tts::default().ok(),
+        "Speaking".to_owned(),
+        &FEMALE_VOICES,
+        None,
+    ) {
+        Some(mut speaker) => speaker.speak(text),
+        None => {
+            println!("No Korean TTS voice is available, so not speaking.");
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tts::{FEMALE_VOICES, with_preferred_voice};
+
+    #[test]
+    fn test_with_preferred_voice_works() {
+        assert_eq!(with_preferred_voice(&None, &FEMALE_VOICES), FEMALE_VOICES);
+        let voice = Some("my-voice".to_owned());
+        let voices = with_preferred_voice(&voice, &FEMALE_VOICES);
+        assert_eq!(voices[0], "my-voice");
+        assert_eq!(&voices[1..], FEMALE_VOICES);
+    }
+}